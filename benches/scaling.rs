@@ -0,0 +1,226 @@
+/*
+Times the stages of the pipeline over growing site counts and reports the
+peak heap use of each, so the growth rate can be read off directly.
+
+	cargo bench --bench scaling
+	cargo bench --bench scaling -- --max-sites 100000 --budget 5
+
+The growth column is the exponent k in time ~ n^k between one size and the
+previous one: about 1 for the O(n log n) stages, and 2 for anything that has
+gone quadratic. Before each size, the time of every stage is estimated from
+the previous size and its last growth rate (taken as at least 1). Once any
+estimate is over the budget (in seconds), that size and the larger ones of
+the distribution are skipped without being run.
+*/
+
+extern crate rand;
+extern crate voroni;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use rand::{SeedableRng, XorShiftRng};
+use voroni::delaunay;
+use voroni::generate::distributions;
+use voroni::geometry::dcel::DoublyConnectedEdgeList;
+use voroni::geometry::point::Point;
+use voroni::voroni::voroni_process::VoroniProcess;
+
+/////////////////////////////////////////////////////////////////
+
+//Counts the bytes on the heap and the most there have been at once
+struct CountingAllocator;
+
+static CURRENT : AtomicUsize = AtomicUsize::new(0);
+static PEAK : AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout : Layout) -> *mut u8 {
+		let ptr = System.alloc(layout);
+		if !ptr.is_null() {
+			let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+			PEAK.fetch_max(current, Ordering::Relaxed);
+		}
+		ptr
+	}
+
+	unsafe fn dealloc(&self, ptr : *mut u8, layout : Layout) {
+		System.dealloc(ptr, layout);
+		CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+	}
+
+	unsafe fn realloc(&self, ptr : *mut u8, layout : Layout, new_size : usize) -> *mut u8 {
+		let new_ptr = System.realloc(ptr, layout, new_size);
+		if !new_ptr.is_null() {
+			CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+			let current = CURRENT.fetch_add(new_size, Ordering::Relaxed) + new_size;
+			PEAK.fetch_max(current, Ordering::Relaxed);
+		}
+		new_ptr
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR : CountingAllocator = CountingAllocator;
+
+/////////////////////////////////////////////////////////////////
+
+struct Measurement {
+	seconds : f64,
+	peak_bytes : usize,
+}
+
+//Runs `f` once, timing it and tracking the heap it uses beyond what was
+//already allocated
+fn measure<T, F : FnOnce() -> T>(f : F) -> (T, Measurement) {
+	let baseline = CURRENT.load(Ordering::Relaxed);
+	PEAK.store(baseline, Ordering::Relaxed);
+	let start = Instant::now();
+	let out = f();
+	let seconds = start.elapsed().as_secs_f64();
+	let peak_bytes = PEAK.load(Ordering::Relaxed) - baseline;
+	(out, Measurement { seconds, peak_bytes })
+}
+
+/////////////////////////////////////////////////////////////////
+
+type Generator = fn(&mut XorShiftRng, usize) -> Vec<Point>;
+
+fn uniform(rng : &mut XorShiftRng, count : usize) -> Vec<Point> {
+	distributions::uniform_box(rng, count, &Point::new(0.0, 0.0), &Point::new(1000.0, 1000.0))
+}
+
+fn clustered(rng : &mut XorShiftRng, count : usize) -> Vec<Point> {
+	distributions::gaussian_clusters(rng, count, 10, 20.0, &Point::new(0.0, 0.0), &Point::new(1000.0, 1000.0))
+}
+
+fn grid(rng : &mut XorShiftRng, count : usize) -> Vec<Point> {
+	let side = (count as f64).sqrt().ceil() as usize;
+	let mut points = distributions::jittered_grid(rng, side, side, 0.0, &Point::new(0.0, 0.0), &Point::new(side as f64, side as f64));
+	points.truncate(count);
+	points
+}
+
+fn collinear(_ : &mut XorShiftRng, count : usize) -> Vec<Point> {
+	(0..count)
+		.map(|i| Point::new(i as f64, 0.5 * i as f64))
+		.collect()
+}
+
+/////////////////////////////////////////////////////////////////
+
+struct Options {
+	max_sites : usize,
+	budget : f64,
+}
+
+fn parse_options() -> Options {
+	let mut options = Options { max_sites : 1_000_000, budget : 30.0 };
+	let args : Vec<String> = env::args().collect();
+	for pair in args.windows(2) {
+		match pair[0].as_str() {
+			"--max-sites" => options.max_sites = pair[1].parse().expect("--max-sites takes a number"),
+			"--budget" => options.budget = pair[1].parse().expect("--budget takes a number of seconds"),
+			_ => {},
+		}
+	}
+	options
+}
+
+//Builds a DCEL of one vertex per site, chained by twin half-edges, without
+//the sweep, to time the vertex hashing and arena growth on their own
+fn build_dcel(sites : &[Point]) -> DoublyConnectedEdgeList {
+	let mut dcel = DoublyConnectedEdgeList::new();
+	let mut last = None;
+	for site in sites {
+		let vertex = dcel.new_vertex(site);
+		if last.is_some() {
+			dcel.new_dual_edges(last, Some(vertex));
+		}
+		last = Some(vertex);
+	}
+	dcel
+}
+
+struct Row {
+	stage : &'static str,
+	measurement : Measurement,
+}
+
+fn run(sites : &[Point]) -> Vec<Row> {
+	let mut rows = Vec::new();
+
+	let (dcel, measurement) = measure(|| {
+		let mut voroni_process = VoroniProcess::with_data(sites.iter().map(|site| (*site, ())).collect(), 0.0);
+		voroni_process.execute();
+		voroni_process.into_dcel()
+	});
+	rows.push(Row { stage : "sweep", measurement });
+
+	let (_, measurement) = measure(|| build_dcel(sites));
+	rows.push(Row { stage : "dcel", measurement });
+
+	let (_, measurement) = measure(|| delaunay::execute(&dcel));
+	rows.push(Row { stage : "delaunay", measurement });
+
+	let (_, measurement) = measure(|| dcel.get_opengl_vertices());
+	rows.push(Row { stage : "render", measurement });
+
+	rows
+}
+
+fn main() {
+	let options = parse_options();
+	let distributions : Vec<(&str, Generator)> = vec![
+		("uniform", uniform),
+		("clustered", clustered),
+		("grid", grid),
+		("collinear", collinear),
+	];
+	let sizes : Vec<usize> = [1_000, 10_000, 100_000, 1_000_000].iter()
+		.cloned()
+		.filter(|&size| size <= options.max_sites)
+		.collect();
+
+	println!("{:<12} {:>9} {:<9} {:>12} {:>7} {:>11}", "distribution", "sites", "stage", "time (ms)", "growth", "peak (MiB)");
+	for &(name, generate) in &distributions {
+		//The last size, time and growth rate of each stage
+		let mut previous : Vec<(usize, f64, Option<f64>)> = Vec::new();
+		for &size in &sizes {
+			let estimate = previous.iter()
+				.map(|&(last_size, last_seconds, growth)| {
+					last_seconds * (size as f64 / last_size as f64).powf(growth.unwrap_or(1.0).max(1.0))
+				})
+				.fold(0.0, f64::max);
+			if estimate > options.budget {
+				println!("{:<12} skipping {} sites and up, a stage would take about {:.1}s, over {}s",
+					name, size, estimate, options.budget);
+				break;
+			}
+
+			let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+			let sites = generate(&mut rng, size);
+			let rows = run(&sites);
+
+			let mut growths = Vec::with_capacity(rows.len());
+			for (index, row) in rows.iter().enumerate() {
+				let seconds = row.measurement.seconds;
+				let growth = match previous.get(index) {
+					Some(&(last_size, last_seconds, _)) if last_seconds > 0.0 && seconds > 0.0 =>
+						Some((seconds / last_seconds).ln() / (size as f64 / last_size as f64).ln()),
+					_ => None,
+				};
+				println!("{:<12} {:>9} {:<9} {:>12.2} {:>7} {:>11.2}",
+					name, size, row.stage, seconds * 1000.0,
+					growth.map_or(String::from("-"), |growth| format!("{:.2}", growth)),
+					row.measurement.peak_bytes as f64 / (1024.0 * 1024.0));
+				growths.push(growth);
+			}
+			previous = rows.iter().zip(growths)
+				.map(|(row, growth)| (size, row.measurement.seconds, growth))
+				.collect();
+		}
+	}
+}
@@ -0,0 +1,28 @@
+//Writes arbitrary bytes to a site file, reads it back the way the viewer
+//does, then sweeps whatever sites were found.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use std::env;
+use std::fs;
+use std::process;
+
+use voroni::file::io::read_site_file;
+use voroni::voroni::voroni_process::VoroniProcess;
+
+fuzz_target!(|data : &[u8]| {
+	let path = env::temp_dir().join(format!("voroni-fuzz-sites-{}.txt", process::id()));
+	fs::write(&path, data).unwrap();
+	let sites = match read_site_file(&path) {
+		Ok(sites) => sites,
+		//Bytes that are not UTF-8 are rejected, which is the expected outcome
+		Err(_) => return,
+	};
+
+	let mut voroni_process = VoroniProcess::new(sites);
+	voroni_process.execute();
+	if let Err(message) = voroni_process.get_dcel().validate() {
+		panic!("invalid DCEL: {}", message);
+	}
+});
@@ -0,0 +1,25 @@
+//Runs the sweep over the points found in arbitrary text, with and without
+//vertex snapping, and checks that the resulting DCEL is well formed.
+//Coordinates are read as floats, so inputs like `1e300`, `1e-300`, `inf`
+//and `NaN` all reach the sweep.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use voroni::file::io::parse_points;
+use voroni::voroni::voroni_process::VoroniProcess;
+
+fuzz_target!(|data : &[u8]| {
+	let sites : Vec<_> = parse_points(&String::from_utf8_lossy(data))
+		.into_iter()
+		.map(|site| (site, ()))
+		.collect();
+
+	for &tolerance in &[0.0, 1e-9] {
+		let mut voroni_process = VoroniProcess::with_data(sites.clone(), tolerance);
+		voroni_process.execute();
+		if let Err(message) = voroni_process.get_dcel().validate() {
+			panic!("invalid DCEL: {}", message);
+		}
+	}
+});
@@ -1,44 +1,174 @@
-
 use std::collections::HashMap;
 
 use geometry::dcel::*;
 
-struct DelaunayProcess {
-    dcel_in : DoublyConnectedEdgeList,
-    dcel_out : DoublyConnectedEdgeList,
-    vertex_map : HashMap<u32, u32>, //FacePtr to VertexPtr
+/// Builds the Delaunay triangulation as the dual of a finished Voronoi
+/// diagram. Every Voronoi face becomes a vertex at its site, every Voronoi
+/// vertex becomes a face, and every Voronoi half-edge becomes the Delaunay
+/// half-edge that crosses it, under the same index.
+///
+/// The vertices of the output carry the Voronoi face they come from and its
+/// faces carry their Voronoi vertex. Half-edges on the convex hull border
+/// the unbounded face and have no incident face.
+pub struct DelaunayProcess<'a, V : 'a, E : 'a, F : 'a> {
+    dcel_in : &'a DoublyConnectedEdgeList<V, E, F>,
+    dcel_out : DoublyConnectedEdgeList<FacePtr, (), VertexPtr>,
+    vertex_map : HashMap<FacePtr, VertexPtr>,
+    face_map : HashMap<VertexPtr, FacePtr>,
 }
 
-impl DelaunayProcess {
-    pub fn DelaunayProcess(dcel : DoublyConnectedEdgeList) -> DelaunayProcess {
+impl<'a, V, E, F> DelaunayProcess<'a, V, E, F> {
+    pub fn new(dcel : &'a DoublyConnectedEdgeList<V, E, F>) -> DelaunayProcess<'a, V, E, F> {
         DelaunayProcess {
-            dcel_in : dcel
-            dcel_out : DoublyConnectedEdgeList::new();
+            dcel_in : dcel,
+            dcel_out : DoublyConnectedEdgeList::new(),
             vertex_map : HashMap::new(),
+            face_map : HashMap::new(),
         }
     }
 
     pub fn execute(&mut self) {
         self.add_vertices();
+        self.add_edges();
+    }
+
+    pub fn get_dcel(&self) -> &DoublyConnectedEdgeList<FacePtr, (), VertexPtr> {
+        &self.dcel_out
+    }
+
+    pub fn into_dcel(self) -> DoublyConnectedEdgeList<FacePtr, (), VertexPtr> {
+        self.dcel_out
     }
 
-    //Add the vertices of what will be the completed Delaunay Triangulation
+    //Add the vertices of what will be the completed Delaunay Triangulation.
+    //They are not marked as sites, since here they are joined by edges, and
+    //each one's payload already names the face it came from
     fn add_vertices(&mut self) {
-        for (index, face) in &self.dcel_in.faces {
+        for face in &self.dcel_in.faces {
             if let Some(site_ptr) = face.site {
-                let point = *self.dcel_in.get_vertex(site_ptr).point;
-                let out_index = self.dcel_out.new_vertex(point);
-                self.vertex_map.insert(*index, out_index);
+                let point = self.dcel_in[site_ptr].point;
+                let out_index = self.dcel_out.new_vertex_with_data(&point, face.index());
+                self.vertex_map.insert(face.index(), out_index);
             }
         }
     }
 
+    //The face around a Voronoi vertex, created the first time it is needed
+    fn get_face(&mut self, voroni_vertex : VertexPtr) -> FacePtr {
+        if let Some(&face) = self.face_map.get(&voroni_vertex) {
+            return face;
+        }
+        let face = self.dcel_out.new_face_with_data(None, voroni_vertex);
+        self.face_map.insert(voroni_vertex, face);
+        face
+    }
+
     fn add_edges(&mut self) {
-        let key_list = Vec::new();
+        let dcel_in = self.dcel_in;
+
+        //One Delaunay half-edge per Voronoi half-edge, keeping the indices
+        for edge in &dcel_in.edges {
+            let origin = edge.incident_face
+                .and_then(|face| self.vertex_map.get(&face).cloned());
+            self.dcel_out.new_edge(origin);
+        }
+
+        for edge in &dcel_in.edges {
+            /*
+            The Voronoi half-edge runs with its site on the left, so the dual
+            half-edge from that site to the other one has the end of the
+            Voronoi half-edge on its left. The next half-edge around that face
+            starts at the other site and crosses the edge of the other cell
+            that ends at the same Voronoi vertex.
+            */
+            let index = edge.index();
+            let twin = match edge.twin {
+                Some(twin) => twin,
+                None => continue,
+            };
+            self.dcel_out[index].twin = Some(twin);
+
+            if let Some(end) = dcel_in[twin].origin {
+                let face = self.get_face(end);
+                self.dcel_out[index].incident_face = Some(face);
+                if self.dcel_out[face].outer_component.is_empty() {
+                    self.dcel_out[face].outer_component.push(index);
+                }
+                if let Some(next) = dcel_in[twin].prev {
+                    self.dcel_out.link_edges(index, next);
+                }
+            }
+
+            if let Some(origin) = self.dcel_out[index].origin {
+                if self.dcel_out[origin].incident_edge.is_none() {
+                    self.dcel_out[origin].incident_edge = Some(index);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometry::point::Point;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use voroni::geometry::{get_circle_center, get_distance};
+    use voroni::voroni_process::VoroniProcess;
 
-        while !self.dcel_in.edges.is_empty() {
-            //Pop out an twin half edges, read the two adjacent faces to find the sites, and connect the
-            //sites with a dual edge
+    fn triangulate(sites : &[Point]) -> DoublyConnectedEdgeList<FacePtr, (), VertexPtr> {
+        let mut vp = VoroniProcess::with_data(sites.iter().map(|site| (*site, ())).collect(), 0.0);
+        vp.execute();
+        let mut delaunay_process = DelaunayProcess::new(vp.get_dcel());
+        delaunay_process.execute();
+        delaunay_process.into_dcel()
+    }
+
+    #[test]
+    fn test_triangle() {
+        let dcel = triangulate(&[Point::new(0.0, 0.0), Point::new(4.0, 0.0), Point::new(1.0, 3.0)]);
+        assert_eq!(dcel.validate(), Ok(()));
+        assert_eq!(dcel.vertices.len(), 3);
+        assert_eq!(dcel.edges.len(), 6);
+        assert_eq!(dcel.faces.len(), 1);
+
+        //The one triangle runs counter-clockwise
+        let start = dcel.faces[0].outer_component[0];
+        let mut corners = vec![];
+        let mut edge = start;
+        loop {
+            corners.push(dcel[dcel[edge].origin.unwrap()].point);
+            edge = dcel[edge].next.unwrap();
+            if edge == start {
+                break;
+            }
         }
+        assert_eq!(corners.len(), 3);
+        assert!((corners[1] - corners[0]).cross(corners[2] - corners[0]) > 0.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_empty_circumcircles() {
+        let mut rng = XorShiftRng::from_seed([5, 8, 13, 21]);
+        let sites : Vec<Point> = (0..200)
+            .map(|_| Point::new(rng.gen_range(-50.0, 50.0), rng.gen_range(-50.0, 50.0)))
+            .collect();
+        let dcel = triangulate(&sites);
+        assert_eq!(dcel.validate(), Ok(()));
+
+        for face in &dcel.faces {
+            let first = face.outer_component[0];
+            let second = dcel[first].next.unwrap();
+            let third = dcel[second].next.unwrap();
+            assert_eq!(dcel[third].next, Some(first));
+
+            let corner = |edge : EdgePtr| dcel[dcel[edge].origin.unwrap()].point;
+            let triple = (corner(first), corner(second), corner(third));
+            let center = get_circle_center(&triple).unwrap();
+            let radius = get_distance(&center, &triple.0);
+            for site in &sites {
+                assert!(get_distance(&center, site) > radius - 1e-9);
+            }
+        }
+    }
+}
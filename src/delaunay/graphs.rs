@@ -0,0 +1,196 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use delaunay;
+use geometry::dcel::FacePtr;
+use geometry::kd_tree::KdTree;
+use geometry::point::Point;
+use voroni::event::SiteId;
+use voroni::geometry::get_distance;
+use voroni::voroni_process::VoroniProcess;
+
+/*
+Proximity graphs over the sites of a finished diagram. The undirected ones
+are subgraphs of the Delaunay triangulation, so they are found by filtering
+its edges, and are listed as pairs of site ids with the lower id first. The
+nearest neighbour graphs are directed, from each site to its neighbours.
+
+A site that duplicates an earlier one has no face of its own, and is left
+out of every graph.
+*/
+
+/// The edges of the Delaunay triangulation, each once, in order.
+pub fn delaunay_edges<F>(vp : &VoroniProcess<F>) -> Vec<(SiteId, SiteId)> {
+    let face_sites = face_sites(vp);
+    let dcel = delaunay::execute(vp.get_dcel());
+    let mut edges = Vec::new();
+    for edge in &dcel.edges {
+        let end = edge.twin.and_then(|twin| dcel[twin].origin);
+        if let (Some(start), Some(end)) = (edge.origin, end) {
+            let (a, b) = (face_sites[&dcel[start].data], face_sites[&dcel[end].data]);
+            if a < b {
+                edges.push((a, b));
+            }
+        }
+    }
+    edges.sort();
+    edges.dedup();
+    edges
+}
+
+/// The Euclidean minimum spanning tree, by Kruskal's algorithm over the
+/// Delaunay edges, listed from the shortest edge to the longest. Sites in
+/// the same place are not joined, as only one of them is kept.
+pub fn minimum_spanning_tree<F>(vp : &VoroniProcess<F>) -> Vec<(SiteId, SiteId)> {
+    let sites = vp.sites();
+    let mut edges = delaunay_edges(vp);
+    edges.sort_by(|e, f| length(sites, e).partial_cmp(&length(sites, f)).unwrap_or(Ordering::Equal).then(e.cmp(f)));
+
+    let mut parent : Vec<usize> = (0..sites.len()).collect();
+    edges.into_iter()
+        .filter(|&(a, b)| {
+            let (a, b) = (root(&mut parent, a.index()), root(&mut parent, b.index()));
+            parent[a] = b;
+            a != b
+        })
+        .collect()
+}
+
+/// The relative neighbourhood graph: the Delaunay edges with no site closer
+/// to both of their ends than they are to each other.
+pub fn relative_neighborhood_graph<F>(vp : &VoroniProcess<F>) -> Vec<(SiteId, SiteId)> {
+    let sites = vp.sites();
+    let tree = site_tree(vp);
+    delaunay_edges(vp).into_iter()
+        .filter(|edge| {
+            let (a, b) = (sites[edge.0.index()], sites[edge.1.index()]);
+            let reach = get_distance(&a, &b) * (1.0 - 1e-9);
+            !within(&tree, &a, reach).into_iter()
+                .any(|(c, _)| get_distance(&a, &c) < reach && get_distance(&b, &c) < reach)
+        })
+        .collect()
+}
+
+/// The Gabriel graph: the Delaunay edges whose diametral circle holds no
+/// other site.
+pub fn gabriel_graph<F>(vp : &VoroniProcess<F>) -> Vec<(SiteId, SiteId)> {
+    let sites = vp.sites();
+    let tree = site_tree(vp);
+    delaunay_edges(vp).into_iter()
+        .filter(|edge| {
+            let (a, b) = (sites[edge.0.index()], sites[edge.1.index()]);
+            let middle = (a + b) * 0.5;
+            let radius = get_distance(&a, &b) * 0.5 * (1.0 - 1e-9);
+            !within(&tree, &middle, radius).into_iter()
+                .any(|(c, _)| get_distance(&middle, &c) < radius)
+        })
+        .collect()
+}
+
+/// The nearest neighbour graph, with an edge from every site to its nearest
+/// other site.
+pub fn nearest_neighbor_graph<F>(vp : &VoroniProcess<F>) -> Vec<(SiteId, SiteId)> {
+    k_nearest_neighbor_graph(vp, 1)
+}
+
+/// The k-nearest neighbour graph, with edges from every site to its `k`
+/// nearest other sites, nearest first. Sites are taken in order.
+pub fn k_nearest_neighbor_graph<F>(vp : &VoroniProcess<F>, k : usize) -> Vec<(SiteId, SiteId)> {
+    let sites = vp.sites();
+    let tree = site_tree(vp);
+    let mut ids : Vec<SiteId> = face_sites(vp).into_values().collect();
+    ids.sort();
+    let mut edges = Vec::with_capacity(ids.len() * k);
+    for id in ids {
+        let neighbours = tree.nearest_k(&sites[id.index()], k + 1).into_iter()
+            .filter(|&(_, other)| other != id)
+            .take(k);
+        edges.extend(neighbours.map(|(_, other)| (id, other)));
+    }
+    edges
+}
+
+//The site kept for every face, which is the first of any repeats
+fn face_sites<F>(vp : &VoroniProcess<F>) -> HashMap<FacePtr, SiteId> {
+    let mut face_sites = HashMap::new();
+    for (id, _, face) in vp.get_cells() {
+        if let Some(face) = face {
+            face_sites.entry(face).or_insert(id);
+        }
+    }
+    face_sites
+}
+
+fn site_tree<F>(vp : &VoroniProcess<F>) -> KdTree<SiteId> {
+    let sites = vp.sites();
+    KdTree::new(face_sites(vp).into_values().map(|id| (sites[id.index()], id)).collect())
+}
+
+//The sites within `radius` of `p`, and perhaps a few farther ones
+fn within(tree : &KdTree<SiteId>, p : &Point, radius : f64) -> Vec<(Point, SiteId)> {
+    let mut k = 8;
+    loop {
+        let found = tree.nearest_k(p, k);
+        if found.len() < k || get_distance(&found[k - 1].0, p) >= radius {
+            return found;
+        }
+        k *= 2;
+    }
+}
+
+fn length(sites : &[Point], edge : &(SiteId, SiteId)) -> f64 {
+    get_distance(&sites[edge.0.index()], &sites[edge.1.index()])
+}
+
+fn root(parent : &mut [usize], mut i : usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generate::distributions;
+    use rand::{SeedableRng, XorShiftRng};
+
+    #[test]
+    fn test_graphs_nest() {
+        let mut rng = XorShiftRng::from_seed([2, 7, 1, 8]);
+        let sites = distributions::uniform_box(&mut rng, 150, &Point::new(0.0, 0.0), &Point::new(20.0, 20.0));
+        let mut vp = VoroniProcess::with_data(sites.iter().map(|site| (*site, ())).collect(), 0.0);
+        vp.execute();
+
+        //Nearest neighbour graph, tree, relative neighbourhood graph, Gabriel
+        //graph and triangulation each contain the one before
+        let undirected = |edges : Vec<(SiteId, SiteId)>| {
+            let mut edges : Vec<(SiteId, SiteId)> = edges.into_iter().map(|(a, b)| (a.min(b), a.max(b))).collect();
+            edges.sort();
+            edges.dedup();
+            edges
+        };
+        let graphs = [
+            undirected(nearest_neighbor_graph(&vp)),
+            undirected(minimum_spanning_tree(&vp)),
+            relative_neighborhood_graph(&vp),
+            gabriel_graph(&vp),
+            delaunay_edges(&vp),
+        ];
+        for pair in graphs.windows(2) {
+            assert!(pair[0].len() < pair[1].len());
+            assert!(pair[0].iter().all(|edge| pair[1].contains(edge)));
+        }
+        assert_eq!(minimum_spanning_tree(&vp).len(), sites.len() - 1);
+
+        //The k nearest neighbours, checked against every site
+        let knn = k_nearest_neighbor_graph(&vp, 3);
+        assert_eq!(knn.len(), sites.len() * 3);
+        for &(a, b) in &knn {
+            let reach = get_distance(&sites[a.index()], &sites[b.index()]);
+            let closer = sites.iter().filter(|&site| get_distance(&sites[a.index()], site) < reach).count();
+            assert!(closer <= 3);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub mod delaunay_process;
+pub mod graphs;
+pub mod regular;
+
+use geometry::dcel::*;
+
+use self::delaunay_process::DelaunayProcess;
+
+/// The Delaunay triangulation dual to a finished Voronoi diagram.
+pub fn execute<V, E, F>(voroni_dcel : &DoublyConnectedEdgeList<V, E, F>) -> DoublyConnectedEdgeList<FacePtr, (), VertexPtr> {
+    let mut delaunay_process = DelaunayProcess::new(voroni_dcel);
+    delaunay_process.execute();
+    delaunay_process.into_dcel()
+}
@@ -0,0 +1,698 @@
+use std::cmp::Ordering;
+
+use geometry::point::Point;
+
+//Stands in for the vertex at infinity that closes off the convex hull
+const INFINITE : usize = usize::MAX;
+//Marks a triangle that has been merged away
+const DEAD : usize = usize::MAX - 1;
+
+#[derive(Clone, Copy)]
+struct Triangle {
+    //Counter-clockwise, with the infinite vertex, if any, last
+    vertices : [usize; 3],
+    //The triangle across the side opposite each vertex
+    neighbours : [usize; 3],
+}
+
+/// The regular triangulation of weighted points, also called the weighted
+/// Delaunay triangulation: the dual of their power diagram. Each point `p`
+/// with weight `w` is lifted to `|p|^2 - w`, and the triangles are the
+/// faces of the lower convex hull of the lifted points. Points whose lift
+/// lies on or above that hull are redundant: they have no cell in the power
+/// diagram and take no part in the triangulation.
+///
+/// Points are added one at a time, each followed by the flips that make the
+/// triangulation regular again, as by Edelsbrunner and Shah. A point whose
+/// lift is not below the triangle it lands in is left out, and a vertex that
+/// a heavier newcomer leaves inside three triangles, or in the middle of a
+/// straight line between two others, is taken out again. The points are
+/// added in an order that keeps neighbours close, so finding each one is a
+/// short walk, and building the whole triangulation takes about O(n log n)
+/// time for evenly spread points.
+pub struct RegularTriangulation {
+    points : Vec<Point>,
+    lifts : Vec<f64>,
+    triangles : Vec<Triangle>,
+    redundant : Vec<bool>,
+    neighbours : Vec<Vec<usize>>,
+    last : usize,
+}
+
+impl RegularTriangulation {
+    /// Triangulates `sites`, each a point and its weight. Points at the same
+    /// place are settled by weight alone: the heaviest one stays, and
+    /// between equal weights the first one does. Sites with a NaN or
+    /// infinite coordinate or weight are left out, but not counted as
+    /// redundant.
+    pub fn new(sites : &[(Point, f64)]) -> RegularTriangulation {
+        let mut rt = RegularTriangulation {
+            points : sites.iter().map(|&(point, _)| point).collect(),
+            lifts : sites.iter().map(|&(point, weight)| point.dot(point) - weight).collect(),
+            triangles : Vec::new(),
+            redundant : vec![false; sites.len()],
+            neighbours : vec![Vec::new(); sites.len()],
+            last : 0,
+        };
+
+        let mut order : Vec<usize> = (0..sites.len())
+            .filter(|&i| {
+                let (point, weight) = sites[i];
+                point.x().is_finite() && point.y().is_finite() && weight.is_finite()
+            })
+            .collect();
+        order.sort_by(|&i, &j| {
+            let (a, b) = (sites[i], sites[j]);
+            (a.0.x(), a.0.y(), -a.1, i).partial_cmp(&(b.0.x(), b.0.y(), -b.1, j)).unwrap_or(Ordering::Equal)
+        });
+        //Of the sites at one point, the heaviest comes first
+        let mut unique : Vec<usize> = Vec::with_capacity(order.len());
+        for i in order {
+            match unique.last() {
+                Some(&kept) if rt.points[kept] == rt.points[i] => rt.redundant[i] = true,
+                _ => unique.push(i),
+            }
+        }
+
+        let order = rt.spatial_order(&unique);
+        match rt.first_triangle(&order) {
+            Some(first) => {
+                for &i in &order {
+                    if !first.contains(&i) {
+                        rt.insert(i);
+                    }
+                }
+                rt.collect_neighbours();
+            },
+            None => rt.settle_line(&order),
+        }
+        rt
+    }
+
+    /// Whether site `i` has no cell in the power diagram, because other
+    /// sites outweigh it everywhere.
+    pub fn is_redundant(&self, i : usize) -> bool {
+        self.redundant[i]
+    }
+
+    /// The neighbours of site `i` in the triangulation, in increasing order.
+    /// These are the sites whose power cells share a side with the cell of
+    /// `i`, so the half-planes of these sites alone cut out the whole cell.
+    pub fn neighbours(&self, i : usize) -> &[usize] {
+        &self.neighbours[i]
+    }
+
+    /// The triangles, as the counter-clockwise indices of their sites.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        self.triangles.iter()
+            .filter(|triangle| triangle.neighbours[0] != DEAD && !triangle.vertices.contains(&INFINITE))
+            .map(|triangle| triangle.vertices)
+            .collect()
+    }
+
+    //Orders the sites along rows of a grid, snaking back and forth, so each
+    //is near the one before
+    fn spatial_order(&self, sites : &[usize]) -> Vec<usize> {
+        let (mut min, mut max) = (Point::new(f64::INFINITY, f64::INFINITY), Point::new(-f64::INFINITY, -f64::INFINITY));
+        for &i in sites {
+            let p = self.points[i];
+            min = Point::new(min.x().min(p.x()), min.y().min(p.y()));
+            max = Point::new(max.x().max(p.x()), max.y().max(p.y()));
+        }
+        let rows = ((sites.len() as f64).sqrt() / 2.0).ceil().max(1.0);
+        let height = (max.y() - min.y()).max(f64::MIN_POSITIVE);
+        let mut keyed : Vec<(i64, f64, usize)> = sites.iter()
+            .map(|&i| {
+                let p = self.points[i];
+                let row = (((p.y() - min.y()) / height * rows) as i64).min(rows as i64 - 1);
+                let along = if row % 2 == 0 { p.x() } else { -p.x() };
+                (row, along, i)
+            })
+            .collect();
+        keyed.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        keyed.into_iter().map(|(_, _, i)| i).collect()
+    }
+
+    fn orient(&self, a : usize, b : usize, c : usize) -> f64 {
+        (self.points[b] - self.points[a]).cross(self.points[c] - self.points[a])
+    }
+
+    //Positive when the lift of `d` lies below the plane through the lifts
+    //of the counter-clockwise triangle `a`, `b`, `c`
+    fn power_test(&self, a : usize, b : usize, c : usize, d : usize) -> f64 {
+        let p = self.points[d];
+        let row = |i : usize| {
+            let v = self.points[i] - p;
+            (v.x(), v.y(), v.dot(v) + (self.lifts[i] - self.points[i].dot(self.points[i]))
+                - (self.lifts[d] - p.dot(p)))
+        };
+        let (ax, ay, al) = row(a);
+        let (bx, by, bl) = row(b);
+        let (cx, cy, cl) = row(c);
+        ax * (by * cl - bl * cy) - ay * (bx * cl - bl * cx) + al * (bx * cy - by * cx)
+    }
+
+    //Whether the lift of `p`, which lies on the segment from `a` to `b`, is
+    //on or above the lifted segment
+    fn above_segment(&self, a : usize, b : usize, p : usize) -> bool {
+        let side = self.points[b] - self.points[a];
+        let t = (self.points[p] - self.points[a]).dot(side) / side.dot(side);
+        self.lifts[p] >= self.lifts[a] + (self.lifts[b] - self.lifts[a]) * t
+    }
+
+    fn push(&mut self, vertices : [usize; 3], neighbours : [usize; 3]) -> usize {
+        self.triangles.push(Triangle { vertices, neighbours });
+        self.triangles.len() - 1
+    }
+
+    //Points the side of `t` that faced `old` at `new` instead
+    fn relink(&mut self, t : usize, old : usize, new : usize) {
+        for k in 0..3 {
+            if self.triangles[t].neighbours[k] == old {
+                self.triangles[t].neighbours[k] = new;
+                return;
+            }
+        }
+    }
+
+    //Sets a triangle's corners and neighbours, turned so that the infinite
+    //vertex, if any, comes last
+    fn set(&mut self, t : usize, vertices : [usize; 3], neighbours : [usize; 3]) {
+        let shift = vertices.iter().position(|&v| v == INFINITE).map_or(0, |k| (k + 1) % 3);
+        self.triangles[t] = Triangle {
+            vertices : [vertices[shift], vertices[(shift + 1) % 3], vertices[(shift + 2) % 3]],
+            neighbours : [neighbours[shift], neighbours[(shift + 1) % 3], neighbours[(shift + 2) % 3]],
+        };
+    }
+
+    fn index_of(&self, t : usize, vertex : usize) -> usize {
+        self.triangles[t].vertices.iter().position(|&v| v == vertex).unwrap_or(0)
+    }
+
+    fn is_infinite(&self, t : usize) -> bool {
+        self.triangles[t].vertices[2] == INFINITE
+    }
+
+    //Starts from the first three sites that are not on one line, closed off
+    //by three infinite triangles
+    fn first_triangle(&mut self, order : &[usize]) -> Option<[usize; 3]> {
+        let a = *order.first()?;
+        let b = *order.get(1)?;
+        let c = *order[2..].iter().find(|&&c| self.orient(a, b, c) != 0.0)?;
+        let (b, c) = if self.orient(a, b, c) > 0.0 { (b, c) } else { (c, b) };
+        //The triangle and, across each of its sides, an infinite one
+        self.push([a, b, c], [2, 3, 1]);
+        self.push([b, a, INFINITE], [3, 2, 0]);
+        self.push([c, b, INFINITE], [1, 3, 0]);
+        self.push([a, c, INFINITE], [2, 1, 0]);
+        self.last = 0;
+        Some([a, b, c])
+    }
+
+    fn insert(&mut self, p : usize) {
+        let t = self.locate(p);
+        let mut stack = Vec::new();
+        if self.is_infinite(t) {
+            self.insert_outside(p, t, &mut stack);
+        } else {
+            let [a, b, c] = self.triangles[t].vertices;
+            let sides = [self.orient(b, c, p), self.orient(c, a, p), self.orient(a, b, p)];
+            match sides.iter().position(|&side| side == 0.0) {
+                Some(k) => {
+                    let (u, w) = (self.triangles[t].vertices[(k + 1) % 3], self.triangles[t].vertices[(k + 2) % 3]);
+                    if self.above_segment(u, w, p) {
+                        self.redundant[p] = true;
+                        return;
+                    }
+                    self.insert_on_side(p, t, k, &mut stack);
+                },
+                None => {
+                    if self.power_test(a, b, c, p) <= 0.0 {
+                        self.redundant[p] = true;
+                        return;
+                    }
+                    self.insert_inside(p, t, &mut stack);
+                },
+            }
+        }
+        self.flip(p, stack);
+    }
+
+    //Walks from the last triangle touched towards `p`, and returns the
+    //finite triangle that holds it, or the infinite triangle of a side of
+    //the hull that it lies beyond
+    fn locate(&mut self, p : usize) -> usize {
+        let mut t = self.last;
+        let limit = 4 * self.triangles.len() + 16;
+        for step in 0..limit {
+            if self.is_infinite(t) {
+                return t;
+            }
+            let vertices = self.triangles[t].vertices;
+            //Trying the sides in turn from a different one each step keeps
+            //the walk from going round in circles
+            let across = (0..3)
+                .map(|k| (k + step) % 3)
+                .find(|&k| self.orient(vertices[(k + 1) % 3], vertices[(k + 2) % 3], p) < 0.0);
+            match across {
+                Some(k) => t = self.triangles[t].neighbours[k],
+                None => return t,
+            }
+        }
+        //Rounding sent the walk astray, so look at every triangle
+        (0..self.triangles.len())
+            .filter(|&t| self.triangles[t].neighbours[0] != DEAD && !self.is_infinite(t))
+            .find(|&t| {
+                let [a, b, c] = self.triangles[t].vertices;
+                self.orient(b, c, p) >= 0.0 && self.orient(c, a, p) >= 0.0 && self.orient(a, b, p) >= 0.0
+            })
+            .or_else(|| (0..self.triangles.len()).find(|&t| {
+                let [a, b, _] = self.triangles[t].vertices;
+                self.triangles[t].neighbours[0] != DEAD && self.is_infinite(t) && self.orient(a, b, p) > 0.0
+            }))
+            .unwrap_or(self.last)
+    }
+
+    fn insert_inside(&mut self, p : usize, t : usize, stack : &mut Vec<usize>) {
+        let Triangle { vertices : [a, b, c], neighbours : [na, nb, nc] } = self.triangles[t];
+        let t1 = self.push([b, c, p], [0, 0, 0]);
+        let t2 = self.push([c, a, p], [0, 0, 0]);
+        self.set(t, [a, b, p], [t1, t2, nc]);
+        self.set(t1, [b, c, p], [t2, t, na]);
+        self.set(t2, [c, a, p], [t, t1, nb]);
+        self.relink(na, t, t1);
+        self.relink(nb, t, t2);
+        stack.extend_from_slice(&[t, t1, t2]);
+    }
+
+    //Splits `t`, and the triangle across its side opposite corner `k`, at
+    //`p` on that side
+    fn insert_on_side(&mut self, p : usize, t : usize, k : usize, stack : &mut Vec<usize>) {
+        let Triangle { vertices, neighbours } = self.triangles[t];
+        let (v, u, w) = (vertices[k], vertices[(k + 1) % 3], vertices[(k + 2) % 3]);
+        let (n_u, n_w) = (neighbours[(k + 1) % 3], neighbours[(k + 2) % 3]);
+        let other = neighbours[k];
+        let j = (0..3).find(|&j| self.triangles[other].neighbours[j] == t).unwrap_or(0);
+        let Triangle { vertices : other_vertices, neighbours : other_neighbours } = self.triangles[other];
+        let d = other_vertices[j];
+        let (m_w, m_u) = (other_neighbours[(j + 1) % 3], other_neighbours[(j + 2) % 3]);
+
+        let t2 = self.push([v, p, w], [0, 0, 0]);
+        let t4 = self.push([d, p, u], [0, 0, 0]);
+        self.set(t, [v, u, p], [t4, t2, n_w]);
+        self.set(t2, [v, p, w], [other, n_u, t]);
+        self.set(other, [d, w, p], [t2, t4, m_u]);
+        self.set(t4, [d, p, u], [t, m_w, other]);
+        self.relink(n_u, t, t2);
+        self.relink(m_w, other, t4);
+        stack.extend_from_slice(&[t, t2, other, t4]);
+    }
+
+    //Joins `p`, beyond the side of the hull held by the infinite triangle
+    //`t`, to every side of the hull it can see
+    fn insert_outside(&mut self, p : usize, t : usize, stack : &mut Vec<usize>) {
+        let visible = |rt : &RegularTriangulation, t : usize| {
+            let [x, y, _] = rt.triangles[t].vertices;
+            rt.orient(x, y, p) > 0.0
+        };
+        let (mut first, mut last) = (t, t);
+        while visible(self, self.triangles[first].neighbours[1]) && self.triangles[first].neighbours[1] != t {
+            first = self.triangles[first].neighbours[1];
+        }
+        while visible(self, self.triangles[last].neighbours[0]) && self.triangles[last].neighbours[0] != first {
+            last = self.triangles[last].neighbours[0];
+        }
+        let before = self.triangles[first].neighbours[1];
+        let after = self.triangles[last].neighbours[0];
+        let start = self.triangles[first].vertices[0];
+        let end = self.triangles[last].vertices[1];
+
+        let head = self.push([start, p, INFINITE], [0, 0, 0]);
+        let tail = self.push([p, end, INFINITE], [0, 0, 0]);
+        let mut current = first;
+        let mut previous = head;
+        loop {
+            let Triangle { vertices : [x, y, _], neighbours : [next, _, inner] } = self.triangles[current];
+            let following = if current == last { tail } else { next };
+            self.set(current, [x, y, p], [following, previous, inner]);
+            stack.push(current);
+            if current == last {
+                break;
+            }
+            previous = current;
+            current = next;
+        }
+        self.set(head, [start, p, INFINITE], [tail, before, first]);
+        self.set(tail, [p, end, INFINITE], [after, head, last]);
+        self.relink(before, first, head);
+        self.relink(after, last, tail);
+    }
+
+    //Restores regularity around the new vertex `p`, given the triangles
+    //that have it as a corner
+    fn flip(&mut self, p : usize, mut stack : Vec<usize>) {
+        //Rounding could make the flips go round in circles, so they are capped
+        let mut budget = 64 * self.triangles.len() + 1024;
+        while let Some(t) = stack.pop() {
+            if self.triangles[t].neighbours[0] == DEAD || !self.triangles[t].vertices.contains(&p) || budget == 0 {
+                continue;
+            }
+            self.last = t;
+            budget -= 1;
+            let i = self.index_of(t, p);
+            let (a, b) = (self.triangles[t].vertices[(i + 1) % 3], self.triangles[t].vertices[(i + 2) % 3]);
+            if a == INFINITE || b == INFINITE {
+                continue;
+            }
+            let other = self.triangles[t].neighbours[i];
+            if self.is_infinite(other) {
+                continue;
+            }
+            let j = (0..3).find(|&j| self.triangles[other].neighbours[j] == t).unwrap_or(0);
+            let d = self.triangles[other].vertices[j];
+            if self.power_test(p, a, b, d) <= 0.0 {
+                continue;
+            }
+
+            let (side_a, side_b) = (self.orient(p, d, a), self.orient(p, d, b));
+            let replaced = if side_a < 0.0 && side_b > 0.0 {
+                self.flip_two(t, i, other, j);
+                vec![t, other]
+            } else if side_a > 0.0 {
+                //`a` sits inside the triangle `p`, `d`, `b`, and goes if it
+                //has no other neighbours
+                let third = self.triangles[t].neighbours[(i + 2) % 3];
+                if self.triangles[third].vertices.contains(&d) {
+                    self.remove_vertex(a, &[t, other, third], &[[p, d, b]])
+                } else {
+                    Vec::new()
+                }
+            } else if side_b < 0.0 {
+                let third = self.triangles[t].neighbours[(i + 1) % 3];
+                if self.triangles[third].vertices.contains(&d) {
+                    self.remove_vertex(b, &[t, other, third], &[[p, a, d]])
+                } else {
+                    Vec::new()
+                }
+            } else if side_a == 0.0 {
+                //`a` sits on the segment from `p` to `d`, and goes if it has
+                //just one neighbour on the far side of that segment
+                let before = self.triangles[t].neighbours[(i + 2) % 3];
+                let after = self.triangles[other].neighbours[self.index_of(other, b)];
+                match self.shared_apex(before, after, a) {
+                    Some(x) => self.remove_vertex(a, &[t, other, before, after], &[[p, d, b], [d, p, x]]),
+                    None => Vec::new(),
+                }
+            } else {
+                let before = self.triangles[t].neighbours[(i + 1) % 3];
+                let after = self.triangles[other].neighbours[self.index_of(other, a)];
+                match self.shared_apex(before, after, b) {
+                    Some(x) => self.remove_vertex(b, &[t, other, before, after], &[[p, a, d], [p, d, x]]),
+                    None => Vec::new(),
+                }
+            };
+            stack.extend(replaced);
+        }
+    }
+
+    //Replaces the side `a`-`b` shared by `t` = (`p`, `a`, `b`) and `other`
+    //= (`d`, `b`, `a`) with the side from `p` to `d`
+    fn flip_two(&mut self, t : usize, i : usize, other : usize, j : usize) {
+        let Triangle { vertices, neighbours } = self.triangles[t];
+        let (p, a, b) = (vertices[i], vertices[(i + 1) % 3], vertices[(i + 2) % 3]);
+        let (t_a, t_b) = (neighbours[(i + 1) % 3], neighbours[(i + 2) % 3]);
+        let Triangle { vertices : other_vertices, neighbours : other_neighbours } = self.triangles[other];
+        let d = other_vertices[j];
+        let (o_b, o_a) = (other_neighbours[(j + 1) % 3], other_neighbours[(j + 2) % 3]);
+
+        self.set(t, [p, a, d], [o_b, other, t_b]);
+        self.set(other, [p, d, b], [o_a, t_a, t]);
+        self.relink(o_b, other, t);
+        self.relink(t_a, t, other);
+    }
+
+    //The corner that the triangles `before` and `after`, both around
+    //`vertex`, have in common besides it, if there is one. It is the
+    //infinite vertex when `vertex` is on a straight side of the hull.
+    fn shared_apex(&self, before : usize, after : usize, vertex : usize) -> Option<usize> {
+        let corners = self.triangles[after].vertices;
+        self.triangles[before].vertices.iter()
+            .cloned()
+            .find(|&x| x != vertex && corners.contains(&x))
+    }
+
+    //Takes out `vertex` along with the triangles `star` around it, and fills
+    //the hole with the triangles `replacement`, which are returned
+    fn remove_vertex(&mut self, vertex : usize, star : &[usize], replacement : &[[usize; 3]]) -> Vec<usize> {
+        //Each side of the hole, with the triangle beyond it
+        let rim : Vec<(usize, usize, usize)> = star.iter()
+            .map(|&s| {
+                let k = self.index_of(s, vertex);
+                let Triangle { vertices, neighbours } = self.triangles[s];
+                (vertices[(k + 1) % 3], vertices[(k + 2) % 3], neighbours[k])
+            })
+            .collect();
+        let slots = &star[..replacement.len()];
+        for (&slot, corners) in slots.iter().zip(replacement) {
+            let mut neighbours = [DEAD; 3];
+            for k in 0..3 {
+                let (from, to) = (corners[(k + 1) % 3], corners[(k + 2) % 3]);
+                neighbours[k] = match rim.iter().find(|&&(u, w, _)| u == from && w == to) {
+                    Some(&(_, _, outside)) => {
+                        let across = (0..3).find(|&m| {
+                            let vertices = self.triangles[outside].vertices;
+                            vertices[(m + 1) % 3] == to && vertices[(m + 2) % 3] == from
+                        });
+                        if let Some(m) = across {
+                            self.triangles[outside].neighbours[m] = slot;
+                        }
+                        outside
+                    },
+                    None => {
+                        let r = replacement.iter()
+                            .position(|c| (0..3).any(|m| c[(m + 1) % 3] == to && c[(m + 2) % 3] == from))
+                            .unwrap_or(0);
+                        slots[r]
+                    },
+                };
+            }
+            self.set(slot, *corners, neighbours);
+        }
+        for &s in &star[replacement.len()..] {
+            self.triangles[s] = Triangle { vertices : [DEAD; 3], neighbours : [DEAD; 3] };
+        }
+        self.redundant[vertex] = true;
+        slots.to_vec()
+    }
+
+    //The points of a run along a line whose lifts are on the lower hull of
+    //the lifted run, in order. The ends always are.
+    fn lower_chain(&self, run : &[usize]) -> Vec<usize> {
+        let origin = self.points[run[0]];
+        let direction = self.points[run[run.len() - 1]] - origin;
+        let at = |v : usize| ((self.points[v] - origin).dot(direction), self.lifts[v]);
+        let mut chain : Vec<usize> = Vec::with_capacity(run.len());
+        for &v in run {
+            while chain.len() >= 2 {
+                let (a, b) = (at(chain[chain.len() - 2]), at(chain[chain.len() - 1]));
+                let c = at(v);
+                //Drop the middle point unless it lies strictly below the chord
+                if (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0) <= 0.0 {
+                    chain.pop();
+                } else {
+                    break;
+                }
+            }
+            chain.push(v);
+        }
+        chain
+    }
+
+    //With every site on one line, the triangulation is just the chain of
+    //sites whose lifts are on the lower hull
+    fn settle_line(&mut self, order : &[usize]) {
+        let mut run = order.to_vec();
+        if let (Some(&first), Some(&second)) = (run.first(), run.get(1)) {
+            let direction = self.points[second] - self.points[first];
+            let origin = self.points[first];
+            run.sort_by(|&a, &b| {
+                (self.points[a] - origin).dot(direction).partial_cmp(&(self.points[b] - origin).dot(direction)).unwrap_or(Ordering::Equal)
+            });
+        }
+        if run.len() < 2 {
+            return;
+        }
+        let chain = self.lower_chain(&run);
+        for &v in &run {
+            if !chain.contains(&v) {
+                self.redundant[v] = true;
+            }
+        }
+        for pair in chain.windows(2) {
+            self.neighbours[pair[0]].push(pair[1]);
+            self.neighbours[pair[1]].push(pair[0]);
+        }
+    }
+
+    fn collect_neighbours(&mut self) {
+        for triangle in &self.triangles {
+            if triangle.neighbours[0] == DEAD {
+                continue;
+            }
+            for k in 0..3 {
+                let (a, b) = (triangle.vertices[k], triangle.vertices[(k + 1) % 3]);
+                if a != INFINITE && b != INFINITE {
+                    self.neighbours[a].push(b);
+                    self.neighbours[b].push(a);
+                }
+            }
+        }
+        for link in &mut self.neighbours {
+            link.sort();
+            link.dedup();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use generate::distributions;
+    use geometry::polygon;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+
+    //The triangles tile the hull, and no site's lift lies below the plane
+    //of any triangle
+    fn check(sites : &[(Point, f64)]) -> RegularTriangulation {
+        let rt = RegularTriangulation::new(sites);
+        let mut area = 0.0;
+        for [a, b, c] in rt.triangles() {
+            assert!(rt.orient(a, b, c) > 0.0);
+            area += rt.orient(a, b, c) / 2.0;
+            for (d, site) in sites.iter().enumerate() {
+                if [a, b, c].contains(&d) || !site.0.x().is_finite() {
+                    continue;
+                }
+                assert!(rt.power_test(a, b, c, d) <= 1e-6, "{} is below triangle {:?}", d, [a, b, c]);
+            }
+            for &v in &[a, b, c] {
+                assert!(!rt.is_redundant(v));
+            }
+        }
+        let finite : Vec<Point> = sites.iter().map(|site| site.0).filter(|site| site.x().is_finite()).collect();
+        let hull = polygon::signed_area(&polygon::convex_hull(&finite));
+        assert!((area - hull).abs() < 1e-9 * hull.max(1.0));
+        rt
+    }
+
+    #[test]
+    fn test_unweighted_is_delaunay() {
+        let mut rng = XorShiftRng::from_seed([4, 4, 1, 9]);
+        let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+        let sites : Vec<(Point, f64)> = distributions::uniform_box(&mut rng, 300, &min, &max).into_iter()
+            .map(|site| (site, 0.0))
+            .collect();
+        let rt = check(&sites);
+        //Every site is used, and the triangles are 2n - 2 - h in number
+        assert!((0..sites.len()).all(|i| !rt.is_redundant(i)));
+        let hull = polygon::convex_hull(&sites.iter().map(|site| site.0).collect::<Vec<_>>()).len();
+        assert_eq!(rt.triangles().len(), 2 * sites.len() - 2 - hull);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut rng = XorShiftRng::from_seed([7, 1, 7, 1]);
+        let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+        for _ in 0..5 {
+            let sites : Vec<(Point, f64)> = distributions::uniform_box(&mut rng, 200, &min, &max).into_iter()
+                .map(|site| (site, rng.gen_range(0.0, 4.0)))
+                .collect();
+            let rt = check(&sites);
+            //A redundant site has a lift on or above some triangle
+            for d in (0..sites.len()).filter(|&d| rt.is_redundant(d)) {
+                let covered = rt.triangles().iter().any(|&[a, b, c]| {
+                    let inside = rt.orient(a, b, d) >= 0.0 && rt.orient(b, c, d) >= 0.0 && rt.orient(c, a, d) >= 0.0;
+                    inside && rt.power_test(a, b, c, d) <= 1e-9
+                });
+                assert!(covered, "{} is redundant", d);
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_ties() {
+        //Sites on a small grid, with repeats and whole-number weights, are
+        //full of sites on one line and lifts on one plane
+        let mut rng = XorShiftRng::from_seed([2, 0, 2, 6]);
+        for _ in 0..200 {
+            let size = rng.gen_range(2, 8);
+            let count = rng.gen_range(3, 80);
+            let sites : Vec<(Point, f64)> = (0..count)
+                .map(|_| {
+                    let site = Point::new(rng.gen_range(0, size) as f64, rng.gen_range(0, size) as f64);
+                    (site, rng.gen_range(0, 4) as f64)
+                })
+                .collect();
+            let rt = check(&sites);
+            let triangles = rt.triangles();
+            if triangles.is_empty() {
+                continue;
+            }
+            for (i, &(site, _)) in sites.iter().enumerate() {
+                let used = triangles.iter().any(|triangle| triangle.contains(&i));
+                let repeated = sites.iter().enumerate().any(|(j, other)| j != i && other.0 == site);
+                assert!(used != rt.is_redundant(i) || repeated && rt.is_redundant(i), "{} in {:?}", i, sites);
+            }
+        }
+    }
+
+    #[test]
+    fn test_heavy_site_swallows_others() {
+        //The heavy site takes out the two light ones beside it
+        let sites = vec![
+            (Point::new(0.0, 0.0), 0.0),
+            (Point::new(10.0, 0.0), 0.0),
+            (Point::new(5.0, 9.0), 0.0),
+            (Point::new(5.0, 3.0), 0.0),
+            (Point::new(4.0, 4.0), 0.0),
+            (Point::new(5.0, 4.0), 40.0),
+        ];
+        let rt = check(&sites);
+        assert!(rt.is_redundant(3) && rt.is_redundant(4));
+        assert_eq!(rt.neighbours(5), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_lines_and_repeats() {
+        //Sites on one line, a light one in the middle of them
+        let line : Vec<(Point, f64)> = (0..5).map(|i| (Point::new(i as f64, i as f64), if i == 2 { -5.0 } else { 0.0 })).collect();
+        let rt = RegularTriangulation::new(&line);
+        assert!(rt.triangles().is_empty());
+        assert!(rt.is_redundant(2));
+        assert_eq!(rt.neighbours(1), &[0, 3]);
+        assert_eq!(rt.neighbours(0), &[1]);
+
+        //A light site in the middle of a straight side of the hull
+        let sites = vec![
+            (Point::new(0.0, 0.0), 0.0),
+            (Point::new(4.0, 0.0), 0.0),
+            (Point::new(2.0, 0.0), -5.0),
+            (Point::new(2.0, 5.0), 0.0),
+        ];
+        let rt = check(&sites);
+        assert!(rt.is_redundant(2));
+        assert!(rt.neighbours(0).contains(&1));
+
+        //The heavier of two sites at one point stays, and NaN is left out
+        let sites = vec![
+            (Point::new(0.0, 0.0), 0.0),
+            (Point::new(1.0, 0.0), 0.0),
+            (Point::new(0.0, 1.0), 1.0),
+            (Point::new(0.0, 1.0), 2.0),
+            (Point::new(f64::NAN, 1.0), 0.0),
+        ];
+        let rt = check(&sites);
+        assert!(rt.is_redundant(2) && !rt.is_redundant(3) && !rt.is_redundant(4));
+        assert_eq!(rt.triangles().len(), 1);
+    }
+}
@@ -0,0 +1,27 @@
+use std::fmt::Write;
+
+use geometry::point::Point;
+use voroni::event::SiteId;
+
+/// Writes a graph over sites in the DOT language of Graphviz. Every site
+/// named by an edge becomes a node pinned at its position, so `neato -n`
+/// lays the graph out as it lies in the plane. Edges are drawn with arrows
+/// when `directed` is set.
+pub fn format_dot(sites : &[Point], edges : &[(SiteId, SiteId)], directed : bool) -> String {
+    let (keyword, connector) = if directed { ("digraph", "->") } else { ("graph", "--") };
+    let mut nodes : Vec<SiteId> = edges.iter().flat_map(|&(a, b)| Some(a).into_iter().chain(Some(b))).collect();
+    nodes.sort();
+    nodes.dedup();
+
+    let mut out = String::new();
+    writeln!(out, "{} proximity {{", keyword).unwrap();
+    for node in nodes {
+        let site = sites[node.index()];
+        writeln!(out, "    {} [pos=\"{},{}!\"];", node, site.x(), site.y()).unwrap();
+    }
+    for &(a, b) in edges {
+        writeln!(out, "    {} {} {};", a, connector, b).unwrap();
+    }
+    out.push_str("}\n");
+    out
+}
@@ -0,0 +1,191 @@
+use std::fmt::Write;
+
+use geometry::geo::{GeoPoint, Vector3};
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::event::SiteId;
+use voroni::spherical::SphericalDiagram;
+
+/// Writes the cells of a spherical diagram as a GeoJSON feature collection,
+/// one feature per site in input order, with the site and the area of its
+/// cell in km² as properties. Sites without a cell get a null geometry.
+///
+/// GeoJSON joins coordinates with straight lines in longitude and latitude,
+/// so each side is broken into great-circle steps of at most `step` degrees.
+/// Cells that cross the antimeridian are split along it into a
+/// `MultiPolygon`, and cells around a pole are closed along the pole, so
+/// every longitude stays within [-180, 180].
+pub fn format_geojson(diagram : &SphericalDiagram, step : f64) -> String {
+    let mut out = String::from("{\"type\":\"FeatureCollection\",\"features\":[");
+    for (index, site) in diagram.sites.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        let id = SiteId(index as u32);
+        let cell = &diagram.cells[index];
+        let area = if cell.is_empty() { 0.0 } else { diagram.area_km2(id) };
+        write!(out, "{{\"type\":\"Feature\",\"properties\":{{\"site\":{},\"lat\":{},\"lon\":{},\"area_km2\":{}}},\"geometry\":",
+            id, number(site.lat), number(site.lon), area).unwrap();
+
+        let pieces = if cell.is_empty() { Vec::new() } else { split_at_antimeridian(cell, step) };
+        let rings : Vec<String> = pieces.iter().map(|piece| format_ring(piece)).collect();
+        match rings.len() {
+            0 => out.push_str("null"),
+            1 => write!(out, "{{\"type\":\"Polygon\",\"coordinates\":[{}]}}", rings[0]).unwrap(),
+            _ => write!(out, "{{\"type\":\"MultiPolygon\",\"coordinates\":[[{}]]}}", rings.join("],[")).unwrap(),
+        }
+        out.push('}');
+    }
+    out.push_str("]}\n");
+    out
+}
+
+//JSON has no NaN or infinity
+fn number(value : f64) -> String {
+    if value.is_finite() { format!("{}", value) } else { String::from("null") }
+}
+
+//A closed ring of [lon, lat] pairs
+fn format_ring(ring : &[Point]) -> String {
+    let corners : Vec<String> = ring.iter().chain(ring.first())
+        .map(|p| format!("[{},{}]", p.x(), p.y()))
+        .collect();
+    format!("[{}]", corners.join(","))
+}
+
+/// Turns a cell into counter-clockwise polygons of (longitude, latitude)
+/// points, with every longitude in [-180, 180].
+pub fn split_at_antimeridian(cell : &[GeoPoint], step : f64) -> Vec<Vec<Point>> {
+    //Follow the great circles, letting the longitude run past ±180
+    let step = step.max(1e-3).to_radians();
+    let mut path : Vec<Point> = Vec::new();
+    let mut lon = cell[0].lon;
+    for i in 0..cell.len() {
+        let (a, b) = (cell[i].to_vector(), cell[(i + 1) % cell.len()].to_vector());
+        let steps = (a.angle_to(&b) / step).ceil().max(1.0) as usize;
+        for k in 0..steps {
+            let point = GeoPoint::from_vector(&slerp(&a, &b, k as f64 / steps as f64));
+            if !path.is_empty() {
+                lon += wrap_turn(point.lon - lon);
+            }
+            path.push(Point::new(lon, point.lat));
+        }
+    }
+    //Closing the ring brings the longitude back where it started, or a turn over
+    let winding = lon + wrap_turn(cell[0].lon - lon) - path[0].x();
+
+    //A cell round a pole comes back a full turn over. Start it where it
+    //crosses the antimeridian, so it spans exactly one turn, and close it
+    //along the pole
+    if winding.abs() > 180.0 {
+        let n = path.len();
+        let around = |i : usize| {
+            let p = path[i % n];
+            Point::new(p.x() + if i >= n { winding } else { 0.0 }, p.y())
+        };
+        let turn_of = |x : f64| ((x + 180.0) / 360.0).floor();
+        let i = (0..n).find(|&i| turn_of(around(i).x()) != turn_of(around(i + 1).x())).unwrap_or(0);
+        let (a, b) = (around(i), around(i + 1));
+        let meridian = 360.0 * turn_of(a.x()).max(turn_of(b.x())) - 180.0;
+        let t = if b.x() == a.x() { 0.0 } else { (meridian - a.x()) / (b.x() - a.x()) };
+        let crossing = Point::new(meridian, a.y() + (b.y() - a.y()) * t);
+
+        let shift = if winding > 0.0 { -180.0 - meridian } else { 180.0 - meridian };
+        let mut rotated = vec![crossing];
+        rotated.extend((i + 1..i + 1 + n).map(around));
+        rotated.push(Point::new(crossing.x() + winding, crossing.y()));
+        let pole = if winding > 0.0 { 90.0 } else { -90.0 };
+        let (start, end) = (crossing.x(), crossing.x() + winding);
+        rotated.push(Point::new(end, pole));
+        rotated.push(Point::new(start, pole));
+        path = rotated.into_iter().map(|p| Point::new(p.x() + shift, p.y())).collect();
+    }
+
+    let (low, high) = path.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), p| (low.min(p.x()), high.max(p.x())));
+    let mut pieces = Vec::new();
+    let first = ((low + 180.0) / 360.0).floor() as i64;
+    let last = ((high + 180.0) / 360.0).floor() as i64;
+    for turn in first..=last {
+        let shift = 360.0 * turn as f64;
+        let piece = polygon::clip_half_plane(&path, Point::new(-1.0, 0.0), 180.0 - shift);
+        let piece = polygon::clip_half_plane(&piece, Point::new(1.0, 0.0), 180.0 + shift);
+        if polygon::signed_area(&piece) > 1e-12 {
+            pieces.push(piece.iter().map(|p| Point::new(p.x() - shift, p.y())).collect());
+        }
+    }
+    pieces
+}
+
+//The change in longitude the same as `turn`, within half a turn
+fn wrap_turn(turn : f64) -> f64 {
+    (turn % 360.0 + 540.0) % 360.0 - 180.0
+}
+
+//The point a fraction `t` of the way along the great circle from `a` to `b`
+fn slerp(a : &Vector3, b : &Vector3, t : f64) -> Vector3 {
+    let angle = a.angle_to(b);
+    if angle < 1e-12 {
+        return *a;
+    }
+    (*a * ((1.0 - t) * angle).sin() + *b * (t * angle).sin()) * (1.0 / angle.sin())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng, XorShiftRng};
+    use voroni::spherical::spherical_voronoi;
+
+    #[test]
+    fn test_split_at_antimeridian() {
+        let sites = vec![
+            GeoPoint::new(90.0, 0.0), GeoPoint::new(-90.0, 0.0),
+            GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 90.0), GeoPoint::new(0.0, 180.0), GeoPoint::new(0.0, -90.0),
+        ];
+        let diagram = spherical_voronoi(&sites);
+
+        //The cell on the antimeridian falls in two, and the polar cells
+        //reach their poles
+        let cut = split_at_antimeridian(&diagram.cells[4], 1.0);
+        assert_eq!(cut.len(), 2);
+        for (index, pole) in &[(0, 90.0), (1, -90.0)] {
+            let pieces = split_at_antimeridian(&diagram.cells[*index], 1.0);
+            assert_eq!(pieces.len(), 1);
+            assert!(pieces[0].iter().any(|p| p.y() == *pole));
+        }
+
+        //Every cell is as wide as a quarter turn in total, however it is cut
+        for index in 2..6 {
+            let pieces = split_at_antimeridian(&diagram.cells[index], 1.0);
+            let width : f64 = pieces.iter()
+                .map(|piece| {
+                    assert!(piece.iter().all(|p| p.x() >= -180.0 && p.x() <= 180.0));
+                    let (low, high) = piece.iter().fold((180.0f64, -180.0f64), |(l, h), p| (l.min(p.x()), h.max(p.x())));
+                    high - low
+                })
+                .sum();
+            assert!((width - 90.0).abs() < 1e-9);
+        }
+
+        let json = format_geojson(&diagram, 1.0);
+        assert!(json.starts_with("{\"type\":\"FeatureCollection\""));
+        assert_eq!(json.matches("\"MultiPolygon\"").count(), 1);
+        assert_eq!(json.matches("\"Polygon\"").count(), 5);
+    }
+
+    #[test]
+    fn test_pieces_tile_the_map() {
+        let mut rng = XorShiftRng::from_seed([5, 1, 5, 1]);
+        for &count in &[4, 20, 200] {
+            let sites : Vec<GeoPoint> = (0..count)
+                .map(|_| GeoPoint::new(rng.gen_range(-1.0f64, 1.0).asin().to_degrees(), rng.gen_range(-180.0, 180.0)))
+                .collect();
+            let diagram = spherical_voronoi(&sites);
+            let total : f64 = diagram.cells.iter()
+                .flat_map(|cell| split_at_antimeridian(cell, 0.5))
+                .map(|piece| polygon::signed_area(&piece))
+                .sum();
+            assert!((total - 360.0 * 180.0).abs() < 1e-6, "pieces cover {}", total);
+        }
+    }
+}
@@ -1,52 +1,174 @@
 
 
+use std::collections::HashMap;
 use std::fs::File;
+use std::io;
 use std::io::prelude::*;
 use std::path::Path;
+use std::str::FromStr;
 
-pub fn read_site_file(path : &Path) -> Vec<(i32, i32)> {
-    
-    let mut file = match File::open(path) {
-        Ok(file) => file,
-        Err(_) => panic!("Couldn't open file!"), 
-    };
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::event::SiteId;
+use voroni::geometry::get_distance;
+use voroni::kth_order::KthOrderDiagram;
+use voroni::shapes::SiteShape;
+use voroni::voroni_process::VoroniProcess;
 
+/// Reads a file of integer sites, such as `(1, 2) (3, 4)` or `1 2 3 4`.
+pub fn read_site_file(path : &Path) -> io::Result<Vec<(i32, i32)>> {
     let mut string = String::new();
-    match file.read_to_string(&mut string) {
-        Err(_) => panic!("Couldn't read file!"),
-        _ => (),
-    };
+    File::open(path)?.read_to_string(&mut string)?;
+    Ok(parse_sites(&string))
+}
 
-    let data : Vec<i32> = string.replace("(","")
+/// Parses integer sites from text. Parentheses and commas are treated as
+/// whitespace, anything that is not an integer is skipped, and a trailing
+/// unpaired number is ignored.
+pub fn parse_sites(string : &str) -> Vec<(i32, i32)> {
+    pair_up(parse_numbers(string))
+}
+
+/// Like `parse_sites`, but accepts any floating point coordinates,
+/// including `inf` and `NaN`.
+pub fn parse_points(string : &str) -> Vec<Point> {
+    pair_up(parse_numbers::<f64>(string)).into_iter()
+        .map(|(x, y)| Point::new(x, y))
+        .collect()
+}
+
+/// Reads a file of point, segment and polygon sites. See `parse_shapes`.
+/// A malformed shape is reported as `InvalidData`.
+pub fn read_shape_file(path : &Path) -> io::Result<Vec<SiteShape>> {
+    let mut string = String::new();
+    File::open(path)?.read_to_string(&mut string)?;
+    parse_shapes(&string).map_err(|message| io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+/// Parses sites of any shape from text, a line at a time. A line starting
+/// with `segment` holds the two ends of a segment, and a line starting with
+/// `polygon` holds the corners of a polygon ring, which must have at least
+/// three. Any other line holds points, as read by `parse_points`:
+///
+/// ```text
+/// (1, 2) (3, 4)
+/// segment (0, 0) (5, 1)
+/// polygon (6, 6) (9, 6) (8, 9)
+/// ```
+///
+/// A segment without exactly two ends, or a polygon with fewer than three
+/// corners, is an error naming its line.
+pub fn parse_shapes(string : &str) -> Result<Vec<SiteShape>, String> {
+    let mut shapes = Vec::new();
+    for (number, line) in string.lines().enumerate() {
+        let line = line.trim();
+        let lower = line.to_lowercase();
+        if lower.starts_with("segment") {
+            let ends = parse_points(&line["segment".len()..]);
+            if ends.len() != 2 {
+                return Err(format!("line {}: a segment needs 2 ends, not {}", number + 1, ends.len()));
+            }
+            shapes.push(SiteShape::Segment(ends[0], ends[1]));
+        } else if lower.starts_with("polygon") {
+            let ring = parse_points(&line["polygon".len()..]);
+            if ring.len() < 3 {
+                return Err(format!("line {}: a polygon needs at least 3 corners, not {}", number + 1, ring.len()));
+            }
+            shapes.push(SiteShape::Polygon(ring));
+        } else {
+            shapes.extend(parse_points(line).into_iter().map(SiteShape::Point));
+        }
+    }
+    Ok(shapes)
+}
+
+fn parse_numbers<T : FromStr>(string : &str) -> Vec<T> {
+    string.replace("(","")
         .replace(")","")
         .replace(","," ")
         .split_whitespace() //split the file by whitespace
         .filter_map( //execute lambdas on each split string part
-            |s| match s.trim().parse() { //trim the string, parse the number
-                Ok(t) => Some(t), //if parsing succeeds, return the number
-                Err(e) => None,}) //if not, return nothing
-        .collect(); //transform the filtermap to a vec
-
-    let mut sites = Vec::new();
-    for i in 0..data.len()/2 {
-        sites.push((
-            data[i*2],
-            data[i*2 + 1],
-        ));
-    }
+            |s| s.trim().parse().ok()) //trim the string, parse the number, keep it if parsing succeeds
+        .collect() //transform the filtermap to a vec
+}
 
-    sites
+fn pair_up<T : Copy>(data : Vec<T>) -> Vec<(T, T)> {
+    data.chunks(2)
+        .filter(|pair| pair.len() == 2)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
 }
 
 pub fn write_output_file(content : String) {
-    let path = Path::new("output.txt");
-    let mut file = match File::create(&path) {
-        Err(why) => panic!("Oh no!"),
+    write_file(Path::new("output.txt"), content);
+}
+
+/// Lists the cells of a finished diagram as CSV, one row per input site in
+/// input order. Duplicate sites share the face of the site they duplicate,
+/// which is named in the last column.
+pub fn format_cells<F>(voroni_process : &VoroniProcess<F>) -> String {
+    let mut out = String::from("site,x,y,face,duplicate_of\n");
+    let duplicates : HashMap<SiteId, SiteId> = voroni_process.get_duplicates().iter().cloned().collect();
+    for (site_id, point, face) in voroni_process.get_cells() {
+        let face = match face {
+            Some(face_ptr) => format!("{}", face_ptr),
+            None => String::new(),
+        };
+        let original = match duplicates.get(&site_id) {
+            Some(original) => format!("{}", original),
+            None => String::new(),
+        };
+        out.push_str(format!("{},{},{},{},{}\n", site_id, point.x(), point.y(), face, original).as_str());
+    }
+    out
+}
+
+/// Lists the cells of a k-th order diagram as CSV, one row per face, with
+/// its nearest sites separated by spaces. For order 2, each row is a region
+/// where the same two sites are nearest, so either falls back on the other.
+pub fn format_kth_order_cells(diagram : &KthOrderDiagram) -> String {
+    let mut out = String::from("face,sites,area\n");
+    for (face, cell) in diagram.dcel.faces.iter().zip(&diagram.cells) {
+        let sites : Vec<String> = face.data.iter().map(|site| format!("{}", site)).collect();
+        out.push_str(format!("{},{},{}\n", face.index(), sites.join(" "), polygon::signed_area(cell)).as_str());
+    }
+    out
+}
+
+/// Lists the edges of a graph over sites as CSV, one row per edge, with
+/// the length of each.
+pub fn format_edges(sites : &[Point], edges : &[(SiteId, SiteId)]) -> String {
+    let mut out = String::from("from,to,length\n");
+    for &(a, b) in edges {
+        let length = get_distance(&sites[a.index()], &sites[b.index()]);
+        out.push_str(format!("{},{},{}\n", a, b, length).as_str());
+    }
+    out
+}
+
+pub fn write_file(path : &Path, content : String) {
+    let mut file = match File::create(path) {
+        Err(_) => panic!("Oh no!"),
         Ok(file) => file,
     };
 
-    match file.write_all(content.as_bytes()) {
-        Err(why) => panic!("Double oh no!"),
-        Ok(_) => (),
+    if file.write_all(content.as_bytes()).is_err() {
+        panic!("Double oh no!");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shapes() {
+        let shapes = parse_shapes("(1, 2) (3, 4)\nsegment (0, 0) (5, 1)\n\npolygon (6, 6) (9, 6) (8, 9)").unwrap();
+        assert_eq!(shapes.len(), 4);
+        assert_eq!(shapes[2], SiteShape::Segment(Point::new(0.0, 0.0), Point::new(5.0, 1.0)));
+
+        assert_eq!(parse_shapes("(1, 2)\nsegment (0, 0)"), Err(String::from("line 2: a segment needs 2 ends, not 1")));
+        assert!(parse_shapes("segment (0, 0) (1, 1) (2, 2)").is_err());
+        assert_eq!(parse_shapes("polygon (6, 6) (9, 6)"), Err(String::from("line 1: a polygon needs at least 3 corners, not 2")));
     }
-}
\ No newline at end of file
+}
@@ -1,2 +1,5 @@
-
-pub mod io;
\ No newline at end of file
+pub mod dot;
+pub mod geojson;
+pub mod io;
+pub mod raster;
+pub mod svg;
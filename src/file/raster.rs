@@ -0,0 +1,76 @@
+use std::fmt::Write;
+
+use geometry::point::Point;
+
+/// The value written for a cell with no data.
+pub const NO_DATA : f64 = -9999.0;
+
+/// A grid of square cells over the plane, each holding a value or nothing.
+/// Rows run from the bottom up, and every value is taken at the centre of
+/// its cell.
+#[derive(Clone, Debug)]
+pub struct Raster {
+    /// The lower left corner of the grid.
+    pub min : Point,
+    pub cell_size : f64,
+    pub columns : usize,
+    pub rows : usize,
+    /// The values row by row, starting at the bottom left.
+    pub values : Vec<Option<f64>>,
+}
+
+impl Raster {
+    /// Fills a grid by calling `surface` at the centre of every cell.
+    pub fn sample<S : Fn(&Point) -> Option<f64>>(min : &Point, cell_size : f64, columns : usize, rows : usize, surface : S) -> Raster {
+        let mut values = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let centre = *min + Point::new(column as f64 + 0.5, row as f64 + 0.5) * cell_size;
+                values.push(surface(&centre));
+            }
+        }
+        Raster { min : *min, cell_size, columns, rows, values }
+    }
+
+    pub fn get(&self, column : usize, row : usize) -> Option<f64> {
+        self.values[row * self.columns + column]
+    }
+}
+
+/// Writes a raster as an Esri ASCII grid, with `NO_DATA` for empty cells.
+pub fn format_ascii_grid(raster : &Raster) -> String {
+    let mut out = String::new();
+    writeln!(out, "ncols {}", raster.columns).unwrap();
+    writeln!(out, "nrows {}", raster.rows).unwrap();
+    writeln!(out, "xllcorner {}", raster.min.x()).unwrap();
+    writeln!(out, "yllcorner {}", raster.min.y()).unwrap();
+    writeln!(out, "cellsize {}", raster.cell_size).unwrap();
+    writeln!(out, "NODATA_value {}", NO_DATA).unwrap();
+    //The format lists the top row first
+    for row in (0..raster.rows).rev() {
+        let line : Vec<String> = (0..raster.columns)
+            .map(|column| format!("{}", raster.get(column, row).unwrap_or(NO_DATA)))
+            .collect();
+        writeln!(out, "{}", line.join(" ")).unwrap();
+    }
+    out
+}
+
+/// Writes a raster as a plain (P2) PGM image, stretching the values over
+/// grey levels 1 to 255 and leaving empty cells black.
+pub fn format_pgm(raster : &Raster) -> String {
+    let present : Vec<f64> = raster.values.iter().filter_map(|value| *value).collect();
+    let low = present.iter().cloned().fold(f64::INFINITY, f64::min);
+    let high = present.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let level = |value : f64| if high > low { 1 + ((value - low) / (high - low) * 254.0).round() as u32 } else { 128 };
+
+    let mut out = String::new();
+    writeln!(out, "P2\n{} {}\n255", raster.columns, raster.rows).unwrap();
+    for row in (0..raster.rows).rev() {
+        let line : Vec<String> = (0..raster.columns)
+            .map(|column| format!("{}", raster.get(column, row).map_or(0, level)))
+            .collect();
+        writeln!(out, "{}", line.join(" ")).unwrap();
+    }
+    out
+}
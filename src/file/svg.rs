@@ -0,0 +1,111 @@
+use std::fmt::Write;
+
+use geometry::conic::ConicArc;
+use voroni::apollonius::{ApolloniusDiagram, CurvedEdge};
+
+//Halvings of any one arc before it is drawn as it stands
+const MAX_HALVINGS : u32 = 16;
+
+/// Draws an Apollonius diagram as SVG. Each edge is drawn from its exact
+/// arcs. SVG has no rational curves, so a hyperbolic arc is halved until
+/// the quadratic Bézier on its control points strays from it by a tenth of
+/// the stroke width, while straight and parabolic arcs are drawn as they
+/// are. The exact arcs of each edge are also kept in its `data-conic`
+/// attribute, as `x0 y0 x1 y1 x2 y2 w` for the start, control point, end
+/// and weight of each, separated by commas. Sites are drawn as circles the
+/// size of their weight.
+pub fn format_apollonius_svg(diagram : &ApolloniusDiagram) -> String {
+    let (min, max) = (diagram.get_min(), diagram.get_max());
+    let size = max - min;
+    let stroke = size.x().max(size.y()) / 500.0;
+
+    let mut out = String::new();
+    writeln!(out, "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+        min.x(), min.y(), size.x(), size.y()).unwrap();
+    //SVG puts y downwards, so flip it about the middle of the box
+    writeln!(out, "<g transform=\"matrix(1 0 0 -1 0 {})\" fill=\"none\" stroke=\"black\" stroke-width=\"{}\">",
+        min.y() + max.y(), stroke).unwrap();
+    writeln!(out, "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>",
+        min.x(), min.y(), size.x(), size.y()).unwrap();
+    for edge in &diagram.edges {
+        writeln!(out, "<path d=\"{}\" data-conic=\"{}\"/>", edge_path(edge, stroke / 10.0), edge_conics(edge)).unwrap();
+    }
+    for (index, &(site, weight)) in diagram.sites.iter().enumerate() {
+        let colour = if diagram.empty_cells.iter().any(|id| id.index() == index) { "grey" } else { "red" };
+        if weight > 0.0 {
+            writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" stroke=\"{}\"/>",
+                site.x(), site.y(), weight, colour).unwrap();
+        }
+        writeln!(out, "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\" stroke=\"none\"/>",
+            site.x(), site.y(), stroke * 2.0, colour).unwrap();
+    }
+    out.push_str("</g>\n</svg>\n");
+    out
+}
+
+fn edge_path(edge : &CurvedEdge, tolerance : f64) -> String {
+    let start = edge.arcs[0].start;
+    let mut path = format!("M {} {}", start.x(), start.y());
+    for arc in &edge.arcs {
+        push_quadratic(&mut path, arc, tolerance, 0);
+    }
+    path
+}
+
+//Draws `arc` as quadratic Béziers, halving it while the Bézier on its
+//control points and the arc part by more than `tolerance` at their middles
+fn push_quadratic(path : &mut String, arc : &ConicArc, tolerance : f64, depth : u32) {
+    let quadratic = (arc.start + arc.control * 2.0 + arc.end) * 0.25;
+    let gap = quadratic - arc.point_at(0.5);
+    if gap.dot(gap).sqrt() > tolerance && depth < MAX_HALVINGS {
+        let (first, second) = arc.halves();
+        push_quadratic(path, &first, tolerance, depth + 1);
+        push_quadratic(path, &second, tolerance, depth + 1);
+    } else {
+        write!(path, " Q {} {} {} {}", arc.control.x(), arc.control.y(), arc.end.x(), arc.end.y()).unwrap();
+    }
+}
+
+fn edge_conics(edge : &CurvedEdge) -> String {
+    edge.arcs.iter()
+        .map(|arc| format!("{} {} {} {} {} {} {}", arc.start.x(), arc.start.y(),
+            arc.control.x(), arc.control.y(), arc.end.x(), arc.end.y(), arc.weight))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometry::bound::Bound;
+    use geometry::point::Point;
+    use voroni::apollonius::apollonius_diagram;
+
+    #[test]
+    fn test_curves_are_quadratic() {
+        let sites = vec![(Point::new(2.0, 5.0), 0.0), (Point::new(7.0, 5.0), 2.0)];
+        let bound = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+        let svg = format_apollonius_svg(&apollonius_diagram(&sites, &bound, 1e-3));
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(" Q "));
+        assert_eq!(svg.matches("<path").count(), 1);
+
+        //The arcs kept with the path lie on the hyperbola, all the way along
+        let conics = svg.split("data-conic=\"").nth(1).unwrap().split('"').next().unwrap();
+        for conic in conics.split(',') {
+            let numbers : Vec<f64> = conic.split(' ').map(|number| number.parse().unwrap()).collect();
+            let arc = ConicArc {
+                start : Point::new(numbers[0], numbers[1]),
+                control : Point::new(numbers[2], numbers[3]),
+                end : Point::new(numbers[4], numbers[5]),
+                weight : numbers[6],
+            };
+            assert!(arc.weight > 1.0);
+            for k in 0..11 {
+                let p = arc.point_at(k as f64 / 10.0);
+                let gap = (p - sites[0].0).dot(p - sites[0].0).sqrt() - ((p - sites[1].0).dot(p - sites[1].0).sqrt() - 2.0);
+                assert!(gap.abs() < 1e-9);
+            }
+        }
+    }
+}
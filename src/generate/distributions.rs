@@ -0,0 +1,238 @@
+/*
+Site generators for common point distributions. The random ones draw from the
+RNG they are given, so seeding it (for example with
+`XorShiftRng::from_seed`) makes the output repeatable.
+*/
+
+use std::f64::consts::PI;
+
+use rand::Rng;
+use rand::distributions::{IndependentSample, Normal};
+
+use geometry::point::Point;
+
+/// Points spread uniformly over the box spanned by `min` and `max`.
+pub fn uniform_box<R : Rng>(rng : &mut R, count : usize, min : &Point, max : &Point) -> Vec<Point> {
+	(0..count)
+		.map(|_| Point::new(
+			min.x() + rng.gen::<f64>() * (max.x() - min.x()),
+			min.y() + rng.gen::<f64>() * (max.y() - min.y())))
+		.collect()
+}
+
+/// Points spread uniformly over a disk.
+pub fn uniform_disk<R : Rng>(rng : &mut R, count : usize, center : &Point, radius : f64) -> Vec<Point> {
+	(0..count)
+		.map(|_| {
+			//The square root keeps the density even towards the rim
+			let r = radius * rng.gen::<f64>().sqrt();
+			let angle = rng.gen::<f64>() * 2.0 * PI;
+			*center + Point::new(r * angle.cos(), r * angle.sin())
+		})
+		.collect()
+}
+
+/// Points in `clusters` normally distributed clumps with standard deviation
+/// `spread`, whose centers are uniform over the box. Points are dealt to the
+/// clusters in turn, so the clusters are the same size give or take one. A
+/// negative or NaN spread is taken as zero, stacking each cluster on its
+/// center. Points can land outside the box.
+pub fn gaussian_clusters<R : Rng>(rng : &mut R, count : usize, clusters : usize, spread : f64,
+		min : &Point, max : &Point) -> Vec<Point> {
+	let centers = uniform_box(rng, clusters.max(1), min, max);
+	let normal = Normal::new(0.0, if spread > 0.0 { spread } else { 0.0 });
+	(0..count)
+		.map(|i| centers[i % centers.len()] + Point::new(normal.ind_sample(rng), normal.ind_sample(rng)))
+		.collect()
+}
+
+/// The centers of a `columns` by `rows` grid of cells over the box, each
+/// moved by up to `jitter` (a fraction of the cell size) in x and y. A jitter
+/// of zero gives a regular lattice, which is full of cocircular sites. The
+/// jitter is clamped to between 0 and 0.5, so every point stays in its cell.
+pub fn jittered_grid<R : Rng>(rng : &mut R, columns : usize, rows : usize, jitter : f64,
+		min : &Point, max : &Point) -> Vec<Point> {
+	let jitter = jitter.clamp(0.0, 0.5);
+	let cell = Point::new((max.x() - min.x()) / columns as f64, (max.y() - min.y()) / rows as f64);
+	let mut out = Vec::with_capacity(columns * rows);
+	for row in 0..rows {
+		for column in 0..columns {
+			let (dx, dy) = if jitter > 0.0 {
+				(rng.gen_range(-jitter, jitter), rng.gen_range(-jitter, jitter))
+			} else {
+				(0.0, 0.0)
+			};
+			out.push(Point::new(
+				min.x() + cell.x() * (column as f64 + 0.5 + dx),
+				min.y() + cell.y() * (row as f64 + 0.5 + dy)));
+		}
+	}
+	out
+}
+
+/// Blue-noise points over the box, no two closer than `min_distance`, with
+/// Bridson's algorithm. A point is given up on after `attempts` failed
+/// candidates around it; 30 is the usual choice.
+pub fn poisson_disk<R : Rng>(rng : &mut R, min_distance : f64, attempts : usize,
+		min : &Point, max : &Point) -> Vec<Point> {
+	let size = *max - *min;
+	if min_distance <= 0.0 || size.x() <= 0.0 || size.y() <= 0.0 {
+		return Vec::new();
+	}
+
+	//Cells small enough to hold at most one point each
+	let cell = min_distance / 2f64.sqrt();
+	let columns = (size.x() / cell).ceil() as usize;
+	let rows = (size.y() / cell).ceil() as usize;
+	let mut grid : Vec<Option<usize>> = vec![None; columns * rows];
+	let grid_index = |p : &Point| {
+		let column = (((p.x() - min.x()) / cell) as usize).min(columns - 1);
+		let row = (((p.y() - min.y()) / cell) as usize).min(rows - 1);
+		(column, row)
+	};
+
+	let mut out = Vec::new();
+	let mut active = Vec::new();
+	let first = uniform_box(rng, 1, min, max)[0];
+	let (column, row) = grid_index(&first);
+	grid[row * columns + column] = Some(0);
+	out.push(first);
+	active.push(0);
+
+	while !active.is_empty() {
+		let active_index = rng.gen_range(0, active.len());
+		let center = out[active[active_index]];
+		let mut found = false;
+
+		for _ in 0..attempts {
+			//A candidate in the annulus between one and two distances away
+			let r = min_distance * (1.0 + rng.gen::<f64>());
+			let angle = rng.gen::<f64>() * 2.0 * PI;
+			let candidate = center + Point::new(r * angle.cos(), r * angle.sin());
+			if candidate.x() < min.x() || candidate.x() >= max.x()
+					|| candidate.y() < min.y() || candidate.y() >= max.y() {
+				continue;
+			}
+
+			let (column, row) = grid_index(&candidate);
+			let mut clear = true;
+			for y in row.saturating_sub(2)..(row + 3).min(rows) {
+				for x in column.saturating_sub(2)..(column + 3).min(columns) {
+					if let Some(other) = grid[y * columns + x] {
+						let offset = out[other] - candidate;
+						if offset.dot(offset) < min_distance * min_distance {
+							clear = false;
+						}
+					}
+				}
+			}
+
+			if clear {
+				grid[row * columns + column] = Some(out.len());
+				active.push(out.len());
+				out.push(candidate);
+				found = true;
+				break;
+			}
+		}
+
+		if !found {
+			active.swap_remove(active_index);
+		}
+	}
+	out
+}
+
+/// The first `count` points of the Halton sequence in bases 2 and 3, scaled
+/// to the box. They are spread evenly without looking like a grid, and are
+/// the same every time.
+pub fn halton(count : usize, min : &Point, max : &Point) -> Vec<Point> {
+	(1..count + 1)
+		.map(|i| Point::new(
+			min.x() + radical_inverse(i, 2) * (max.x() - min.x()),
+			min.y() + radical_inverse(i, 3) * (max.y() - min.y())))
+		.collect()
+}
+
+//Mirrors the digits of `index` in `base` about the point
+fn radical_inverse(mut index : usize, base : usize) -> f64 {
+	let mut out = 0.0;
+	let mut digit_value = 1.0 / base as f64;
+	while index > 0 {
+		out += (index % base) as f64 * digit_value;
+		index /= base;
+		digit_value /= base as f64;
+	}
+	out
+}
+
+/// `count` evenly spaced points on a circle. Every site is cocircular with
+/// every other, which makes this a worst case for the circle events.
+pub fn circle(count : usize, center : &Point, radius : f64) -> Vec<Point> {
+	(0..count)
+		.map(|i| {
+			let angle = 2.0 * PI * i as f64 / count as f64;
+			*center + Point::new(radius * angle.cos(), radius * angle.sin())
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{SeedableRng, XorShiftRng};
+
+	fn inside(points : &[Point], min : &Point, max : &Point) -> bool {
+		points.iter().all(|p| p.x() >= min.x() && p.x() <= max.x() && p.y() >= min.y() && p.y() <= max.y())
+	}
+
+	#[test]
+	fn test_seeded_generators_repeat() {
+		let (min, max) = (Point::new(-5.0, 0.0), Point::new(5.0, 20.0));
+		let run = |seed| {
+			let mut rng = XorShiftRng::from_seed(seed);
+			let mut out = uniform_box(&mut rng, 50, &min, &max);
+			out.extend(uniform_disk(&mut rng, 50, &Point::new(0.0, 10.0), 5.0));
+			out.extend(gaussian_clusters(&mut rng, 50, 4, 1.0, &min, &max));
+			out.extend(jittered_grid(&mut rng, 5, 10, 0.4, &min, &max));
+			out.extend(poisson_disk(&mut rng, 2.0, 30, &min, &max));
+			out
+		};
+		assert!(run([1, 2, 3, 4]) == run([1, 2, 3, 4]));
+		assert!(run([1, 2, 3, 4]) != run([4, 3, 2, 1]));
+	}
+
+	#[test]
+	fn test_points_stay_in_their_region() {
+		let mut rng = XorShiftRng::from_seed([6, 6, 6, 6]);
+		let (min, max) = (Point::new(-5.0, 0.0), Point::new(5.0, 20.0));
+		assert!(inside(&uniform_box(&mut rng, 200, &min, &max), &min, &max));
+		assert!(inside(&jittered_grid(&mut rng, 10, 20, 0.5, &min, &max), &min, &max));
+		assert!(inside(&jittered_grid(&mut rng, 10, 20, 3.0, &min, &max), &min, &max));
+		assert!(gaussian_clusters(&mut rng, 20, 2, -1.0, &min, &max).iter().all(|p| p.x().is_finite()));
+		assert!(inside(&halton(200, &min, &max), &min, &max));
+
+		let center = Point::new(1.0, 2.0);
+		for p in uniform_disk(&mut rng, 200, &center, 3.0) {
+			assert!((p - center).dot(p - center) <= 9.0 + 1e-9);
+		}
+		for p in circle(16, &center, 3.0) {
+			assert!(((p - center).dot(p - center).sqrt() - 3.0).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn test_poisson_disk_spacing() {
+		let mut rng = XorShiftRng::from_seed([8, 6, 7, 5]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(30.0, 20.0));
+		let points = poisson_disk(&mut rng, 1.5, 30, &min, &max);
+		assert!(inside(&points, &min, &max));
+		//Bridson's algorithm packs the box fairly tightly
+		assert!(points.len() > 100);
+		for (i, a) in points.iter().enumerate() {
+			for b in &points[i + 1..] {
+				assert!((*a - *b).dot(*a - *b) >= 1.5 * 1.5);
+			}
+		}
+	}
+}
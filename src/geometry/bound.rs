@@ -15,38 +15,46 @@ impl Bound {
         }
     }
 
+    /// The box with the given corners, without waiting for points to grow it.
+    pub fn from_corners(min : &Point, max : &Point) -> Bound {
+        let mut bound = Bound::new();
+        bound.update(min);
+        bound.update(max);
+        bound
+    }
+
     pub fn update(&mut self, point: &Point) {
         if let Some(min) = self.min_point {
             self.min_point = Some(Point::new(
-                {if point.x() < min.x() {point.x()} else {min.x()}},
-                {if point.y() < min.y() {point.y()} else {min.y()}}));
+                if point.x() < min.x() {point.x()} else {min.x()},
+                if point.y() < min.y() {point.y()} else {min.y()}));
         } else {
             self.min_point = Some(*point);
         }
 
         if let Some(max) = self.max_point {
             self.max_point = Some(Point::new(
-                {if point.x() > max.x() {point.x()} else {max.x()}},
-                {if point.y() > max.y() {point.y()} else {max.y()}}));
+                if point.x() > max.x() {point.x()} else {max.x()},
+                if point.y() > max.y() {point.y()} else {max.y()}));
         } else {
             self.max_point = Some(*point);
         }
     }
 
     pub fn get_top(&self) -> Option<f64> {
-        self.min_point.and_then(|min| self.margin().and_then(|marg| Some(min.y() - marg)))
+        self.min_point.and_then(|min| self.margin().map(|marg| min.y() - marg))
     }
 
     pub fn get_bottom(&self) -> Option<f64> {
-        self.max_point.and_then(|max| self.margin().and_then(|marg| Some(max.y() + marg)))
+        self.max_point.and_then(|max| self.margin().map(|marg| max.y() + marg))
     }
 
     pub fn get_left(&self) -> Option<f64> {
-        self.min_point.and_then(|min| self.margin().and_then(|marg| Some(min.x() - marg)))
+        self.min_point.and_then(|min| self.margin().map(|marg| min.x() - marg))
     }
     
     pub fn get_right(&self) -> Option<f64> {
-        self.max_point.and_then(|max| self.margin().and_then(|marg| Some(max.x() + marg)))
+        self.max_point.and_then(|max| self.margin().map(|marg| max.x() + marg))
     }
 	
 	pub fn get_max(&self) -> Option<Point> {
@@ -65,14 +73,12 @@ impl Bound {
                 return Some(larger/5.0 + 1.0); 
             }
         }
-        return None;
+        None
     }
 
-    pub fn find_bound_intersection(&self, site1 : &Point, site2 : &Point) -> Option<Point> {
-        if self.get_right().is_none() {
-            return None;
-        }
-        return None;
+    pub fn find_bound_intersection(&self, _site1 : &Point, _site2 : &Point) -> Option<Point> {
+        self.get_right()?;
+        None
 /*
         let mid = (*site2 - *site1)*0.5 + *site1;
         let ray = Point::new(
@@ -105,15 +111,16 @@ impl Bound {
 */
     }
     
+    #[allow(dead_code)]
     fn closer_point(x : &Point, a : &Point, b : &Point) -> Point {
         let x_a = *x - *a;
         let x_b = *x - *b;
         let x_a_sq = x_a.x()*x_a.x() + x_a.y()*x_a.y();
         let x_b_sq = x_b.x()*x_b.x() + x_b.y()*x_b.y();
         if x_a_sq > x_b_sq { 
-            b.clone()
+            *b
         } else { 
-            a.clone()
+            *a
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,179 @@
+use geometry::point::Point;
+
+//Halvings of any one arc before `flatten` gives up on the tolerance
+const MAX_DEPTH : u32 = 24;
+
+/// An arc of a conic, as a rational quadratic Bézier curve: the points
+/// `(P0 (1-s)^2 + 2 w P1 s (1-s) + P2 s^2) / ((1-s)^2 + 2 w s (1-s) + s^2)`
+/// for `s` from 0 to 1. A weight of 1 gives an ordinary quadratic Bézier
+/// curve, which traces a parabola, a weight below 1 an ellipse, and a weight
+/// above 1 a hyperbola.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ConicArc {
+	pub start : Point,
+	pub control : Point,
+	pub end : Point,
+	pub weight : f64,
+}
+
+impl ConicArc {
+	/// The straight segment from `start` to `end`.
+	pub fn line(start : &Point, end : &Point) -> ConicArc {
+		ConicArc {
+			start : *start,
+			control : (*start + *end) * 0.5,
+			end : *end,
+			weight : 1.0,
+		}
+	}
+
+	/// The arc of the conic that leaves `start` heading along `start_direction`,
+	/// passes through `through`, and reaches `end` heading along
+	/// `end_direction`. Returns `None` if the two tangents do not meet ahead of
+	/// `start` and behind `end`, or if `through` does not lie between them.
+	pub fn through(start : &Point, start_direction : &Point, end : &Point, end_direction : &Point, through : &Point) -> Option<ConicArc> {
+		let denominator = start_direction.cross(*end_direction);
+		if denominator == 0.0 {
+			return None;
+		}
+		let ahead = (*end - *start).cross(*end_direction) / denominator;
+		let behind = (*start - *end).cross(*start_direction) / denominator;
+		if !(ahead > 0.0 && behind > 0.0) {
+			return None;
+		}
+		let control = *start + *start_direction * ahead;
+
+		//The weight follows from where `through` sits in the triangle of the
+		//control points
+		let area = (control - *start).cross(*end - *start);
+		let near_start = (control - *through).cross(*end - *through) / area;
+		let near_control = (*end - *through).cross(*start - *through) / area;
+		let near_end = (*start - *through).cross(control - *through) / area;
+		if !(near_start > 0.0 && near_control > 0.0 && near_end > 0.0) {
+			return None;
+		}
+		Some(ConicArc {
+			start : *start,
+			control,
+			end : *end,
+			weight : near_control / (2.0 * (near_start * near_end).sqrt()),
+		})
+	}
+
+	/// The point at parameter `s`, from 0 at `start` to 1 at `end`.
+	pub fn point_at(&self, s : f64) -> Point {
+		let (a, b, c) = ((1.0 - s) * (1.0 - s), 2.0 * self.weight * s * (1.0 - s), s * s);
+		(self.start * a + self.control * b + self.end * c) * (1.0 / (a + b + c))
+	}
+
+	/// The same arc, run from `end` to `start`.
+	pub fn reversed(&self) -> ConicArc {
+		ConicArc {
+			start : self.end,
+			control : self.control,
+			end : self.start,
+			weight : self.weight,
+		}
+	}
+
+	/// Splits the arc at its middle into two arcs of the same conic. Their
+	/// weights are nearer 1, so repeated halving tends to parabolic pieces.
+	pub fn halves(&self) -> (ConicArc, ConicArc) {
+		let scale = 1.0 / (1.0 + self.weight);
+		let first = (self.start + self.control * self.weight) * scale;
+		let second = (self.control * self.weight + self.end) * scale;
+		let middle = (first + second) * 0.5;
+		let weight = ((1.0 + self.weight) / 2.0).sqrt();
+		(
+			ConicArc { start : self.start, control : first, end : middle, weight },
+			ConicArc { start : middle, control : second, end : self.end, weight },
+		)
+	}
+
+	/// Points along the arc, after `start` and up to `end`, so that the
+	/// polyline through them strays from the arc by at most `tolerance`. The
+	/// arc is halved until its control point lies that close to the chord,
+	/// and the arc stays within the triangle of its control points.
+	pub fn flatten(&self, tolerance : f64) -> Vec<Point> {
+		let mut points = Vec::new();
+		self.flatten_into(tolerance, 0, &mut points);
+		points
+	}
+
+	fn flatten_into(&self, tolerance : f64, depth : u32, out : &mut Vec<Point>) {
+		let chord = self.end - self.start;
+		let length = chord.dot(chord).sqrt();
+		let offset = self.control - self.start;
+		let deviation = if length > 0.0 { chord.cross(offset).abs() / length } else { offset.dot(offset).sqrt() };
+		if deviation > tolerance && depth < MAX_DEPTH {
+			let (first, second) = self.halves();
+			first.flatten_into(tolerance, depth + 1, out);
+			second.flatten_into(tolerance, depth + 1, out);
+		} else {
+			out.push(self.end);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn close(a : &Point, b : &Point) -> bool {
+		(*a - *b).dot(*a - *b).sqrt() < 1e-9
+	}
+
+	#[test]
+	fn test_through_a_hyperbola() {
+		//The branch of x y = 1 from (1/2, 2) to (2, 1/2)
+		let tangent = |x : f64| Point::new(1.0, -1.0 / (x * x));
+		let (start, end) = (Point::new(0.5, 2.0), Point::new(2.0, 0.5));
+		let arc = ConicArc::through(&start, &tangent(0.5), &end, &tangent(2.0), &Point::new(1.0, 1.0)).unwrap();
+		assert!(arc.weight > 1.0);
+		for k in 0..11 {
+			let p = arc.point_at(k as f64 / 10.0);
+			assert!((p.x() * p.y() - 1.0).abs() < 1e-9);
+		}
+		assert!(close(&arc.point_at(0.5), &Point::new(1.0, 1.0)));
+
+		let (first, second) = arc.halves();
+		assert!(close(&first.end, &Point::new(1.0, 1.0)) && close(&second.start, &first.end));
+		for k in 0..11 {
+			let p = second.point_at(k as f64 / 10.0);
+			assert!((p.x() * p.y() - 1.0).abs() < 1e-9);
+		}
+		assert!(close(&arc.reversed().point_at(0.25), &arc.point_at(0.75)));
+	}
+
+	#[test]
+	fn test_through_a_parabola() {
+		//y = x^2 from (-1, 1) to (2, 4) is a plain quadratic curve
+		let arc = ConicArc::through(&Point::new(-1.0, 1.0), &Point::new(1.0, -2.0), &Point::new(2.0, 4.0),
+			&Point::new(1.0, 4.0), &Point::new(0.5, 0.25)).unwrap();
+		assert!((arc.weight - 1.0).abs() < 1e-12);
+		assert!(close(&arc.control, &Point::new(0.5, -2.0)));
+
+		//Tangents that meet behind the start give no arc
+		assert!(ConicArc::through(&Point::new(0.0, 0.0), &Point::new(1.0, 0.0), &Point::new(1.0, 1.0),
+			&Point::new(1.0, 0.0), &Point::new(0.5, 0.5)).is_none());
+	}
+
+	#[test]
+	fn test_flatten() {
+		let arc = ConicArc::through(&Point::new(1.0, 0.0), &Point::new(0.0, 1.0), &Point::new(0.0, 1.0),
+			&Point::new(-1.0, 0.0), &Point::new(0.5f64.sqrt(), 0.5f64.sqrt())).unwrap();
+		let tolerance = 1e-4;
+		let points = arc.flatten(tolerance);
+		assert!(close(points.last().unwrap(), &Point::new(0.0, 1.0)));
+		let mut previous = arc.start;
+		for p in points {
+			//A quarter of the unit circle, so each chord's middle is within
+			//the tolerance of it
+			assert!((p.dot(p) - 1.0).abs() < 1e-9);
+			let middle = (previous + p) * 0.5;
+			assert!(1.0 - middle.dot(middle).sqrt() <= tolerance);
+			previous = p;
+		}
+		assert_eq!(ConicArc::line(&Point::new(0.0, 0.0), &Point::new(3.0, 0.0)).flatten(tolerance), vec![Point::new(3.0, 0.0)]);
+	}
+}
@@ -1,123 +1,346 @@
-
-extern crate glium;
-
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::ops::{Index, IndexMut};
 
 use geometry::bound::Bound;
 use geometry::edge::Edge;
 use geometry::face::Face;
+pub use geometry::handle::{EdgePtr, FacePtr, VertexPtr};
 use geometry::point::Point;
+use geometry::polygon;
 use geometry::vertex::Vertex;
 use graphics::display::OpenglVertex;
 
-pub type VertexPtr = u32;
-pub type EdgePtr = u32;
-pub type FacePtr = u32;
-
+/// A doubly connected edge list. Vertices, half-edges and faces each carry a
+/// user payload of type `V`, `E` and `F` respectively, defaulting to `()`.
 #[derive(Clone, Default, PartialEq)]
-pub struct DoublyConnectedEdgeList {
-	index_vertex : u32,
-	index_edge : u32,
-	index_face : u32,
-	pub vertices : Vec<Vertex>,
-	pub edges : Vec<Edge>,
-	pub faces : Vec<Face>,
+pub struct DoublyConnectedEdgeList<V = (), E = (), F = ()> {
+	pub vertices : Vec<Vertex<V>>,
+	pub edges : Vec<Edge<E>>,
+	pub faces : Vec<Face<F>>,
 	bounding_box : Bound,
+	snap_tolerance : f64,
+	//Keyed by whether the vertices are sites, then by cell
+	vertex_grid : HashMap<(bool, i64, i64), Vec<VertexPtr>>,
 }
 
-impl DoublyConnectedEdgeList {
-	pub fn new() -> DoublyConnectedEdgeList {
+impl<V, E, F> DoublyConnectedEdgeList<V, E, F> {
+	pub fn new() -> DoublyConnectedEdgeList<V, E, F> {
 		DoublyConnectedEdgeList {
-			index_vertex : 0,
-			index_edge : 0,
-			index_face : 0,
 			vertices : Vec::new(),
 			edges : Vec::new(),
 			faces : Vec::new(),
 			bounding_box : Bound::new(),
+			snap_tolerance : 0.0,
+			vertex_grid : HashMap::new(),
 		}
 	}
-	pub fn new_vertex(&mut self, point : &Point) -> VertexPtr {
-		for vertex in &self.vertices {
-			if vertex.point == *point {
-				return vertex.index();
-			}
+	/// Creates an empty list whose `new_vertex` merges any two points closer
+	/// than `tolerance`. A tolerance of zero only merges exact duplicates.
+	pub fn with_tolerance(tolerance : f64) -> DoublyConnectedEdgeList<V, E, F> {
+		let mut dcel = DoublyConnectedEdgeList::new();
+		dcel.snap_tolerance = tolerance.max(0.0);
+		dcel
+	}
+	pub fn snap_tolerance(&self) -> f64 {
+		self.snap_tolerance
+	}
+	/// Returns the vertex at `point`, creating it if no vertex lies within the
+	/// snapping tolerance. Existing vertices are found through a hash grid with
+	/// cells as wide as the tolerance, so only the 3x3 block of cells around
+	/// `point` has to be searched. Sites are kept out of that grid, so a vertex
+	/// never snaps onto a site.
+	pub fn new_vertex(&mut self, point : &Point) -> VertexPtr where V : Default {
+		self.new_vertex_with_data(point, V::default())
+	}
+	/// Like `new_vertex`, attaching `data` if a new vertex is created. When
+	/// `point` snaps to an existing vertex, its payload is left unchanged.
+	pub fn new_vertex_with_data(&mut self, point : &Point, data : V) -> VertexPtr {
+		if let Some(existing) = self.find_vertex(point) {
+			return existing;
 		}
-
-		let index = self.index_vertex;
-		let new_vertex = Vertex::new(index, *point);
+		self.push_vertex(point, data, false)
+	}
+	/// Creates a vertex marked as the site of a face. It is never snapped to
+	/// another vertex, even one in the same place, and later vertices are never
+	/// snapped to it. Use `find_site` to look for an earlier site nearby.
+	pub fn new_site_vertex(&mut self, point : &Point) -> VertexPtr where V : Default {
+		self.new_site_vertex_with_data(point, V::default())
+	}
+	pub fn new_site_vertex_with_data(&mut self, point : &Point, data : V) -> VertexPtr {
+		self.push_vertex(point, data, true)
+	}
+	fn push_vertex(&mut self, point : &Point, data : V, is_site : bool) -> VertexPtr {
+		let index = VertexPtr(self.vertices.len() as u32);
 		self.bounding_box.update(point);
-		self.vertices.push(new_vertex);
-		self.index_vertex += 1;
-		return index;
-	}
-	pub fn new_edge(&mut self, from : Option<u32>) -> EdgePtr {
-		let index = self.index_edge;
-		self.edges.push(Edge::new(index, from));
-		self.index_edge += 1;
-		return index;
-	}
-	pub fn new_face(&mut self, site : Option<VertexPtr>) -> FacePtr {
-		let index = self.index_face;
-		self.faces.push(Face::new(index, site));
-		self.index_face += 1;
-		return index;
+		let mut vertex = Vertex::new(index, *point, data);
+		vertex.is_site = is_site;
+		self.vertices.push(vertex);
+		let (cx, cy) = self.grid_cell(point);
+		self.vertex_grid.entry((is_site, cx, cy)).or_default().push(index);
+		index
+	}
+	/// Finds the vertex nearest to `point` within the snapping tolerance,
+	/// leaving out sites.
+	pub fn find_vertex(&self, point : &Point) -> Option<VertexPtr> {
+		self.find_nearest(point, false)
+	}
+	/// Finds the site vertex nearest to `point` within the snapping tolerance.
+	pub fn find_site(&self, point : &Point) -> Option<VertexPtr> {
+		self.find_nearest(point, true)
+	}
+	fn find_nearest(&self, point : &Point, is_site : bool) -> Option<VertexPtr> {
+		let (cx, cy) = self.grid_cell(point);
+		if self.snap_tolerance == 0.0 {
+			return self.vertex_grid.get(&(is_site, cx, cy))
+				.and_then(|bucket| bucket.iter()
+					.find(|&&ptr| self[ptr].point == *point)
+					.cloned());
+		}
+
+		let limit = self.snap_tolerance * self.snap_tolerance;
+		let mut best : Option<(f64, VertexPtr)> = None;
+		for dx in -1..2 {
+			for dy in -1..2 {
+				//Far out coordinates saturate the cell index, so wrap rather than overflow
+				if let Some(bucket) = self.vertex_grid.get(&(is_site, cx.wrapping_add(dx), cy.wrapping_add(dy))) {
+					for &ptr in bucket {
+						let offset = self[ptr].point - *point;
+						let dist = offset.dot(offset);
+						if dist <= limit && best.is_none_or(|(best_dist, _)| dist < best_dist) {
+							best = Some((dist, ptr));
+						}
+					}
+				}
+			}
+		}
+		best.map(|(_, ptr)| ptr)
+	}
+	fn grid_cell(&self, point : &Point) -> (i64, i64) {
+		if self.snap_tolerance == 0.0 {
+			//Exact matching only needs equal points to share a key; adding 0.0
+			//folds -0.0 into 0.0 so the two zeros hash alike
+			((point.x() + 0.0).to_bits() as i64, (point.y() + 0.0).to_bits() as i64)
+		} else {
+			((point.x() / self.snap_tolerance).floor() as i64,
+				(point.y() / self.snap_tolerance).floor() as i64)
+		}
+	}
+	pub fn new_edge(&mut self, from : Option<VertexPtr>) -> EdgePtr where E : Default {
+		self.new_edge_with_data(from, E::default())
+	}
+	pub fn new_edge_with_data(&mut self, from : Option<VertexPtr>, data : E) -> EdgePtr {
+		let index = EdgePtr(self.edges.len() as u32);
+		self.edges.push(Edge::new(index, from, data));
+		index
+	}
+	pub fn new_face(&mut self, site : Option<VertexPtr>) -> FacePtr where F : Default {
+		self.new_face_with_data(site, F::default())
+	}
+	pub fn new_face_with_data(&mut self, site : Option<VertexPtr>, data : F) -> FacePtr {
+		let index = FacePtr(self.faces.len() as u32);
+		self.faces.push(Face::new(index, site, data));
+		index
 	}
 	pub fn new_dual_edges(&mut self, e1_origin : Option<VertexPtr>, 
-			e2_origin : Option<VertexPtr>) -> (EdgePtr, EdgePtr) {
+			e2_origin : Option<VertexPtr>) -> (EdgePtr, EdgePtr) where E : Default {
 
 		let index1 = self.new_edge(e1_origin);
 		let index2 = self.new_edge(e2_origin);
-		self.get_edge(index1).twin = Some(index2);
-		self.get_edge(index2).twin = Some(index1);
+		self[index1].twin = Some(index2);
+		self[index2].twin = Some(index1);
 
 		(index1, index2)
 	}
-	pub fn get_vertex(&mut self, index : u32) -> &mut Vertex {
-		return self.vertices.get_mut(index as usize).unwrap();
+	/// Makes `next` follow `prev` around their face.
+	pub fn link_edges(&mut self, prev : EdgePtr, next : EdgePtr) {
+		self[prev].next = Some(next);
+		self[next].prev = Some(prev);
+	}
+	pub fn get_vertex(&self, index : VertexPtr) -> Option<&Vertex<V>> {
+		self.vertices.get(index.index())
+	}
+	pub fn get_edge(&self, index : EdgePtr) -> Option<&Edge<E>> {
+		self.edges.get(index.index())
 	}
-	pub fn get_edge(&mut self, index : u32) -> &mut Edge {
-		return self.edges.get_mut(index as usize).unwrap();
-	}	
-	pub fn get_face(&mut self, index : u32) -> &mut Face {
-		return self.faces.get_mut(index as usize).unwrap();
+	pub fn get_face(&self, index : FacePtr) -> Option<&Face<F>> {
+		self.faces.get(index.index())
 	}
-	pub fn get_imm_vertex(&self, index : u32) -> &Vertex {
-		return self.vertices.get(index as usize).unwrap();
+	pub fn get_vertex_mut(&mut self, index : VertexPtr) -> Option<&mut Vertex<V>> {
+		self.vertices.get_mut(index.index())
 	}
-	pub fn get_imm_edge(&self, index : u32) -> &Edge {
-		return self.edges.get(index as usize).unwrap();
-	}	
-	pub fn get_imm_face(&self, index : u32) -> &Face {
-		return self.faces.get(index as usize).unwrap();
+	pub fn get_edge_mut(&mut self, index : EdgePtr) -> Option<&mut Edge<E>> {
+		self.edges.get_mut(index.index())
+	}
+	pub fn get_face_mut(&mut self, index : FacePtr) -> Option<&mut Face<F>> {
+		self.faces.get_mut(index.index())
 	}
 	pub fn get_edge_tuple(&self, edge_ptr : EdgePtr) -> Option<(VertexPtr, VertexPtr)> {
-		let twin_ptr_option;
-		let origin_ptr_option;
-		if let Some(ref edge) = self.edges.get(edge_ptr as usize) {
-			twin_ptr_option = edge.twin;
-			origin_ptr_option = edge.origin;
-		} else {
-			return None;
+		let edge = self.get_edge(edge_ptr)?;
+		let twin = self.get_edge(edge.twin?)?;
+		Some((edge.origin?, twin.origin?))
+	}
+	/// Builds a list with one face per polygon, in order, each carrying its
+	/// payload. Polygons should be counter-clockwise and must not overlap.
+	///
+	/// Corners closer than `tolerance` are merged, and sides that shared
+	/// corners make between two polygons become twins. A side with no
+	/// neighbor gets a twin with no incident face, on the outside. An empty
+	/// or degenerate polygon still gets its (edgeless) face.
+	pub fn from_polygons(polygons : Vec<(Vec<Point>, F)>, tolerance : f64) -> DoublyConnectedEdgeList<V, E, F>
+			where V : Default, E : Default {
+		DoublyConnectedEdgeList::from_rings(
+			polygons.into_iter().map(|(polygon, data)| (vec![polygon], data)).collect(),
+			tolerance)
+	}
+
+	/// Like `from_polygons`, but each face is bounded by any number of rings.
+	/// Counter-clockwise rings become outer components of their face, and
+	/// clockwise rings, around holes, become inner components.
+	pub fn from_rings(faces : Vec<(Vec<Vec<Point>>, F)>, tolerance : f64) -> DoublyConnectedEdgeList<V, E, F>
+			where V : Default, E : Default {
+		let mut dcel = DoublyConnectedEdgeList::with_tolerance(tolerance);
+		let mut sides : BTreeMap<(VertexPtr, VertexPtr), EdgePtr> = BTreeMap::new();
+
+		for (rings, data) in faces {
+			let face = dcel.new_face_with_data(None, data);
+
+			for ring in rings {
+				let mut corners : Vec<VertexPtr> = ring.iter().map(|p| dcel.new_vertex(p)).collect();
+				corners.dedup();
+				while corners.len() > 1 && corners.first() == corners.last() {
+					corners.pop();
+				}
+				if corners.len() < 3 {
+					continue;
+				}
+
+				let edges : Vec<EdgePtr> = corners.iter()
+					.map(|&corner| {
+						let edge = dcel.new_edge(Some(corner));
+						dcel[edge].incident_face = Some(face);
+						if dcel[corner].incident_edge.is_none() {
+							dcel[corner].incident_edge = Some(edge);
+						}
+						edge
+					})
+					.collect();
+				for i in 0..edges.len() {
+					dcel.link_edges(edges[i], edges[(i + 1) % edges.len()]);
+					let side = (corners[i], corners[(i + 1) % corners.len()]);
+					if let Some(twin) = sides.remove(&(side.1, side.0)) {
+						dcel[edges[i]].twin = Some(twin);
+						dcel[twin].twin = Some(edges[i]);
+					} else {
+						sides.insert(side, edges[i]);
+					}
+				}
+				if polygon::signed_area(&ring) >= 0.0 {
+					dcel[face].outer_component.push(edges[0]);
+				} else {
+					dcel[face].inner_component.push(edges[0]);
+				}
+			}
 		}
 
-		let twin_origin_ptr_option;
-		if let Some(twin_ptr) = twin_ptr_option {
-			if let Some(ref twin) = self.edges.get(twin_ptr as usize) {
-				twin_origin_ptr_option = twin.origin;
-			} else {
-				return None;
+		//Whatever is left borders the outside
+		for ((_, end), edge) in sides {
+			let twin = dcel.new_edge(Some(end));
+			dcel[edge].twin = Some(twin);
+			dcel[twin].twin = Some(edge);
+		}
+		dcel
+	}
+
+	/// Checks the invariants of the list and describes the first one broken:
+	/// every pointer is in range, twins pair up, `next` and `prev` agree and
+	/// stay in one face, and consecutive half-edges meet at a vertex.
+	pub fn validate(&self) -> Result<(), String> {
+		let (vertex_count, edge_count, face_count) = (self.vertices.len(), self.edges.len(), self.faces.len());
+		let check_vertex = |owner : String, ptr : Option<VertexPtr>| match ptr {
+			Some(ptr) if ptr.index() >= vertex_count => Err(format!("{} points at missing vertex v{}", owner, ptr)),
+			_ => Ok(()),
+		};
+		let check_edge = |owner : String, ptr : Option<EdgePtr>| match ptr {
+			Some(ptr) if ptr.index() >= edge_count => Err(format!("{} points at missing edge e{}", owner, ptr)),
+			_ => Ok(()),
+		};
+		let check_face = |owner : String, ptr : Option<FacePtr>| match ptr {
+			Some(ptr) if ptr.index() >= face_count => Err(format!("{} points at missing face f{}", owner, ptr)),
+			_ => Ok(()),
+		};
+
+		for (index, vertex) in self.vertices.iter().enumerate() {
+			if vertex.index().index() != index {
+				return Err(format!("v{} is stored at position {}", vertex.index(), index));
+			}
+			if !vertex.point.x().is_finite() || !vertex.point.y().is_finite() {
+				return Err(format!("v{} is at {:?}", index, vertex.point));
+			}
+			check_edge(format!("v{}", index), vertex.incident_edge)?;
+			if vertex.is_site && vertex.incident_edge.is_some() {
+				return Err(format!("v{} is a site but has an incident edge", index));
+			}
+			if let Some(edge) = vertex.incident_edge {
+				if self[edge].origin != Some(vertex.index()) {
+					return Err(format!("v{} has incident edge e{}, which starts elsewhere", index, edge));
+				}
 			}
-		} else {
-			return None;
 		}
 
-		if let Some(vertex1_ptr) = origin_ptr_option {
-			if let Some(vertex2_ptr) = twin_origin_ptr_option {
-				return Some((vertex1_ptr, vertex2_ptr))
+		for (index, edge) in self.edges.iter().enumerate() {
+			let name = format!("e{}", index);
+			if edge.index().index() != index {
+				return Err(format!("e{} is stored at position {}", edge.index(), index));
+			}
+			check_vertex(name.clone(), edge.origin)?;
+			check_edge(name.clone(), edge.next)?;
+			check_edge(name.clone(), edge.prev)?;
+			check_edge(name.clone(), edge.twin)?;
+			check_face(name.clone(), edge.incident_face)?;
+			if edge.origin.is_some_and(|origin| self[origin].is_site) {
+				return Err(format!("{} starts at site v{}", name, edge.origin.unwrap()));
+			}
+
+			let twin = match edge.twin {
+				Some(twin) => twin,
+				None => return Err(format!("{} has no twin", name)),
+			};
+			if twin == edge.index() || self[twin].twin != Some(edge.index()) {
+				return Err(format!("{} and its twin e{} do not pair up", name, twin));
+			}
+			if let Some(next) = edge.next {
+				if self[next].prev != Some(edge.index()) {
+					return Err(format!("{} is followed by e{}, which does not lead back", name, next));
+				}
+				if self[next].incident_face != edge.incident_face {
+					return Err(format!("{} is followed by e{} in another face", name, next));
+				}
+				if self[next].origin.is_some() && self[next].origin != self[twin].origin {
+					return Err(format!("{} does not end where e{} starts", name, next));
+				}
+			}
+			if let Some(prev) = edge.prev {
+				if self[prev].next != Some(edge.index()) {
+					return Err(format!("{} is preceded by e{}, which does not lead on", name, prev));
+				}
+			}
+		}
+
+		for (index, face) in self.faces.iter().enumerate() {
+			let name = format!("f{}", index);
+			if face.index().index() != index {
+				return Err(format!("f{} is stored at position {}", face.index(), index));
+			}
+			check_vertex(name.clone(), face.site)?;
+			for &edge in face.outer_component.iter().chain(face.inner_component.iter()) {
+				check_edge(name.clone(), Some(edge))?;
+				if self[edge].incident_face != Some(face.index()) {
+					return Err(format!("{} lists e{}, which belongs to another face", name, edge));
+				}
 			}
 		}
-		return None;
+		Ok(())
 	}
 	pub fn get_opengl_vertices(&self) -> Vec<OpenglVertex> {
 
@@ -127,10 +350,10 @@ impl DoublyConnectedEdgeList {
 			if let Some(origin_ptr) = edge.origin {
 				if let Some(twin_ptr) = edge.twin {
 					if let Some(face_ptr) = edge.incident_face {
-						if let Some(twin_origin_ptr) = self.get_imm_edge(twin_ptr).origin {
-							let color = self.get_imm_face(face_ptr).color();
-							let a = self.get_imm_vertex(origin_ptr).point;
-							let b = self.get_imm_vertex(twin_origin_ptr).point;
+						if let Some(twin_origin_ptr) = self[twin_ptr].origin {
+							let color = self[face_ptr].color();
+							let a = self[origin_ptr].point;
+							let b = self[twin_origin_ptr].point;
 							
 							let a_gl = self.opengl_point_shift(&a);
 							let b_gl = self.opengl_point_shift(&b);
@@ -142,23 +365,23 @@ impl DoublyConnectedEdgeList {
 
 							let a_vertex = OpenglVertex {
 								position : [a_gl.x() as f32, a_gl.y() as f32],
-								color : color,};
+								color,};
 							let b_vertex = OpenglVertex {
 								position : [b_gl.x() as f32, b_gl.y() as f32],
-								color : color,};
+								color,};
 							let c_vertex = OpenglVertex {
 								position : [c_gl.x() as f32, c_gl.y() as f32],
-								color : color,};
+								color,};
 							let d_vertex = OpenglVertex {
 								position : [d_gl.x() as f32, d_gl.y() as f32],
-								color : color,};
-
-							out.push(a_vertex.clone());
-							out.push(b_vertex.clone());
-							out.push(c_vertex.clone());
-							out.push(b_vertex.clone());
-							out.push(c_vertex.clone());
-							out.push(d_vertex.clone());
+								color,};
+
+							out.push(a_vertex);
+							out.push(b_vertex);
+							out.push(c_vertex);
+							out.push(b_vertex);
+							out.push(c_vertex);
+							out.push(d_vertex);
 						}
 					}
 				}
@@ -176,7 +399,49 @@ impl DoublyConnectedEdgeList {
 	}
 }
 
-impl fmt::Debug for DoublyConnectedEdgeList {
+impl<V, E, F> Index<VertexPtr> for DoublyConnectedEdgeList<V, E, F> {
+	type Output = Vertex<V>;
+
+	fn index(&self, index : VertexPtr) -> &Vertex<V> {
+		&self.vertices[index.index()]
+	}
+}
+
+impl<V, E, F> IndexMut<VertexPtr> for DoublyConnectedEdgeList<V, E, F> {
+	fn index_mut(&mut self, index : VertexPtr) -> &mut Vertex<V> {
+		&mut self.vertices[index.index()]
+	}
+}
+
+impl<V, E, F> Index<EdgePtr> for DoublyConnectedEdgeList<V, E, F> {
+	type Output = Edge<E>;
+
+	fn index(&self, index : EdgePtr) -> &Edge<E> {
+		&self.edges[index.index()]
+	}
+}
+
+impl<V, E, F> IndexMut<EdgePtr> for DoublyConnectedEdgeList<V, E, F> {
+	fn index_mut(&mut self, index : EdgePtr) -> &mut Edge<E> {
+		&mut self.edges[index.index()]
+	}
+}
+
+impl<V, E, F> Index<FacePtr> for DoublyConnectedEdgeList<V, E, F> {
+	type Output = Face<F>;
+
+	fn index(&self, index : FacePtr) -> &Face<F> {
+		&self.faces[index.index()]
+	}
+}
+
+impl<V, E, F> IndexMut<FacePtr> for DoublyConnectedEdgeList<V, E, F> {
+	fn index_mut(&mut self, index : FacePtr) -> &mut Face<F> {
+		&mut self.faces[index.index()]
+	}
+}
+
+impl<V, E, F> fmt::Debug for DoublyConnectedEdgeList<V, E, F> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
 
 		let mut s = String::from("Doubly Connected Edge List\n\n");
@@ -309,7 +574,7 @@ mod tests {
 
 	#[test]
 	fn test_dcel_out() {
-		let mut dcel = DoublyConnectedEdgeList::new();
+		let mut dcel : DoublyConnectedEdgeList = DoublyConnectedEdgeList::new();
 		let v1 = dcel.new_vertex(&Point::new(6.0, 7.8));
 		let v2 = dcel.new_vertex(&Point::new(-1.4, 3.6));
 		let v3 = dcel.new_vertex(&Point::new(-5.8, -0.7));
@@ -317,14 +582,72 @@ mod tests {
 		let e1 = dcel.new_edge(Some(v1));
 		let e2 = dcel.new_edge(Some(v2));
 		let e3 = dcel.new_edge(Some(v3));
-		let e4 = dcel.new_edge(Some(v1));
-		let e5 = dcel.new_edge(Some(v2));
-		let e6 = dcel.new_edge(Some(v3));
+		dcel.new_edge(Some(v1));
+		dcel.new_edge(Some(v2));
+		dcel.new_edge(Some(v3));
 
-		dcel.get_edge(e1).next = Some(e2);
-		dcel.get_edge(e2).next = Some(e3);
-		dcel.get_edge(e3).next = Some(e1);
+		dcel[e1].next = Some(e2);
+		dcel[e2].next = Some(e3);
+		dcel[e3].next = Some(e1);
+		assert_eq!(dcel.get_edge(e3).and_then(|e| e.next), Some(e1));
+		assert!(dcel.get_edge(EdgePtr(6)).is_none());
 
 		println!("{:?}", dcel);	
 	}
+
+	#[test]
+	fn test_vertex_snapping() {
+		let mut exact : DoublyConnectedEdgeList = DoublyConnectedEdgeList::new();
+		let a = exact.new_vertex(&Point::new(1.0, 2.0));
+		assert_eq!(exact.new_vertex(&Point::new(1.0, 2.0)), a);
+		assert_eq!(exact.new_vertex(&Point::new(-0.0, 0.0)), exact.new_vertex(&Point::new(0.0, -0.0)));
+		assert!(exact.new_vertex(&Point::new(1.0 + 1e-12, 2.0)) != a);
+
+		let mut snapped : DoublyConnectedEdgeList = DoublyConnectedEdgeList::with_tolerance(1e-6);
+		let b = snapped.new_vertex(&Point::new(1.0, 2.0));
+		assert_eq!(snapped.new_vertex(&Point::new(1.0 + 1e-12, 2.0 - 1e-12)), b);
+		assert_eq!(snapped.new_vertex(&Point::new(1.0000009, 2.0)), b);
+		assert!(snapped.new_vertex(&Point::new(1.000002, 2.0)) != b);
+		assert_eq!(snapped.vertices.len(), 2);
+
+		//Sites and other vertices never snap onto each other
+		let site = snapped.new_site_vertex(&Point::new(1.0, 2.0));
+		assert!(site != b);
+		assert_eq!(snapped.new_vertex(&Point::new(1.0, 2.0)), b);
+		assert_eq!(snapped.find_site(&Point::new(1.0000009, 2.0)), Some(site));
+		assert!(snapped.new_site_vertex(&Point::new(1.0, 2.0)) != site);
+		assert!(snapped.find_site(&Point::new(5.0, 5.0)).is_none());
+		assert!(snapped.find_vertex(&Point::new(1e300, -1e300)).is_none());
+	}
+
+	#[test]
+	fn test_validate() {
+		let mut dcel : DoublyConnectedEdgeList = DoublyConnectedEdgeList::new();
+		let v1 = dcel.new_vertex(&Point::new(0.0, 0.0));
+		let v2 = dcel.new_vertex(&Point::new(1.0, 0.0));
+		let (e1, e2) = dcel.new_dual_edges(Some(v1), Some(v2));
+		assert_eq!(dcel.validate(), Ok(()));
+
+		dcel.link_edges(e1, e1);
+		assert!(dcel.validate().is_err());
+		dcel[e1].next = None;
+		dcel[e1].prev = None;
+
+		dcel[e2].twin = Some(e2);
+		assert!(dcel.validate().is_err());
+		dcel[e2].twin = Some(e1);
+
+		dcel[v1].incident_edge = Some(e2);
+		assert!(dcel.validate().is_err());
+		dcel[v1].incident_edge = Some(e1);
+		assert_eq!(dcel.validate(), Ok(()));
+
+		let site = dcel.new_site_vertex(&Point::new(0.5, 1.0));
+		assert_eq!(dcel.validate(), Ok(()));
+		dcel[e1].origin = Some(site);
+		assert!(dcel.validate().is_err());
+		dcel[e1].origin = Some(v1);
+		dcel[site].incident_edge = Some(e1);
+		assert!(dcel.validate().is_err());
+	}
 }
@@ -1,13 +1,7 @@
-
-pub use geometry::face::Face;
-pub use geometry::vertex::Vertex;
-
-pub type VertexPtr = u32;
-pub type EdgePtr = u32;
-pub type FacePtr = u32;
+use geometry::handle::{EdgePtr, FacePtr, VertexPtr};
 
 #[derive(Copy, Clone, Eq, Debug)]
-pub struct Edge {
+pub struct Edge<E = ()> {
 	index : EdgePtr,
 	pub is_inf : bool,
 	pub origin : Option<VertexPtr>,
@@ -15,18 +9,20 @@ pub struct Edge {
 	pub prev : Option<EdgePtr>,
 	pub twin : Option<EdgePtr>,
 	pub incident_face : Option<FacePtr>,
+	pub data : E,
 }
 
-impl Edge {
-	pub fn new(index : EdgePtr, from : Option<VertexPtr>) -> Edge {
+impl<E> Edge<E> {
+	pub fn new(index : EdgePtr, from : Option<VertexPtr>, data : E) -> Edge<E> {
 		Edge {
-			index : index,
+			index,
 			is_inf : false,
 			origin : from,
 			next : None,
 			prev : None,
 			twin : None,
 			incident_face : None,
+			data,
 		}
 	}
 	pub fn index(&self) -> EdgePtr {
@@ -34,8 +30,8 @@ impl Edge {
 	}
 }
 
-impl PartialEq for Edge {
-	fn eq(&self, other : &Edge) -> bool {
+impl<E> PartialEq for Edge<E> {
+	fn eq(&self, other : &Edge<E>) -> bool {
 		self.index == other.index
 	}
 }
\ No newline at end of file
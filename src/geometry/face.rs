@@ -2,28 +2,28 @@
 use rand;
 use rand::Rng;
 
-pub type VertexPtr = u32;
-pub type EdgePtr = u32;
-pub type FacePtr = u32;
+use geometry::handle::{EdgePtr, FacePtr, VertexPtr};
 
 #[derive(Clone, Default, PartialEq, Debug)]
-pub struct Face {
+pub struct Face<F = ()> {
 	index : FacePtr,
 	color : [f32; 3],
 	pub inner_component: Vec<EdgePtr>,
 	pub outer_component: Vec<EdgePtr>,
 	pub site : Option<VertexPtr>,
+	pub data : F,
 }
 
-impl Face {
-	pub fn new(index : FacePtr, site : Option<VertexPtr>) -> Face {
+impl<F> Face<F> {
+	pub fn new(index : FacePtr, site : Option<VertexPtr>, data : F) -> Face<F> {
     	let mut rng = rand::thread_rng();
 		Face {
-			index : index,
+			index,
 			color : [rng.next_f32(), rng.next_f32(), rng.next_f32()],
 			inner_component : Vec::new(),
 			outer_component : Vec::new(),
-			site : site,
+			site,
+			data,
 		}
 	}
 	pub fn index(&self) -> FacePtr {
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+/// The mean radius of the Earth, in kilometres.
+pub const EARTH_RADIUS_KM : f64 = 6371.0088;
+
+/// A point on the sphere, by latitude and longitude in degrees. The
+/// geographic counterpart of `Point`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+	pub lat : f64,
+	pub lon : f64,
+}
+
+impl GeoPoint {
+	pub fn new(lat : f64, lon : f64) -> GeoPoint {
+		GeoPoint { lat, lon }
+	}
+
+	/// The point as a unit vector, with the z axis through the north pole
+	/// and the x axis through longitude 0.
+	pub fn to_vector(&self) -> Vector3 {
+		let (lat, lon) = (self.lat.to_radians(), self.lon.to_radians());
+		Vector3::new(lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin())
+	}
+
+	/// The point a vector points at, with longitude in [-180, 180].
+	pub fn from_vector(v : &Vector3) -> GeoPoint {
+		GeoPoint::new(v.z.atan2(v.x.hypot(v.y)).to_degrees(), v.y.atan2(v.x).to_degrees())
+	}
+
+	/// The angle between two points seen from the center, in radians.
+	pub fn angle_to(&self, other : &GeoPoint) -> f64 {
+		self.to_vector().angle_to(&other.to_vector())
+	}
+}
+
+impl fmt::Debug for GeoPoint {
+	fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "({:.4}°, {:.4}°)", self.lat, self.lon)
+	}
+}
+
+/// A vector in three dimensions.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Vector3 {
+	pub x : f64,
+	pub y : f64,
+	pub z : f64,
+}
+
+impl Vector3 {
+	pub fn new(x : f64, y : f64, z : f64) -> Vector3 {
+		Vector3 { x, y, z }
+	}
+
+	pub fn dot(&self, other : &Vector3) -> f64 {
+		self.x * other.x + self.y * other.y + self.z * other.z
+	}
+
+	pub fn cross(&self, other : &Vector3) -> Vector3 {
+		Vector3::new(
+			self.y * other.z - self.z * other.y,
+			self.z * other.x - self.x * other.z,
+			self.x * other.y - self.y * other.x)
+	}
+
+	pub fn length(&self) -> f64 {
+		self.dot(self).sqrt()
+	}
+
+	pub fn unit(&self) -> Vector3 {
+		*self * (1.0 / self.length())
+	}
+
+	/// The angle between two vectors, in radians, accurate for small angles.
+	pub fn angle_to(&self, other : &Vector3) -> f64 {
+		self.cross(other).length().atan2(self.dot(other))
+	}
+}
+
+impl Add<Vector3> for Vector3 {
+	type Output = Vector3;
+
+	fn add(self, rhs : Vector3) -> Vector3 {
+		Vector3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+	}
+}
+
+impl Sub<Vector3> for Vector3 {
+	type Output = Vector3;
+
+	fn sub(self, rhs : Vector3) -> Vector3 {
+		Vector3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+	}
+}
+
+impl Mul<f64> for Vector3 {
+	type Output = Vector3;
+
+	fn mul(self, rhs : f64) -> Vector3 {
+		Vector3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+	}
+}
@@ -0,0 +1,34 @@
+/// Declares a typed index into one of the arenas of the crate.
+///
+/// Every handle wraps a `u32`, but each is its own type so that, for example,
+/// an edge pointer can not be used where a face pointer is expected.
+macro_rules! handle {
+	($(#[$attr:meta])* $name:ident) => {
+		$(#[$attr])*
+		#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+		pub struct $name(pub u32);
+
+		impl $name {
+			/// The position of the element in its arena.
+			pub fn index(self) -> usize {
+				self.0 as usize
+			}
+		}
+
+		impl ::std::fmt::Display for $name {
+			fn fmt(&self, f : &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+				write!(f, "{}", self.0)
+			}
+		}
+	};
+}
+
+handle!(
+	/// Points at a `Vertex` of a `DoublyConnectedEdgeList`.
+	VertexPtr);
+handle!(
+	/// Points at an `Edge` (half-edge) of a `DoublyConnectedEdgeList`.
+	EdgePtr);
+handle!(
+	/// Points at a `Face` of a `DoublyConnectedEdgeList`.
+	FacePtr);
@@ -0,0 +1,136 @@
+use std::cmp::Ordering;
+
+use geometry::point::Point;
+
+/// A static 2-d tree over points, each carrying a small copyable payload.
+///
+/// The tree is stored implicitly: every slice of `nodes` has its splitting
+/// node in the middle, points left of it in the lower half and points right
+/// of it in the upper half. Even depths split on x, odd depths split on y.
+#[derive(Clone, Debug)]
+pub struct KdTree<T : Copy> {
+	nodes : Vec<(Point, T)>,
+}
+
+impl<T : Copy> KdTree<T> {
+	pub fn new(mut items : Vec<(Point, T)>) -> KdTree<T> {
+		build(&mut items, 0);
+		KdTree {
+			nodes : items,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	/// Finds the item closest to `query`, in O(log n) for well spread points.
+	/// Ties are broken in favour of the item found first.
+	pub fn nearest(&self, query : &Point) -> Option<(Point, T)> {
+		let mut best : Option<(f64, usize)> = None;
+		nearest_in(&self.nodes, 0, 0, query, &mut best);
+		best.map(|(_, index)| self.nodes[index])
+	}
+
+	/// Finds the `k` items closest to `query`, ordered from nearest to farthest.
+	pub fn nearest_k(&self, query : &Point, k : usize) -> Vec<(Point, T)> {
+		let mut best : Vec<(f64, usize)> = Vec::with_capacity(k + 1);
+		if k > 0 {
+			nearest_k_in(&self.nodes, 0, 0, query, k, &mut best);
+		}
+		best.into_iter().map(|(_, index)| self.nodes[index]).collect()
+	}
+}
+
+fn axis_cmp(a : &Point, b : &Point, depth : usize) -> Ordering {
+	if depth.is_multiple_of(2) {
+		a.x.cmp(&b.x)
+	} else {
+		a.y.cmp(&b.y)
+	}
+}
+
+fn axis_delta(query : &Point, split : &Point, depth : usize) -> f64 {
+	if depth.is_multiple_of(2) {
+		query.x() - split.x()
+	} else {
+		query.y() - split.y()
+	}
+}
+
+fn distance_sq(a : &Point, b : &Point) -> f64 {
+	let d = *a - *b;
+	d.dot(d)
+}
+
+fn build<T : Copy>(items : &mut [(Point, T)], depth : usize) {
+	if items.len() <= 1 {
+		return;
+	}
+	let mid = items.len() / 2;
+	items.select_nth_unstable_by(mid, |a, b| axis_cmp(&a.0, &b.0, depth));
+	let (lower, upper) = items.split_at_mut(mid);
+	build(lower, depth + 1);
+	build(&mut upper[1..], depth + 1);
+}
+
+fn nearest_in<T : Copy>(nodes : &[(Point, T)], offset : usize, depth : usize,
+		query : &Point, best : &mut Option<(f64, usize)>) {
+
+	if nodes.is_empty() {
+		return;
+	}
+	let mid = nodes.len() / 2;
+	let split = nodes[mid].0;
+
+	let dist = distance_sq(query, &split);
+	if best.is_none_or(|(best_dist, _)| dist < best_dist) {
+		*best = Some((dist, offset + mid));
+	}
+
+	let delta = axis_delta(query, &split, depth);
+	let (near, near_offset, far, far_offset) = if delta < 0.0 {
+		(&nodes[..mid], offset, &nodes[mid + 1..], offset + mid + 1)
+	} else {
+		(&nodes[mid + 1..], offset + mid + 1, &nodes[..mid], offset)
+	};
+
+	nearest_in(near, near_offset, depth + 1, query, best);
+	if best.is_none_or(|(best_dist, _)| delta * delta < best_dist) {
+		nearest_in(far, far_offset, depth + 1, query, best);
+	}
+}
+
+fn nearest_k_in<T : Copy>(nodes : &[(Point, T)], offset : usize, depth : usize,
+		query : &Point, k : usize, best : &mut Vec<(f64, usize)>) {
+
+	if nodes.is_empty() {
+		return;
+	}
+	let mid = nodes.len() / 2;
+	let split = nodes[mid].0;
+
+	//Keep the candidate list sorted by distance and at most k long
+	let dist = distance_sq(query, &split);
+	if best.len() < k || dist < best[best.len() - 1].0 {
+		let position = best.iter().position(|&(d, _)| dist < d).unwrap_or(best.len());
+		best.insert(position, (dist, offset + mid));
+		best.truncate(k);
+	}
+
+	let delta = axis_delta(query, &split, depth);
+	let (near, near_offset, far, far_offset) = if delta < 0.0 {
+		(&nodes[..mid], offset, &nodes[mid + 1..], offset + mid + 1)
+	} else {
+		(&nodes[mid + 1..], offset + mid + 1, &nodes[..mid], offset)
+	};
+
+	nearest_k_in(near, near_offset, depth + 1, query, k, best);
+	if best.len() < k || delta * delta < best[best.len() - 1].0 {
+		nearest_k_in(far, far_offset, depth + 1, query, k, best);
+	}
+}
@@ -1,7 +1,13 @@
+#[macro_use]
+pub mod handle;
 
 pub mod bound;
+pub mod conic;
 pub mod dcel;
 pub mod edge;
 pub mod face;
+pub mod geo;
+pub mod kd_tree;
 pub mod point;
-pub mod vertex;
\ No newline at end of file
+pub mod polygon;
+pub mod vertex;
@@ -1,5 +1,5 @@
 
-use rand::{Rand, Rng, random};
+use rand::{Rand, Rng};
 use std::ops::{Sub, Mul, Add};
 use std::fmt;
 use ordered_float::OrderedFloat;
@@ -37,10 +37,11 @@ impl fmt::Debug for Point {
     }
 }
 
-#[allow(unused_variables)]
+/// A point in the unit square, drawn from the given generator. See
+/// `generate::distributions` for other regions and distributions.
 impl Rand for Point {
     fn rand<R: Rng>(rng: &mut R) -> Point {
-        Point::new(random::<f64>(), random::<f64>())
+        Point::new(rng.gen::<f64>(), rng.gen::<f64>())
     }
 }
 
@@ -93,11 +94,11 @@ impl PartialOrd for Point {
 
 impl Ord for Point {
     fn cmp(&self, other: &Point) -> Ordering {
-        if self.y > other.y { return Ordering::Greater; }
+        if self.y > other.y { Ordering::Greater }
         else if self.y == other.y {
-            if self.x < other.x { return Ordering::Greater; }
-            else if self.x == other.x { return Ordering::Equal; }
-            else { return Ordering::Less; }
-        } else { return Ordering::Less; }
+            if self.x < other.x { Ordering::Greater }
+            else if self.x == other.x { Ordering::Equal }
+            else { Ordering::Less }
+        } else { Ordering::Less }
     }
 }
\ No newline at end of file
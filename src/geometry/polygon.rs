@@ -0,0 +1,290 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use geometry::dcel::{DoublyConnectedEdgeList, VertexPtr};
+use geometry::point::Point;
+
+/// Clips a convex polygon to the half-plane `normal . p <= offset`, keeping
+/// its vertex order. Returns an empty polygon if nothing is left.
+pub fn clip_half_plane(polygon : &[Point], normal : Point, offset : f64) -> Vec<Point> {
+	let mut out = Vec::with_capacity(polygon.len() + 1);
+	for i in 0..polygon.len() {
+		let a = polygon[i];
+		let b = polygon[(i + 1) % polygon.len()];
+		let a_side = normal.dot(a) - offset;
+		let b_side = normal.dot(b) - offset;
+
+		if a_side <= 0.0 {
+			out.push(a);
+		}
+		if (a_side < 0.0 && b_side > 0.0) || (a_side > 0.0 && b_side < 0.0) {
+			let t = a_side / (a_side - b_side);
+			out.push(a + (b - a) * t);
+		}
+	}
+	out
+}
+
+/// Clips a convex polygon to the side of the perpendicular bisector of
+/// `site` and `other` that contains `site`.
+pub fn clip_bisector(polygon : &[Point], site : &Point, other : &Point) -> Vec<Point> {
+	let normal = *other - *site;
+	let offset = (other.dot(*other) - site.dot(*site)) / 2.0;
+	clip_half_plane(polygon, normal, offset)
+}
+
+/// The counter-clockwise rectangle spanned by two corners.
+pub fn rectangle(min : &Point, max : &Point) -> Vec<Point> {
+	vec![
+		Point::new(min.x(), min.y()),
+		Point::new(max.x(), min.y()),
+		Point::new(max.x(), max.y()),
+		Point::new(min.x(), max.y()),
+	]
+}
+
+/// Signed area of a simple polygon, positive when counter-clockwise.
+pub fn signed_area(polygon : &[Point]) -> f64 {
+	let mut sum = 0.0;
+	for i in 0..polygon.len() {
+		sum += polygon[i].cross(polygon[(i + 1) % polygon.len()]);
+	}
+	sum / 2.0
+}
+
+/// The centroid of a simple polygon, or `None` if it has no area.
+pub fn centroid(polygon : &[Point]) -> Option<Point> {
+	let area = signed_area(polygon);
+	if area == 0.0 || !area.is_finite() {
+		return None;
+	}
+	let mut sum = Point::new(0.0, 0.0);
+	for i in 0..polygon.len() {
+		let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+		sum = sum + (a + b) * a.cross(b);
+	}
+	Some(sum * (1.0 / (6.0 * area)))
+}
+
+/// The polar second moment of a simple polygon about `about`: the integral
+/// of the squared distance to `about` over its area. Positive when the
+/// polygon is counter-clockwise.
+pub fn second_moment(polygon : &[Point], about : &Point) -> f64 {
+	let mut sum = 0.0;
+	for i in 0..polygon.len() {
+		let a = polygon[i] - *about;
+		let b = polygon[(i + 1) % polygon.len()] - *about;
+		sum += a.cross(b) * (a.dot(a) + a.dot(b) + b.dot(b));
+	}
+	sum / 12.0
+}
+
+/// Whether `p` lies inside a simple polygon, given in either order.
+pub fn contains(polygon : &[Point], p : &Point) -> bool {
+	let mut inside = false;
+	for i in 0..polygon.len() {
+		let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+		if (a.y() > p.y()) != (b.y() > p.y())
+				&& p.x() < a.x() + (p.y() - a.y()) / (b.y() - a.y()) * (b.x() - a.x()) {
+			inside = !inside;
+		}
+	}
+	inside
+}
+
+/// The distance from `p` to the nearest point of the segment from `a` to `b`.
+pub fn distance_to_segment(p : &Point, a : &Point, b : &Point) -> f64 {
+	let side = *b - *a;
+	let length_sq = side.dot(side);
+	let t = if length_sq == 0.0 { 0.0 } else { ((*p - *a).dot(side) / length_sq).clamp(0.0, 1.0) };
+	let offset = *p - (*a + side * t);
+	offset.x().hypot(offset.y())
+}
+
+/// Joins convex pieces that tile a region into the rings that bound it,
+/// merging corners closer than `eps`. Outer rings are counter-clockwise and
+/// come first, largest first, followed by the clockwise rings around any
+/// holes.
+pub fn union(pieces : &[Vec<Point>], eps : f64) -> Vec<Vec<Point>> {
+	let mut corners : Vec<Point> = pieces.iter().flat_map(|piece| piece.iter().cloned()).collect();
+	corners.sort_by(|a, b| a.x().partial_cmp(&b.x()).unwrap_or(Ordering::Equal));
+
+	//Sides shared by two pieces run both ways and cancel out
+	let mut pool : DoublyConnectedEdgeList = DoublyConnectedEdgeList::with_tolerance(eps);
+	let mut open : BTreeSet<(VertexPtr, VertexPtr)> = BTreeSet::new();
+	for piece in pieces {
+		let piece = insert_points_on_sides(piece, &corners, eps);
+		let ptrs : Vec<VertexPtr> = piece.iter().map(|p| pool.new_vertex(p)).collect();
+		for i in 0..ptrs.len() {
+			let (a, b) = (ptrs[i], ptrs[(i + 1) % ptrs.len()]);
+			if a == b {
+				continue;
+			}
+			if !open.remove(&(b, a)) {
+				open.insert((a, b));
+			}
+		}
+	}
+	let mut sides : BTreeMap<VertexPtr, VertexPtr> = open.into_iter().collect();
+
+	let mut rings : Vec<(f64, Vec<Point>)> = Vec::new();
+	while let Some((&start, _)) = sides.iter().next() {
+		let mut ring = Vec::new();
+		let mut current = start;
+		while let Some(next) = sides.remove(&current) {
+			ring.push(pool[current].point);
+			current = next;
+		}
+		let ring = remove_collinear(&ring, eps);
+		if ring.len() >= 3 {
+			rings.push((signed_area(&ring), ring));
+		}
+	}
+	//Outer rings have positive area, so this puts them first, largest first
+	rings.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(Ordering::Equal));
+	rings.into_iter().map(|(_, ring)| ring).collect()
+}
+
+/// Adds to each side of `polygon` the points of `points` that lie on it,
+/// within `eps`, strictly between its ends. `points` must be sorted by x.
+/// Neighbouring polygons built apart can use this to share corners.
+pub fn insert_points_on_sides(polygon : &[Point], points : &[Point], eps : f64) -> Vec<Point> {
+	let mut out = Vec::with_capacity(polygon.len());
+	for i in 0..polygon.len() {
+		let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+		out.push(a);
+		let side = b - a;
+		let length_sq = side.dot(side);
+		if length_sq <= eps * eps {
+			continue;
+		}
+		let (low, high) = (a.x().min(b.x()) - eps, a.x().max(b.x()) + eps);
+		let first = points.iter().position(|p| p.x() >= low).unwrap_or(points.len());
+		let mut on_side : Vec<(f64, Point)> = points[first..].iter()
+			.take_while(|p| p.x() <= high)
+			.filter_map(|p| {
+				let t = (*p - a).dot(side) / length_sq;
+				let off = side.cross(*p - a).abs() / length_sq.sqrt();
+				let near_end = (*p - a).dot(*p - a) <= eps * eps || (*p - b).dot(*p - b) <= eps * eps;
+				if t > 0.0 && t < 1.0 && off <= eps && !near_end { Some((t, *p)) } else { None }
+			})
+			.collect();
+		on_side.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(Ordering::Equal));
+		out.extend(on_side.into_iter().map(|(_, p)| p));
+	}
+	out
+}
+
+/// Drops the corners of `polygon` that lie within `eps` of the straight
+/// line through their neighbours.
+pub fn remove_collinear(polygon : &[Point], eps : f64) -> Vec<Point> {
+	let mut out = polygon.to_vec();
+	//Drop one corner at a time, so that of two corners next to each other
+	//only one goes
+	let mut i = 0;
+	let mut kept = 0;
+	while out.len() >= 3 && kept < out.len() {
+		let n = out.len();
+		let (before, p, after) = (out[(i + n - 1) % n], out[i % n], out[(i + 1) % n]);
+		let chord = after - before;
+		let length = chord.dot(chord).sqrt();
+		let between = (p - before).dot(chord) >= 0.0 && (after - p).dot(chord) >= 0.0;
+		let duplicate = (p - before).dot(p - before) <= eps * eps;
+		if duplicate || (length > 0.0 && between && chord.cross(p - before).abs() / length <= eps) {
+			out.remove(i % n);
+			kept = 0;
+		} else {
+			i = (i + 1) % n;
+			kept += 1;
+		}
+	}
+	out
+}
+
+/// The convex hull of `points`, counter-clockwise from the leftmost point,
+/// the lowest of them if there are several, by Andrew's monotone chain. Points along the sides are left out.
+pub fn convex_hull(points : &[Point]) -> Vec<Point> {
+	convex_hull_indices(points).into_iter().map(|i| points[i]).collect()
+}
+
+/// Like `convex_hull`, but gives the positions of the corners in `points`.
+/// Repeated points count once, as the first of them, and points that are
+/// not finite are skipped.
+pub fn convex_hull_indices(points : &[Point]) -> Vec<usize> {
+	let mut order : Vec<usize> = (0..points.len())
+		.filter(|&i| points[i].x().is_finite() && points[i].y().is_finite())
+		.collect();
+	order.sort_by(|&i, &j| {
+		let (a, b) = (points[i], points[j]);
+		(a.x(), a.y(), i).partial_cmp(&(b.x(), b.y(), j)).unwrap_or(Ordering::Equal)
+	});
+	order.dedup_by(|j, i| points[*i] == points[*j]);
+	if order.len() < 3 {
+		return order;
+	}
+
+	let turns_left = |a : usize, b : usize, c : usize| (points[b] - points[a]).cross(points[c] - points[a]) > 0.0;
+	let mut hull : Vec<usize> = Vec::with_capacity(order.len() + 1);
+	for pass in 0..2 {
+		let start = hull.len();
+		for k in 0..order.len() {
+			let i = if pass == 0 { order[k] } else { order[order.len() - 1 - k] };
+			while hull.len() >= start + 2 && !turns_left(hull[hull.len() - 2], hull[hull.len() - 1], i) {
+				hull.pop();
+			}
+			hull.push(i);
+		}
+		//The last corner of each chain starts the other
+		hull.pop();
+	}
+	hull
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_union_keeps_every_ring() {
+		let square = |x : f64, y : f64| rectangle(&Point::new(x, y), &Point::new(x + 1.0, y + 1.0));
+
+		//A ring of eight squares around an empty middle
+		let pieces : Vec<Vec<Point>> = (0..9)
+			.filter(|&k| k != 4)
+			.map(|k| square((k % 3) as f64, (k / 3) as f64))
+			.collect();
+		let rings = union(&pieces, 1e-9);
+		assert_eq!(rings.len(), 2);
+		assert!((signed_area(&rings[0]) - 9.0).abs() < 1e-9);
+		assert!((signed_area(&rings[1]) + 1.0).abs() < 1e-9);
+
+		//Two squares apart, the larger first
+		let pieces = vec![square(5.0, 0.0), square(0.0, 0.0), square(1.0, 0.0)];
+		let rings = union(&pieces, 1e-9);
+		assert_eq!(rings.len(), 2);
+		assert!((signed_area(&rings[0]) - 2.0).abs() < 1e-9);
+		assert!((signed_area(&rings[1]) - 1.0).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_convex_hull() {
+		let points = |coordinates : &[(f64, f64)]| coordinates.iter().map(|&(x, y)| Point::new(x, y)).collect::<Vec<_>>();
+
+		//A square with its middle, a point on one side and two repeats, from
+		//the lower of the two leftmost corners
+		let square = points(&[(2.0, 2.0), (0.0, 2.0), (1.0, 1.0), (2.0, 0.0), (0.0, 0.0), (1.0, 0.0), (2.0, 2.0), (0.0, 0.0)]);
+		assert_eq!(convex_hull_indices(&square), vec![4, 3, 0, 1]);
+		assert_eq!(convex_hull(&square), points(&[(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)]));
+
+		//Points on a line give just its ends
+		let line = points(&[(1.0, 1.0), (3.0, 3.0), (0.0, 0.0), (2.0, 2.0)]);
+		assert_eq!(convex_hull_indices(&line), vec![2, 1]);
+
+		//Points that are not finite are skipped, and too few points come back
+		//as they are
+		let broken = points(&[(f64::NAN, 0.0), (1.0, f64::INFINITY), (4.0, 0.0), (0.0, 3.0), (0.0, 0.0)]);
+		assert_eq!(convex_hull_indices(&broken), vec![4, 2, 3]);
+		assert_eq!(convex_hull_indices(&points(&[(1.0, 0.0), (f64::NAN, f64::NAN), (1.0, 0.0)])), vec![0]);
+		assert!(convex_hull(&[]).is_empty());
+	}
+}
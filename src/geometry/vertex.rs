@@ -1,24 +1,23 @@
-
+use geometry::handle::{EdgePtr, VertexPtr};
 use geometry::point::Point;
 
-pub type VertexPtr = u32;
-pub type EdgePtr = u32;
-
 #[derive(Copy, Clone, Debug)]
-pub struct Vertex {
+pub struct Vertex<V = ()> {
 	index : VertexPtr,
 	pub point : Point,
 	pub incident_edge : Option<EdgePtr>,
 	pub is_site : bool,
+	pub data : V,
 }
 
-impl Vertex {
-	pub fn new(index : VertexPtr, point : Point) -> Vertex {
+impl<V> Vertex<V> {
+	pub fn new(index : VertexPtr, point : Point, data : V) -> Vertex<V> {
 		Vertex {
-			index : index, 
-			point : point, 
+			index,
+			point,
 			incident_edge : None,
 			is_site : false,
+			data,
 		}
 	}
 	pub fn index(&self) -> VertexPtr {
@@ -26,8 +25,8 @@ impl Vertex {
 	}
 }
 
-impl PartialEq for Vertex {
-	fn eq(&self, other : &Vertex) -> bool {
+impl<V> PartialEq for Vertex<V> {
+	fn eq(&self, other : &Vertex<V>) -> bool {
 		self.index == other.index
 	}
 }
\ No newline at end of file
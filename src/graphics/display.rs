@@ -1,4 +1,7 @@
 
+//The vertex implementation generated by glium still uses mem::uninitialized
+#![allow(deprecated)]
+
 use glium;
 
 #[derive(Copy, Clone)]
@@ -7,6 +10,8 @@ pub struct OpenglVertex {
 	pub color: [f32; 3],
 }
 
+implement_vertex!(OpenglVertex, position, color);
+
 pub fn opengl_window(input_shapes : Vec<OpenglVertex>) {
 	use glium::Surface;
 
@@ -16,8 +21,6 @@ pub fn opengl_window(input_shapes : Vec<OpenglVertex>) {
 		.with_title("Hello world");
 	let context = glium::glutin::ContextBuilder::new();
 	let display = glium::Display::new(window, context, &events_loop).unwrap();
-	
-	implement_vertex!(OpenglVertex, position, color);
 	let vertex_buffer = glium::VertexBuffer::new(&display, &input_shapes).unwrap();
 	let indices = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
 	//let index_buffer = glium::IndexBuffer::new(vec![0]);
@@ -55,17 +58,13 @@ pub fn opengl_window(input_shapes : Vec<OpenglVertex>) {
 	
 		let mut target = display.draw();
 		target.clear_color(0.05, 0.05, 0.05, 1.0);
-		target.draw(&vertex_buffer, &indices, &program, &uniform! {},
+		target.draw(&vertex_buffer, indices, &program, &uniform! {},
 				&Default::default()).unwrap();
 		target.finish().unwrap();
 	
 		events_loop.poll_events(|ev| {
-			match ev {
-				glium::glutin::Event::WindowEvent { event, .. } => match event {
-					glium::glutin::WindowEvent::Closed => closed = true,
-					_ => (),
-				},
-				_ => (),
+			if let glium::glutin::Event::WindowEvent { event : glium::glutin::WindowEvent::Closed, .. } = ev {
+				closed = true;
 			}
 		})
 	}
@@ -0,0 +1,17 @@
+/*
+The library half of the crate, so that the fuzz targets and benchmarks can
+link against the sweep. The binary in main.rs is a thin front end over it.
+*/
+
+#[macro_use]
+extern crate glium;
+extern crate ordered_float;
+extern crate rand;
+
+pub mod delaunay;
+pub mod file;
+pub mod generate;
+#[macro_use]
+pub mod geometry;
+pub mod graphics;
+pub mod voroni;
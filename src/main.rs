@@ -3,30 +3,34 @@
 https://github.com/glium/glium/tree/master/book
 */
 
-#[macro_use]
-extern crate glium;
-extern crate ordered_float;
 extern crate rand;
+extern crate voroni;
 
 use std::env;
 use std::path::Path;
 
-mod file;
-mod geometry;
-mod graphics;
-mod voroni;
+use rand::{SeedableRng, XorShiftRng};
+use voroni::{file, graphics};
+use voroni::generate::distributions;
+use voroni::geometry::point::Point;
 
 fn main() {
 	let args : Vec<String> = env::args().collect();
-	let sites;
-	if args.len() > 1 {
+	let sites = if args.len() > 1 {
 		let path = Path::new(&args[1]);
-		sites = file::io::read_site_file(path);
+		file::io::read_site_file(path).expect("Couldn't read the site file")
+			.into_iter()
+			.map(|(x, y)| Point::new(x as f64, y as f64))
+			.collect()
 	} else {
-		sites = vec![(0,0)];
-	}
+		//Without a site file, show a fixed set of demo sites
+		let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
+		distributions::poisson_disk(&mut rng, 4.0, 30, &Point::new(-15.0, -15.0), &Point::new(15.0, 15.0))
+	};
 	
-	let mut voroni_process = voroni::voroni_process::VoroniProcess::new(sites);
+	let sites = sites.into_iter().map(|site| (site, ())).collect();
+	let mut voroni_process = voroni::voroni::voroni_process::VoroniProcess::with_data(sites, 0.0);
+	voroni_process.set_verbose(true);
 	voroni_process.execute();
 	println!("{:?}", voroni_process.get_dcel());
 	let gl_vertices = voroni_process.get_dcel().get_opengl_vertices();
@@ -42,5 +46,6 @@ fn main() {
 	graphics::display::opengl_window(gl_vertices);
 	let string = format!("{:?}", voroni_process.get_dcel());
 	file::io::write_output_file(string);
+	file::io::write_file(Path::new("cells.csv"), file::io::format_cells(&voroni_process));
 }
 
@@ -0,0 +1,229 @@
+use std::f64::consts::PI;
+
+use geometry::bound::Bound;
+use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::point::Point;
+use voroni::cells::box_corners;
+use voroni::envelope::{nearest_regions, Feature};
+use voroni::event::SiteId;
+
+pub use voroni::envelope::CurvedEdge;
+
+/// An additively weighted Voronoi diagram, clipped to a box.
+pub struct ApolloniusDiagram {
+	/// The sites and their weights, in input order.
+	pub sites : Vec<(Point, f64)>,
+	/// One face per input site, in input order, carrying its `SiteId`.
+	/// Curved edges appear as chains of straight half-edges.
+	pub dcel : DoublyConnectedEdgeList<(), (), SiteId>,
+	/// Every edge between two cells, once, as arcs of the hyperbola that
+	/// bisects its two sites, or of a straight line if their weights are
+	/// equal.
+	pub edges : Vec<CurvedEdge>,
+	/// The sites with no cell inside the box: those whose disk lies within
+	/// another site's disk, and those outside the box. Sites outside the box
+	/// are left out of the diagram, so they do not bound the other cells.
+	pub empty_cells : Vec<SiteId>,
+	min : Point,
+	max : Point,
+}
+
+/// Computes the Apollonius diagram of weighted sites inside `bound`, where
+/// the distance from a point to a site is `|x - p| - w`. This is the
+/// diagram of disks of radius `w` growing at the same speed, or of seeds of
+/// equal growth rate started at times `-w`.
+///
+/// Cell boundaries are hyperbolic arcs, found exactly: each cell reaches
+/// out from its site to the lower envelope of its bisectors with the other
+/// sites, and where two bisectors cross follows in closed form. This takes
+/// O(n^2 log n) time. The arcs are kept in `edges`, and the faces of `dcel`
+/// follow them as polylines that stray from them by at most `tolerance`.
+pub fn apollonius_diagram(sites : &[(Point, f64)], bound : &Bound, tolerance : f64) -> ApolloniusDiagram {
+	let (min, max, eps) = box_corners(bound);
+	let tolerance = tolerance.max(eps * 1e-3);
+
+	let mut empty_cells = Vec::new();
+	let mut kept = Vec::new();
+	for index in 0..sites.len() {
+		if is_empty(sites, index, &min, &max) {
+			empty_cells.push(SiteId(index as u32));
+		} else {
+			kept.push(index);
+		}
+	}
+	let features : Vec<Feature> = kept.iter().map(|&index| Feature::Point(sites[index].0, sites[index].1, 0.0, 2.0 * PI)).collect();
+	let regions = nearest_regions(&features, &min, &max, eps, tolerance);
+
+	let mut polygons : Vec<(Vec<Point>, SiteId)> = (0..sites.len()).map(|index| (Vec::new(), SiteId(index as u32))).collect();
+	for (polygon, &index) in regions.polygons.into_iter().zip(&kept) {
+		polygons[index].0 = polygon;
+	}
+	let edges = regions.edges.into_iter()
+		.map(|edge| CurvedEdge {
+			sites : (SiteId(kept[edge.features.0] as u32), SiteId(kept[edge.features.1] as u32)),
+			arcs : edge.arcs,
+			points : edge.points,
+		})
+		.collect();
+
+	let mut dcel = DoublyConnectedEdgeList::from_polygons(polygons, eps);
+	for (index, &(site, _)) in sites.iter().enumerate() {
+		let face = dcel.faces[index].index();
+		let vertex = dcel.new_site_vertex(&site);
+		dcel[face].site = Some(vertex);
+	}
+
+	ApolloniusDiagram {
+		sites : sites.to_vec(),
+		dcel,
+		edges,
+		empty_cells,
+		min,
+		max,
+	}
+}
+
+impl ApolloniusDiagram {
+	pub fn get_min(&self) -> Point {
+		self.min
+	}
+
+	pub fn get_max(&self) -> Point {
+		self.max
+	}
+
+	/// The direction of the edge between sites `a` and `b` at `point`, which
+	/// should lie on it.
+	pub fn tangent(&self, a : SiteId, b : SiteId, point : &Point) -> Point {
+		let to_a = (*point - self.sites[a.index()].0).unit();
+		let to_b = (*point - self.sites[b.index()].0).unit();
+		//The edge runs across the gradient of the difference of distances
+		let gradient = to_a - to_b;
+		Point::new(-gradient.y(), gradient.x())
+	}
+}
+
+fn is_outside(site : &Point, min : &Point, max : &Point) -> bool {
+	site.x() < min.x() || site.x() > max.x() || site.y() < min.y() || site.y() > max.y()
+}
+
+fn is_empty(sites : &[(Point, f64)], index : usize, min : &Point, max : &Point) -> bool {
+	let (site, weight) = sites[index];
+	if is_outside(&site, min, max) {
+		return true;
+	}
+	sites.iter().enumerate().any(|(other_index, &(other, other_weight))| {
+		let gap = (other - site).dot(other - site).sqrt();
+		other_index != index && !is_outside(&other, min, max) && (gap < other_weight - weight
+			|| (gap == other_weight - weight && (gap > 0.0 || other_index < index)))
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use geometry::polygon;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+	use voroni::power::power_diagram;
+
+	fn distance(p : &Point, site : &(Point, f64)) -> f64 {
+		(*p - site.0).dot(*p - site.0).sqrt() - site.1
+	}
+
+	#[test]
+	fn test_equal_weights_match_voroni() {
+		let mut rng = XorShiftRng::from_seed([1, 4, 1, 4]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+		let bound = Bound::from_corners(&min, &max);
+		let sites : Vec<(Point, f64)> = distributions::uniform_box(&mut rng, 20, &min, &max).into_iter()
+			.map(|site| (site, 1.0))
+			.collect();
+
+		let diagram = apollonius_diagram(&sites, &bound, 1e-6);
+		let power = power_diagram(&sites, &bound);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		for (face, cell) in diagram.dcel.faces.iter().zip(&power.cells) {
+			let area = face_area(&diagram.dcel, face.index());
+			assert!((area - polygon::signed_area(cell)).abs() < 1e-6);
+		}
+	}
+
+	#[test]
+	fn test_weighted_cells() {
+		let mut rng = XorShiftRng::from_seed([2, 7, 1, 8]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(20.0, 20.0));
+		let bound = Bound::from_corners(&min, &max);
+		let mut sites : Vec<(Point, f64)> = distributions::poisson_disk(&mut rng, 3.0, 30, &min, &max).into_iter()
+			.map(|site| (site, rng.gen_range(0.0, 1.5)))
+			.collect();
+		//A seed swallowed by an earlier, nearby one
+		sites.push((sites[0].0 + Point::new(0.1, 0.0), sites[0].1 - 0.5));
+		let swallowed = SiteId(sites.len() as u32 - 1);
+
+		let tolerance = 1e-3;
+		let diagram = apollonius_diagram(&sites, &bound, tolerance);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(diagram.empty_cells, vec![swallowed]);
+
+		let total : f64 = diagram.dcel.faces.iter().map(|face| face_area(&diagram.dcel, face.index())).sum();
+		assert!((total - 400.0).abs() < 1e-6);
+
+		//Every point on an edge is about as far from both its sites
+		for edge in &diagram.edges {
+			for point in &edge.points {
+				let gap = distance(point, &sites[edge.sites.0.index()]) - distance(point, &sites[edge.sites.1.index()]);
+				assert!(gap.abs() < 1e-6);
+			}
+		}
+		//And the polyline stays within the tolerance of the curve
+		for edge in &diagram.edges {
+			let (a, b) = (&sites[edge.sites.0.index()], &sites[edge.sites.1.index()]);
+			for pair in edge.points.windows(2) {
+				let middle = (pair[0] + pair[1]) * 0.5;
+				let gap = (distance(&middle, a) - distance(&middle, b)).abs();
+				assert!(gap < 4.0 * tolerance);
+			}
+		}
+	}
+
+	#[test]
+	fn test_sites_on_the_box() {
+		let bound = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+		//A site on a corner, and one on a side
+		for &site in &[Point::new(0.0, 0.0), Point::new(0.0, 5.0)] {
+			let sites = [(site, 0.0), (Point::new(6.0, 6.0), 0.0)];
+			let diagram = apollonius_diagram(&sites, &bound, 1e-6);
+			let power = power_diagram(&sites, &bound);
+			assert_eq!(diagram.dcel.validate(), Ok(()));
+				for (face, cell) in diagram.dcel.faces.iter().zip(&power.cells) {
+				assert!((face_area(&diagram.dcel, face.index()) - polygon::signed_area(cell)).abs() < 1e-6);
+			}
+		}
+
+		//A site outside the box has no cell and leaves no hole
+		let sites = [(Point::new(5.0, 5.0), 0.0), (Point::new(-1.0, 5.0), 0.0)];
+		let diagram = apollonius_diagram(&sites, &bound, 1e-6);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(diagram.empty_cells, vec![SiteId(1)]);
+		let total : f64 = diagram.dcel.faces.iter().map(|face| face_area(&diagram.dcel, face.index())).sum();
+		assert!((total - 100.0).abs() < 1e-6);
+	}
+
+	fn face_area(dcel : &DoublyConnectedEdgeList<(), (), SiteId>, face : ::geometry::dcel::FacePtr) -> f64 {
+		let start = match dcel[face].outer_component.first() {
+			Some(&edge) => edge,
+			None => return 0.0,
+		};
+		let mut corners = Vec::new();
+		let mut edge = start;
+		loop {
+			corners.push(dcel[dcel[edge].origin.unwrap()].point);
+			edge = dcel[edge].next.unwrap();
+			if edge == start {
+				break;
+			}
+		}
+		polygon::signed_area(&corners)
+	}
+}
@@ -0,0 +1,311 @@
+use std::cmp::Ordering;
+
+use geometry::bound::Bound;
+use geometry::dcel::{DoublyConnectedEdgeList, FacePtr};
+use geometry::point::Point;
+use geometry::polygon;
+
+/// Clips every face of a finished diagram to the box of `bound`, returning
+/// the counter-clockwise polygon of each face, indexed like `dcel.faces`.
+///
+/// Each cell is cut from the box by the bisectors between its site and the
+/// sites of the faces across its edges, so unbounded cells need no special
+/// handling. A cell that misses the box, or a face without a site, gives an
+/// empty polygon, as does every face of an empty `bound`.
+pub fn clip_cells<V, E, F>(dcel : &DoublyConnectedEdgeList<V, E, F>, bound : &Bound) -> Vec<Vec<Point>> {
+	let (min, max) = match (bound.get_min(), bound.get_max()) {
+		(Some(min), Some(max)) => (min, max),
+		_ => return vec![Vec::new(); dcel.faces.len()],
+	};
+
+	//The sites across the edges of each face
+	let mut neighbors : Vec<Vec<Point>> = vec![Vec::new(); dcel.faces.len()];
+	for edge in &dcel.edges {
+		let other_face = edge.twin.and_then(|twin| dcel[twin].incident_face);
+		if let (Some(face), Some(other_face)) = (edge.incident_face, other_face) {
+			if let Some(site) = dcel[other_face].site {
+				neighbors[face.index()].push(dcel[site].point);
+			}
+		}
+	}
+
+	let rectangle = polygon::rectangle(&min, &max);
+	dcel.faces.iter()
+		.map(|face| match face.site {
+			Some(site) => {
+				let site = dcel[site].point;
+				let bisectors = neighbors[face.index().index()].iter()
+					.filter(|&&other| other != site)
+					.map(|&other| (other - site, (other.dot(other) - site.dot(site)) / 2.0));
+				cut_convex(rectangle.clone(), bisectors)
+			},
+			None => Vec::new(),
+		})
+		.collect()
+}
+
+/// The corners of the box of `bound`, and the tolerance diagrams cut from
+/// it use to merge corners: a billionth of its larger side, or of 1 if that
+/// is larger. An empty `bound` gives a box with both corners at the origin.
+pub fn box_corners(bound : &Bound) -> (Point, Point, f64) {
+	let (min, max) = match (bound.get_min(), bound.get_max()) {
+		(Some(min), Some(max)) => (min, max),
+		_ => (Point::new(0.0, 0.0), Point::new(0.0, 0.0)),
+	};
+	let extent = (max - min).x().abs().max((max - min).y().abs()).max(1.0);
+	(min, max, extent * 1e-9)
+}
+
+/// Cuts the convex polygon `cell` down to each half-plane
+/// `normal . p <= offset` in turn, stopping once nothing is left.
+pub fn cut_convex<I>(cell : Vec<Point>, half_planes : I) -> Vec<Point>
+	where I : IntoIterator<Item = (Point, f64)> {
+	let mut cell = cell;
+	for (normal, offset) in half_planes {
+		if cell.is_empty() {
+			break;
+		}
+		cell = polygon::clip_half_plane(&cell, normal, offset);
+	}
+	cell
+}
+
+/// Adds to every ring the corners of the others that lie part way along its
+/// sides, within `eps`. Cells built apart can then share their sides when
+/// they go into one diagram.
+pub fn share_corners(rings : Vec<&mut Vec<Point>>, eps : f64) {
+	let mut corners : Vec<Point> = rings.iter().flat_map(|ring| ring.iter().cloned()).collect();
+	corners.sort_by(|a, b| a.x().partial_cmp(&b.x()).unwrap_or(Ordering::Equal));
+	for ring in rings {
+		*ring = polygon::insert_points_on_sides(ring, &corners, eps);
+	}
+}
+
+/// A diagram cut down to a region. The faces match the faces of the diagram
+/// it came from, and each carries the pointer of its original.
+pub struct ClippedDiagram {
+	pub dcel : DoublyConnectedEdgeList<(), (), FacePtr>,
+	/// The faces left with nothing inside the region.
+	pub dropped : Vec<FacePtr>,
+}
+
+/// Clips every cell of a finished diagram to a simple polygon, minus the
+/// simple polygons in `holes`, which must lie inside it and apart from one
+/// another. Rings may be given in either order.
+///
+/// A cell may come out in several pieces, each an outer component of its
+/// face, and a cell around a hole gets an inner component for it. Cells
+/// that keep something keep their site, even when it falls outside.
+pub fn clip_to_polygon<V, E, F>(dcel : &DoublyConnectedEdgeList<V, E, F>, boundary : &[Point], holes : &[Vec<Point>]) -> ClippedDiagram {
+	//The region lies to the left of every one of its sides
+	let mut rings : Vec<Vec<Point>> = Vec::with_capacity(holes.len() + 1);
+	rings.push(oriented(boundary, true));
+	rings.extend(holes.iter().map(|hole| oriented(hole, false)));
+
+	let mut bound = Bound::new();
+	for p in boundary {
+		bound.update(p);
+	}
+	let eps = match (bound.get_min(), bound.get_max()) {
+		(Some(min), Some(max)) => (max.x() - min.x()).max(max.y() - min.y()) * 1e-9,
+		_ => 0.0,
+	};
+
+	let mut pieces : Vec<Vec<Vec<Point>>> = clip_cells(dcel, &bound).iter()
+		.map(|cell| if cell.len() < 3 { Vec::new() } else { intersect_region(cell, &rings, eps) })
+		.collect();
+
+	//Cells cut apart along a side must still share its corners
+	share_corners(pieces.iter_mut().flatten().collect(), eps);
+	let is_dropped : Vec<bool> = pieces.iter().map(|rings| rings.is_empty()).collect();
+	let dropped = dcel.faces.iter().filter(|face| is_dropped[face.index().index()]).map(|face| face.index()).collect();
+	let faces = pieces.into_iter().zip(&dcel.faces)
+		.map(|(rings, face)| (rings, face.index()))
+		.collect();
+
+	let mut clipped : DoublyConnectedEdgeList<(), (), FacePtr> = DoublyConnectedEdgeList::from_rings(faces, eps);
+	for face in &dcel.faces {
+		if let (Some(site), false) = (face.site, is_dropped[face.index().index()]) {
+			let point = dcel[site].point;
+			let vertex = clipped.new_site_vertex(&point);
+			clipped[face.index()].site = Some(vertex);
+		}
+	}
+	ClippedDiagram { dcel : clipped, dropped }
+}
+
+fn oriented(ring : &[Point], counter_clockwise : bool) -> Vec<Point> {
+	let mut ring = ring.to_vec();
+	if (polygon::signed_area(&ring) > 0.0) != counter_clockwise {
+		ring.reverse();
+	}
+	ring
+}
+
+fn sides(ring : &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+	(0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()]))
+}
+
+//Where along `a`-`b` it meets the other side, or where that side ends on it
+fn split_points(a : &Point, b : &Point, others : &[(Point, Point)], eps : f64) -> Vec<f64> {
+	let side = *b - *a;
+	let length_sq = side.dot(side);
+	let mut cuts = vec![0.0, 1.0];
+	for &(p, q) in others {
+		let other = q - p;
+		let denominator = side.cross(other);
+		if denominator.abs() > eps * length_sq.sqrt() * other.dot(other).sqrt() {
+			let t = (p - *a).cross(other) / denominator;
+			let u = (p - *a).cross(side) / denominator;
+			if t > 0.0 && t < 1.0 && (0.0..=1.0).contains(&u) {
+				cuts.push(t);
+			}
+		}
+		for end in &[p, q] {
+			if polygon::distance_to_segment(end, a, b) <= eps {
+				cuts.push(((*end - *a).dot(side) / length_sq).clamp(0.0, 1.0));
+			}
+		}
+	}
+	cuts.sort_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+	cuts.dedup_by(|x, y| (*x - *y) * length_sq.sqrt() <= eps);
+	cuts
+}
+
+//The rings of the part of a convex cell inside the region, with the region
+//given as an outer ring followed by holes, each with the region on its left
+fn intersect_region(cell : &[Point], rings : &[Vec<Point>], eps : f64) -> Vec<Vec<Point>> {
+	let cell_sides : Vec<(Point, Point)> = sides(cell).collect();
+	let region_sides : Vec<(Point, Point)> = rings.iter().flat_map(|ring| sides(ring)).collect();
+	let in_region = |p : &Point| polygon::contains(&rings[0], p) && !rings[1..].iter().any(|hole| polygon::contains(hole, p));
+	let in_cell = |p : &Point| cell_sides.iter().all(|&(a, b)| (b - a).cross(*p - a) > eps * (b - a).dot(b - a).sqrt());
+
+	let mut kept : Vec<(Point, Point)> = Vec::new();
+	//Sides of the cell inside the region, or running along it the same way
+	for &(a, b) in &cell_sides {
+		let cuts = split_points(&a, &b, &region_sides, eps);
+		for pair in cuts.windows(2) {
+			let (start, end) = (a + (b - a) * pair[0], a + (b - a) * pair[1]);
+			let middle = (start + end) * 0.5;
+			let along = region_sides.iter().find(|&&(p, q)| polygon::distance_to_segment(&middle, &p, &q) <= eps);
+			let keep = match along {
+				Some(&(p, q)) => (q - p).dot(b - a) > 0.0,
+				None => in_region(&middle),
+			};
+			if keep {
+				kept.push((start, end));
+			}
+		}
+	}
+	//Sides of the region strictly inside the cell
+	for &(p, q) in &region_sides {
+		let cuts = split_points(&p, &q, &cell_sides, eps);
+		for pair in cuts.windows(2) {
+			let (start, end) = (p + (q - p) * pair[0], p + (q - p) * pair[1]);
+			if in_cell(&((start + end) * 0.5)) {
+				kept.push((start, end));
+			}
+		}
+	}
+
+	//Chain the kept sides into rings
+	let mut out = Vec::new();
+	while let Some((start, mut end)) = kept.pop() {
+		let mut ring = vec![start];
+		let near = |x : &Point, y : &Point| (*x - *y).dot(*x - *y) <= eps * eps;
+		let closed = loop {
+			if near(&end, &start) {
+				break true;
+			}
+			match kept.iter().position(|&(from, _)| near(&from, &end)) {
+				Some(i) => {
+					let (from, to) = kept.swap_remove(i);
+					ring.push(from);
+					end = to;
+				},
+				None => break false,
+			}
+		};
+		//A chain that never closes is a scrap left by rounding, not a ring
+		if !closed {
+			continue;
+		}
+		let ring = polygon::remove_collinear(&ring, eps);
+		if ring.len() >= 3 && polygon::signed_area(&ring).abs() > eps * eps {
+			out.push(ring);
+		}
+	}
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use rand::{SeedableRng, XorShiftRng};
+	use voroni::event::SiteId;
+	use voroni::voroni_process::VoroniProcess;
+
+	#[test]
+	fn test_cells_tile_the_bound() {
+		let mut rng = XorShiftRng::from_seed([4, 4, 2, 1]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 8.0));
+		//Some sites fall outside the bound, and their cells may miss it
+		let sites = distributions::uniform_box(&mut rng, 60, &Point::new(-2.0, -2.0), &Point::new(12.0, 10.0));
+		let mut vp = VoroniProcess::with_data(sites.into_iter().map(|site| (site, ())).collect(), 0.0);
+		vp.execute();
+
+		let cells = clip_cells(vp.get_dcel(), &Bound::from_corners(&min, &max));
+		assert_eq!(cells.len(), vp.get_dcel().faces.len());
+		let area : f64 = cells.iter().map(|cell| polygon::signed_area(cell)).sum();
+		assert!((area - 80.0).abs() < 1e-9);
+		for (face, cell) in vp.get_dcel().faces.iter().zip(&cells) {
+			let site = vp.get_dcel()[face.site.unwrap()].point;
+			if site.x() > min.x() && site.x() < max.x() && site.y() > min.y() && site.y() < max.y() {
+				assert!(polygon::signed_area(cell) > 0.0);
+			}
+		}
+	}
+
+	#[test]
+	fn test_clip_to_polygon_with_hole() {
+		//An L shape, given clockwise, around a small square hole
+		let boundary = vec![Point::new(0.0, 0.0), Point::new(0.0, 10.0), Point::new(7.0, 10.0),
+			Point::new(7.0, 7.0), Point::new(10.0, 7.0), Point::new(10.0, 0.0)];
+		let holes = vec![polygon::rectangle(&Point::new(4.5, 4.5), &Point::new(5.5, 5.5))];
+
+		let sites = vec![Point::new(5.0, 5.0), Point::new(-1.0, -1.0), Point::new(11.0, -1.0),
+			Point::new(11.0, 11.0), Point::new(-1.0, 11.0), Point::new(30.0, 30.0)];
+		let mut vp = VoroniProcess::with_data(sites.into_iter().map(|site| (site, ())).collect(), 0.0);
+		vp.execute();
+		let clipped = clip_to_polygon(vp.get_dcel(), &boundary, &holes);
+		assert_eq!(clipped.dcel.validate(), Ok(()));
+		assert_eq!(clipped.dropped, vec![vp.get_site_face(SiteId(5)).unwrap()]);
+
+		let area = |dcel : &DoublyConnectedEdgeList<(), (), FacePtr>| -> f64 {
+			let mut area = 0.0;
+			for edge in &dcel.edges {
+				if let (Some(_), Some(a), Some(b)) = (edge.incident_face, edge.origin, edge.twin.and_then(|twin| dcel[twin].origin)) {
+					area += dcel[a].point.cross(dcel[b].point) / 2.0;
+				}
+			}
+			area
+		};
+		assert!((area(&clipped.dcel) - 90.0).abs() < 1e-9);
+
+		let centre = &clipped.dcel[vp.get_site_face(SiteId(0)).unwrap()];
+		assert_eq!(centre.inner_component.len(), 1);
+		let corner = &clipped.dcel[vp.get_site_face(SiteId(1)).unwrap()];
+		assert!(!corner.outer_component.is_empty());
+		assert_eq!(clipped.dcel[corner.site.unwrap()].point, Point::new(-1.0, -1.0));
+
+		//Many sites cut every which way
+		let mut rng = XorShiftRng::from_seed([7, 1, 3, 9]);
+		let sites = distributions::uniform_box(&mut rng, 80, &Point::new(-2.0, -2.0), &Point::new(12.0, 12.0));
+		let mut vp = VoroniProcess::with_data(sites.into_iter().map(|site| (site, ())).collect(), 0.0);
+		vp.execute();
+		let clipped = clip_to_polygon(vp.get_dcel(), &boundary, &holes);
+		assert_eq!(clipped.dcel.validate(), Ok(()));
+		assert!((area(&clipped.dcel) - 90.0).abs() < 1e-9);
+	}
+}
@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use geometry::bound::Bound;
+use geometry::point::Point;
+use voroni::cells;
+use voroni::event::SiteId;
+use voroni::geometry::get_distance;
+use voroni::voroni_process::VoroniProcess;
+
+/// Where the centre of an empty circle may go.
+#[derive(Copy, Clone)]
+pub enum Region<'a> {
+	/// The box of a bound.
+	Box(&'a Bound),
+	/// A simple polygon, with its corners in either order.
+	Polygon(&'a [Point]),
+}
+
+impl<'a> From<&'a Bound> for Region<'a> {
+	fn from(bound : &'a Bound) -> Region<'a> {
+		Region::Box(bound)
+	}
+}
+
+impl<'a> From<&'a [Point]> for Region<'a> {
+	fn from(polygon : &'a [Point]) -> Region<'a> {
+		Region::Polygon(polygon)
+	}
+}
+
+/// A circle with no site inside it, and the sites on its edge that stop it
+/// growing. When the centre lies on the edge of the region, fewer than three
+/// sites may hold it.
+#[derive(Clone, Debug)]
+pub struct EmptyCircle {
+	pub center : Point,
+	pub radius : f64,
+	pub sites : Vec<SiteId>,
+}
+
+/// Finds the largest circle centred in `region` with no site of a finished
+/// diagram inside it, or `None` if there are no sites or the region is empty.
+///
+/// Within each cell, clipped to the region, the site is farthest at a corner.
+/// The corners are the Voronoi vertices in the region, the points where the
+/// Voronoi edges cross its edge, and the corners of the region itself, so
+/// only those are tried.
+pub fn largest_empty_circle<'a, F, R : Into<Region<'a>>>(vp : &VoroniProcess<F>, region : R) -> Option<EmptyCircle> {
+	let dcel = vp.get_dcel();
+	//Pairs of a candidate centre and the site of the cell it lies in
+	let mut candidates : Vec<(Point, Point)> = Vec::new();
+	let mut bound = Bound::new();
+	match region.into() {
+		Region::Box(region) => {
+			if let (Some(min), Some(max)) = (region.get_min(), region.get_max()) {
+				bound = Bound::from_corners(&min, &max);
+			}
+			for (face, cell) in dcel.faces.iter().zip(cells::clip_cells(dcel, region)) {
+				if let Some(site) = face.site {
+					candidates.extend(cell.into_iter().map(|corner| (corner, dcel[site].point)));
+				}
+			}
+		},
+		Region::Polygon(ring) => {
+			for p in ring {
+				bound.update(p);
+			}
+			let clipped = cells::clip_to_polygon(dcel, ring, &[]).dcel;
+			for edge in &clipped.edges {
+				let site = edge.incident_face.and_then(|face| clipped[face].site);
+				if let (Some(origin), Some(site)) = (edge.origin, site) {
+					candidates.push((clipped[origin].point, clipped[site].point));
+				}
+			}
+		},
+	}
+
+	let (center, site) = candidates.into_iter()
+		.fold(None, |best : Option<(Point, Point)>, (corner, site)| match best {
+			Some((center, other)) if get_distance(&center, &other) >= get_distance(&corner, &site) => best,
+			_ => Some((corner, site)),
+		})?;
+	let radius = get_distance(&center, &site);
+
+	//Every site as near as the one found also holds the circle
+	let (min, max) = (bound.get_min()?, bound.get_max()?);
+	let eps = (max.x() - min.x()).max(max.y() - min.y()) * 1e-9;
+	let mut face_sites = HashMap::new();
+	for (id, point, face) in vp.get_cells() {
+		if (get_distance(&center, &point) - radius).abs() <= eps {
+			face_sites.entry(face).or_insert(id);
+		}
+	}
+	let mut sites : Vec<SiteId> = face_sites.into_values().collect();
+	sites.sort();
+	Some(EmptyCircle { center, radius, sites })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use geometry::polygon;
+	use rand::{SeedableRng, XorShiftRng};
+
+	//The largest distance to the nearest site over a fine grid of the region
+	fn sampled_best(sites : &[Point], inside : &dyn Fn(&Point) -> bool, min : &Point, max : &Point) -> f64 {
+		let mut best : f64 = 0.0;
+		for i in 0..=200 {
+			for j in 0..=200 {
+				let p = Point::new(min.x() + (max.x() - min.x()) * i as f64 / 200.0, min.y() + (max.y() - min.y()) * j as f64 / 200.0);
+				if inside(&p) {
+					let nearest = sites.iter().map(|site| get_distance(site, &p)).fold(f64::INFINITY, f64::min);
+					best = best.max(nearest);
+				}
+			}
+		}
+		best
+	}
+
+	#[test]
+	fn test_largest_empty_circle() {
+		let square = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+		let mut vp = VoroniProcess::new(vec![(0, 0), (10, 0), (0, 10), (10, 10), (0, 0)]);
+		vp.execute();
+		let circle = largest_empty_circle(&vp, &square).unwrap();
+		assert!(get_distance(&circle.center, &Point::new(5.0, 5.0)) < 1e-9);
+		assert_eq!(circle.sites, vec![SiteId(0), SiteId(1), SiteId(2), SiteId(3)]);
+
+		let mut rng = XorShiftRng::from_seed([6, 2, 8, 3]);
+		let sites = distributions::uniform_box(&mut rng, 40, &Point::new(-1.0, -1.0), &Point::new(11.0, 11.0));
+		let mut vp = VoroniProcess::with_data(sites.iter().map(|site| (*site, ())).collect(), 0.0);
+		vp.execute();
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+		let step = 10.0 / 200.0;
+
+		let circle = largest_empty_circle(&vp, &square).unwrap();
+		let best = sampled_best(&sites, &|_| true, &min, &max);
+		assert!(circle.radius >= best - 1e-9 && circle.radius <= best + step);
+		assert!(!circle.sites.is_empty());
+
+		//An L shape, which leaves out the top right of the square
+		let ring = vec![Point::new(0.0, 0.0), Point::new(10.0, 0.0), Point::new(10.0, 4.0),
+			Point::new(4.0, 4.0), Point::new(4.0, 10.0), Point::new(0.0, 10.0)];
+		let circle = largest_empty_circle(&vp, &ring[..]).unwrap();
+		assert!(polygon::contains(&ring, &circle.center) || ring.iter().enumerate()
+			.any(|(i, a)| polygon::distance_to_segment(&circle.center, a, &ring[(i + 1) % ring.len()]) < 1e-9));
+		let best = sampled_best(&sites, &|p| polygon::contains(&ring, p), &min, &max);
+		assert!(circle.radius >= best - 1e-9 && circle.radius <= best + step);
+	}
+}
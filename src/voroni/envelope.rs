@@ -0,0 +1,633 @@
+use std::f64::consts::PI;
+
+use geometry::conic::ConicArc;
+use geometry::dcel::{DoublyConnectedEdgeList, VertexPtr};
+use geometry::point::Point;
+use voroni::event::SiteId;
+
+//Halvings of a hyperbolic arc before it is drawn however far it turns
+const MAX_SPLITS : u32 = 8;
+
+/// A part of a site that can be the nearest part of it to a point, such as
+/// an end or a side of a segment. Every feature is seen only from a region
+/// of its own, on which its distance is a simple formula.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Feature {
+	/// A point with an additive weight, as `(point, weight, from, width)`, at
+	/// distance `|x - point| - weight` from `x`. It is seen in the wedge of
+	/// directions from the angle `from` counter-clockwise through `width`,
+	/// which must be at most π, or else a whole turn.
+	Point(Point, f64, f64, f64),
+	/// The side of the segment from the first point to the second that lies
+	/// to its right, at the distance of `x` from the segment's line. It is
+	/// seen where the foot of that distance lies on the segment.
+	Side(Point, Point),
+}
+
+/// A curved edge between two cells, given exactly by conic arcs.
+pub struct CurvedEdge {
+	/// The sites on the left and right of the edge, as it runs along `arcs`.
+	pub sites : (SiteId, SiteId),
+	/// The exact edge, end to end.
+	pub arcs : Vec<ConicArc>,
+	/// The edge as a polyline, matching its chain of half-edges in the
+	/// diagram.
+	pub points : Vec<Point>,
+}
+
+/// An edge between the regions of two features.
+pub struct FeatureEdge {
+	/// The features on the left and right of the edge, as it runs along
+	/// `arcs`.
+	pub features : (usize, usize),
+	/// The exact edge, end to end. Every arc is a straight segment, a parabola
+	/// or a hyperbola.
+	pub arcs : Vec<ConicArc>,
+	/// The edge as a polyline, with the same points that bound both regions.
+	pub points : Vec<Point>,
+}
+
+/// The regions nearest to each of a set of features, inside a box.
+pub struct Regions {
+	/// The counter-clockwise polygon of each feature's region, in input order,
+	/// with curved sides flattened. Neighbouring regions have the same points
+	/// along the sides they share. A feature with nothing inside the box has
+	/// an empty polygon.
+	pub polygons : Vec<Vec<Point>>,
+	/// Every edge between the regions of two features, once.
+	pub edges : Vec<FeatureEdge>,
+}
+
+//A function of where the sweep of a feature has got to: for a point, of
+//the angle θ of its ray, as `a + b . (cos θ, sin θ)`, and for a side, of
+//the distance s along it, as `a + b s + c s^2`
+#[derive(Copy, Clone, Debug)]
+enum Form {
+	Linear(f64, Point),
+	Quadratic(f64, f64, f64),
+}
+
+impl Form {
+	fn at(&self, x : f64) -> f64 {
+		match *self {
+			Form::Linear(a, b) => a + b.x() * x.cos() + b.y() * x.sin(),
+			Form::Quadratic(a, b, c) => a + x * (b + x * c),
+		}
+	}
+
+	//Where the form is zero, strictly between `lo` and `hi`, in order
+	fn roots(&self, lo : f64, hi : f64) -> Vec<f64> {
+		let mut roots = Vec::new();
+		match *self {
+			Form::Linear(a, b) => {
+				let length = b.dot(b).sqrt();
+				if length > 0.0 && a.abs() <= length {
+					let (centre, spread) = (b.y().atan2(b.x()), (-a / length).acos());
+					for turn in -2..3 {
+						let base = centre + 2.0 * PI * turn as f64;
+						roots.push(base - spread);
+						roots.push(base + spread);
+					}
+				}
+			},
+			Form::Quadratic(a, b, c) => if c == 0.0 {
+				if b != 0.0 {
+					roots.push(-a / b);
+				}
+			} else {
+				let discriminant = b * b - 4.0 * a * c;
+				if discriminant >= 0.0 {
+					//The stable pair of formulas, which lose no digits to
+					//cancellation
+					let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+					roots.push(q / c);
+					if q != 0.0 {
+						roots.push(a / q);
+					}
+				}
+			},
+		}
+		roots.retain(|&x| x > lo && x < hi);
+		roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+		roots.dedup();
+		roots
+	}
+}
+
+//How far the sweep of a feature goes before meeting a wall: along the ray
+//at angle θ, `k / (b + c . (cos θ, sin θ))`, and along the normal at s,
+//`a + b s + c s^2`
+#[derive(Copy, Clone, Debug)]
+enum Reach {
+	Polar(f64, f64, Point),
+	Normal(f64, f64, f64),
+}
+
+impl Reach {
+	fn at(&self, x : f64) -> f64 {
+		match *self {
+			Reach::Polar(k, b, c) => k / Form::Linear(b, c).at(x),
+			Reach::Normal(a, b, c) => Form::Quadratic(a, b, c).at(x),
+		}
+	}
+
+	//The reach at `x`, or infinity where rounding near the edge of where the
+	//wall counts has sent it behind the sweep
+	fn ahead(&self, x : f64) -> f64 {
+		let t = self.at(x);
+		if t >= 0.0 { t } else { f64::INFINITY }
+	}
+
+	//A form that is zero where the two reaches are equal
+	fn meets(&self, other : &Reach) -> Form {
+		match (*self, *other) {
+			(Reach::Polar(k1, b1, c1), Reach::Polar(k2, b2, c2)) => Form::Linear(k1 * b2 - k2 * b1, c2 * k1 - c1 * k2),
+			(Reach::Normal(a1, b1, c1), Reach::Normal(a2, b2, c2)) => Form::Quadratic(a1 - a2, b1 - b2, c1 - c2),
+			_ => unreachable!(),
+		}
+	}
+}
+
+//Something the sweep of a feature can run into: another feature, or a side
+//of the box. It only counts where every condition is positive, or at least
+//zero when not strict.
+struct Wall {
+	across : Option<usize>,
+	reach : Reach,
+	conditions : Vec<(Form, bool)>,
+}
+
+//A stretch of the sweep from one value to another, and the wall it meets
+//there, if any
+type Envelope = Vec<(f64, f64, Option<usize>)>;
+
+/// Finds the region of the box from `min` to `max` nearest to each of
+/// `features`, where a feature's distance only counts in its own region.
+/// Every feature must lie inside the box.
+///
+/// Each region is swept out from its feature, along rays from a point or
+/// along the normals of a side. Along every ray or normal the distance to
+/// each other feature starts to win at most once, at a point given in closed
+/// form, so a region reaches to the lower envelope of those points. The
+/// envelope is found by divide and conquer, in O(n log n) time for n
+/// features, as any two of the functions cross at most twice at places that
+/// are also known in closed form. That makes O(n^2 log n) time in all.
+///
+/// Every side of a region is a straight segment, a parabola or a hyperbola,
+/// and comes out as exact conic arcs. Each shared side is computed once,
+/// from the feature listed first, between corners merged within `eps`, and
+/// flattened to within `tolerance` for the polygons.
+pub fn nearest_regions(features : &[Feature], min : &Point, max : &Point, eps : f64, tolerance : f64) -> Regions {
+	let mut pool : DoublyConnectedEdgeList = DoublyConnectedEdgeList::with_tolerance(eps);
+	let boundaries : Vec<Vec<(VertexPtr, VertexPtr, Option<usize>)>> = (0..features.len())
+		.map(|index| boundary(features, index, min, max, &mut pool))
+		.collect();
+
+	let mut polygons = Vec::with_capacity(features.len());
+	let mut edges = Vec::new();
+	for (index, sides) in boundaries.iter().enumerate() {
+		let mut polygon = Vec::new();
+		for &(start, end, across) in sides {
+			let (from, to) = (pool[start].point, pool[end].point);
+			let points = match across {
+				Some(other) if other > index => {
+					let arcs = sweep_arcs(features, index, other, &from, &to);
+					let points = flatten(&arcs, tolerance);
+					edges.push(FeatureEdge {
+						features : (index, other),
+						arcs,
+						points : points.clone(),
+					});
+					points
+				},
+				Some(other) => {
+					//Traced from the other side, so both regions get the same points
+					let mut points = flatten(&sweep_arcs(features, other, index, &to, &from), tolerance);
+					points.reverse();
+					points
+				},
+				None => vec![from, to],
+			};
+			polygon.extend_from_slice(&points[..points.len() - 1]);
+		}
+		polygons.push(polygon);
+	}
+
+	Regions {
+		polygons,
+		edges,
+	}
+}
+
+//The points along `arcs`, from the start of the first to the end of the last
+fn flatten(arcs : &[ConicArc], tolerance : f64) -> Vec<Point> {
+	let mut points = vec![arcs[0].start];
+	for arc in arcs {
+		points.extend(arc.flatten(tolerance));
+	}
+	points
+}
+
+//The sides around the region of feature `index`, counter-clockwise, as
+//(start, end, feature across). Sides of no length are left out.
+fn boundary(features : &[Feature], index : usize, min : &Point, max : &Point,
+		pool : &mut DoublyConnectedEdgeList) -> Vec<(VertexPtr, VertexPtr, Option<usize>)> {
+	let (lo, hi) = sweep_range(&features[index]);
+	let walls = walls(features, index, min, max);
+	let mut envelope = lower_envelope(&walls, 0, walls.len(), lo, hi);
+	if envelope.is_empty() || envelope.iter().any(|piece| piece.2.is_none()) {
+		return Vec::new();
+	}
+	//A whole turn has no start of its own, so begin it where the wall changes
+	if hi - lo >= 2.0 * PI && envelope.len() > 1 && envelope[0].2 == envelope[envelope.len() - 1].2 {
+		let (start, _, _) = envelope.pop().unwrap();
+		envelope[0].0 = start - 2.0 * PI;
+	}
+
+	//Each stretch of the envelope runs between the points its wall reaches at
+	//either end. Where the nearest wall jumps, as it does along the box from
+	//a site on it, a straight piece of the ray joins them.
+	let feature = &features[index];
+	let reach = |k : usize, other : usize, x : f64| {
+		let t = walls[envelope[k].2.unwrap()].reach.at(x);
+		let t = if t.is_finite() { t } else { walls[envelope[other].2.unwrap()].reach.at(x) };
+		if t.is_finite() { t.max(0.0) } else { 0.0 }
+	};
+	let last = envelope.len() - 1;
+	let mut sides : Vec<(VertexPtr, VertexPtr, Option<usize>)> = Vec::with_capacity(2 * envelope.len() + 3);
+	for (k, &(start, end, wall)) in envelope.iter().enumerate() {
+		let start = pool.new_vertex(&position(feature, start, reach(k, k.max(1) - 1, start)));
+		let end = pool.new_vertex(&position(feature, end, reach(k, (k + 1).min(last), end)));
+		if let Some(&(_, previous, _)) = sides.last() {
+			sides.push((previous, start, None));
+		}
+		sides.push((start, end, walls[wall.unwrap()].across));
+	}
+	let (first, last) = (sides[0].0, sides[sides.len() - 1].1);
+	match features[index] {
+		Feature::Point(point, _, _, width) => if width < 2.0 * PI {
+			let apex = pool.new_vertex(&point);
+			sides.insert(0, (apex, first, None));
+			sides.push((last, apex, None));
+		} else {
+			sides.push((last, first, None));
+		},
+		Feature::Side(start, end) => {
+			let (start, end) = (pool.new_vertex(&start), pool.new_vertex(&end));
+			sides.insert(0, (start, first, None));
+			sides.push((last, end, None));
+			sides.push((end, start, None));
+		},
+	}
+	sides.retain(|&(start, end, _)| start != end);
+	sides
+}
+
+//The values the sweep of a feature runs over
+fn sweep_range(feature : &Feature) -> (f64, f64) {
+	match *feature {
+		Feature::Point(_, _, from, width) => (from, from + width.min(2.0 * PI)),
+		Feature::Side(start, end) => (0.0, (end - start).dot(end - start).sqrt()),
+	}
+}
+
+//Where the sweep of a feature is at `x`, having gone `t` out
+fn position(feature : &Feature, x : f64, t : f64) -> Point {
+	match *feature {
+		Feature::Point(point, _, _, _) => point + Point::new(x.cos(), x.sin()) * t,
+		Feature::Side(start, end) => {
+			let (along, out) = frame(&start, &end);
+			start + along * x + out * t
+		},
+	}
+}
+
+//Where along the sweep of a feature `p` lies
+fn sweep_value(feature : &Feature, p : &Point) -> f64 {
+	match *feature {
+		Feature::Point(point, _, _, _) => (p.y() - point.y()).atan2(p.x() - point.x()),
+		Feature::Side(start, end) => (*p - start).dot(frame(&start, &end).0),
+	}
+}
+
+//The unit direction of a side, and its unit normal to the right
+fn frame(start : &Point, end : &Point) -> (Point, Point) {
+	let along = (*end - *start).unit();
+	(along, Point::new(along.y(), -along.x()))
+}
+
+//The walls the sweep of feature `index` can meet: the sides of the box, and
+//every other feature
+fn walls(features : &[Feature], index : usize, min : &Point, max : &Point) -> Vec<Wall> {
+	let box_sides = [
+		(Point::new(-1.0, 0.0), -min.x()),
+		(Point::new(1.0, 0.0), max.x()),
+		(Point::new(0.0, -1.0), -min.y()),
+		(Point::new(0.0, 1.0), max.y()),
+	];
+	let mut walls = Vec::with_capacity(features.len() + 3);
+	for &(normal, offset) in &box_sides {
+		walls.extend(box_wall(&features[index], normal, offset));
+	}
+	for (other, feature) in features.iter().enumerate() {
+		if other != index {
+			walls.extend(feature_wall(&features[index], feature, other));
+		}
+	}
+	walls
+}
+
+//Where the sweep of `feature` leaves the box through the side
+//`normal . x <= offset`
+fn box_wall(feature : &Feature, normal : Point, offset : f64) -> Option<Wall> {
+	match *feature {
+		Feature::Point(point, _, _, _) => Some(Wall {
+			across : None,
+			reach : Reach::Polar(offset - normal.dot(point), 0.0, normal),
+			conditions : vec![(Form::Linear(0.0, normal), true)],
+		}),
+		Feature::Side(start, end) => {
+			let (along, out) = frame(&start, &end);
+			let speed = normal.dot(out);
+			if speed <= 0.0 {
+				return None;
+			}
+			Some(Wall {
+				across : None,
+				reach : Reach::Normal((offset - normal.dot(start)) / speed, -normal.dot(along) / speed, 0.0),
+				conditions : Vec::new(),
+			})
+		},
+	}
+}
+
+//Where the sweep of `feature` first comes as near to `other`, which has
+//position `across` in the list, and only where that happens in the region
+//`other` is seen from
+fn feature_wall(feature : &Feature, other : &Feature, across : usize) -> Option<Wall> {
+	let mut conditions = Vec::new();
+	let reach = match (*feature, *other) {
+		(Feature::Point(point, weight, _, _), Feature::Point(other_point, other_weight, from, width)) => {
+			let (d, c) = (point - other_point, other_weight - weight);
+			let k = (c * c - d.dot(d)) / 2.0;
+			if k == 0.0 {
+				return None;
+			}
+			let reach = Reach::Polar(k, -c, d);
+			conditions.push((Form::Linear(-k * c, d * k), true));
+			if width < 2.0 * PI {
+				for &(normal, origin) in &wedge_sides(&other_point, from, width) {
+					conditions.push((polar_condition(&point, &reach, normal, &origin), false));
+				}
+			}
+			reach
+		},
+		(Feature::Point(point, weight, _, _), Feature::Side(start, end)) => {
+			//An end of the side only meets it at the end itself, which
+			//rounding could move either way
+			if point == start || point == end {
+				return None;
+			}
+			let (along, out) = frame(&start, &end);
+			let k = out.dot(point - start) + weight;
+			if k == 0.0 {
+				return None;
+			}
+			let reach = Reach::Polar(k, 1.0, out * -1.0);
+			conditions.push((Form::Linear(k, out * -k), true));
+			for &(normal, origin) in &[(along, start), (along * -1.0, end), (out, start)] {
+				conditions.push((polar_condition(&point, &reach, normal, &origin), false));
+			}
+			reach
+		},
+		(Feature::Side(start, end), Feature::Point(other_point, other_weight, from, width)) => {
+			if other_point == start || other_point == end {
+				return None;
+			}
+			let (along, out) = frame(&start, &end);
+			let offset = start - other_point;
+			let denominator = 2.0 * (other_weight - offset.dot(out));
+			if denominator <= 0.0 {
+				return None;
+			}
+			let reach = Reach::Normal(
+				(offset.dot(offset) - other_weight * other_weight) / denominator,
+				2.0 * along.dot(offset) / denominator,
+				1.0 / denominator);
+			conditions.push((reach_form(&reach), true));
+			if width < 2.0 * PI {
+				for &(normal, origin) in &wedge_sides(&other_point, from, width) {
+					conditions.push((normal_condition(&start, &end, &reach, normal, &origin), false));
+				}
+			}
+			reach
+		},
+		(Feature::Side(start, end), Feature::Side(other_start, other_end)) => {
+			//The other face of the same segment only meets it on the segment
+			if (other_start, other_end) == (end, start) {
+				return None;
+			}
+			let (along, out) = frame(&start, &end);
+			let (other_along, other_out) = frame(&other_start, &other_end);
+			let denominator = 1.0 - other_out.dot(out);
+			if denominator <= 1e-12 {
+				return None;
+			}
+			let reach = Reach::Normal(other_out.dot(start - other_start) / denominator, other_out.dot(along) / denominator, 0.0);
+			conditions.push((reach_form(&reach), true));
+			for &(normal, origin) in &[(other_along, other_start), (other_along * -1.0, other_end)] {
+				conditions.push((normal_condition(&start, &end, &reach, normal, &origin), false));
+			}
+			reach
+		},
+	};
+	Some(Wall {
+		across : Some(across),
+		reach,
+		conditions,
+	})
+}
+
+//The half-planes, as (normal, origin) for `normal . (x - origin) >= 0`,
+//whose overlap is a wedge of at most half a turn at `apex`
+fn wedge_sides(apex : &Point, from : f64, width : f64) -> [(Point, Point); 2] {
+	let (first, last) = (from, from + width);
+	[
+		(Point::new(-first.sin(), first.cos()), *apex),
+		(Point::new(last.sin(), -last.cos()), *apex),
+	]
+}
+
+fn reach_form(reach : &Reach) -> Form {
+	match *reach {
+		Reach::Normal(a, b, c) => Form::Quadratic(a, b, c),
+		Reach::Polar(..) => unreachable!(),
+	}
+}
+
+//Whether the point a ray from `point` reaches, `reach` along, lies on the
+//side `normal . (x - origin) >= 0`. Where the reach is valid its
+//denominator has the sign of its numerator, so multiplying through by both
+//keeps the condition linear.
+fn polar_condition(point : &Point, reach : &Reach, normal : Point, origin : &Point) -> Form {
+	match *reach {
+		Reach::Polar(k, b, c) => {
+			let offset = normal.dot(*point - *origin);
+			let sign = k.signum();
+			Form::Linear(sign * offset * b, (c * offset + normal * k) * sign)
+		},
+		Reach::Normal(..) => unreachable!(),
+	}
+}
+
+//Whether the point the normal from the side `start` to `end` reaches,
+//`reach` out, lies on the side `normal . (x - origin) >= 0`
+fn normal_condition(start : &Point, end : &Point, reach : &Reach, normal : Point, origin : &Point) -> Form {
+	let (along, out) = frame(start, end);
+	let rise = normal.dot(out);
+	match *reach {
+		Reach::Normal(a, b, c) => Form::Quadratic(normal.dot(*start - *origin) + rise * a, normal.dot(along) + rise * b, rise * c),
+		Reach::Polar(..) => unreachable!(),
+	}
+}
+
+//The lowest of `walls[first..last]` over `lo` to `hi`
+fn lower_envelope(walls : &[Wall], first : usize, last : usize, lo : f64, hi : f64) -> Envelope {
+	if last - first == 0 {
+		return vec![(lo, hi, None)];
+	}
+	if last - first == 1 {
+		return wall_envelope(&walls[first], first, lo, hi);
+	}
+	let middle = (first + last) / 2;
+	let below = lower_envelope(walls, first, middle, lo, hi);
+	let above = lower_envelope(walls, middle, last, lo, hi);
+	merge(walls, &below, &above)
+}
+
+//Where one wall counts, over `lo` to `hi`
+fn wall_envelope(wall : &Wall, position : usize, lo : f64, hi : f64) -> Envelope {
+	let mut cuts = vec![lo];
+	for &(form, _) in &wall.conditions {
+		cuts.extend(form.roots(lo, hi));
+	}
+	cuts.push(hi);
+	cuts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+	let mut envelope = Vec::new();
+	for pair in cuts.windows(2) {
+		if pair[1] <= pair[0] {
+			continue;
+		}
+		let middle = (pair[0] + pair[1]) / 2.0;
+		let counts = wall.conditions.iter().all(|&(form, strict)| {
+			let value = form.at(middle);
+			if strict { value > 0.0 } else { value >= 0.0 }
+		});
+		push(&mut envelope, pair[0], pair[1], if counts { Some(position) } else { None });
+	}
+	envelope
+}
+
+//The lower of two envelopes over the same range
+fn merge(walls : &[Wall], below : &Envelope, above : &Envelope) -> Envelope {
+	let mut envelope = Vec::new();
+	let (mut i, mut j) = (0, 0);
+	while i < below.len() && j < above.len() {
+		let start = below[i].0.max(above[j].0);
+		let end = below[i].1.min(above[j].1);
+		if end > start {
+			match (below[i].2, above[j].2) {
+				(Some(a), Some(b)) => {
+					let mut cuts = vec![start];
+					cuts.extend(walls[a].reach.meets(&walls[b].reach).roots(start, end));
+					cuts.push(end);
+					for pair in cuts.windows(2) {
+						let middle = (pair[0] + pair[1]) / 2.0;
+						let lower = if walls[b].reach.ahead(middle) < walls[a].reach.ahead(middle) { b } else { a };
+						push(&mut envelope, pair[0], pair[1], Some(lower));
+					}
+				},
+				(a, b) => push(&mut envelope, start, end, a.or(b)),
+			}
+		}
+		if below[i].1 <= above[j].1 {
+			i += 1;
+		} else {
+			j += 1;
+		}
+	}
+	envelope
+}
+
+fn push(envelope : &mut Envelope, start : f64, end : f64, wall : Option<usize>) {
+	if let Some(last) = envelope.last_mut() {
+		if last.2 == wall {
+			last.1 = end;
+			return;
+		}
+	}
+	envelope.push((start, end, wall));
+}
+
+//The edge between `from` and `to` where the sweep of feature `index` meets
+//feature `other`, as exact arcs. The sweep reaches `to` after `from`.
+fn sweep_arcs(features : &[Feature], index : usize, other : usize, from : &Point, to : &Point) -> Vec<ConicArc> {
+	let feature = &features[index];
+	let reach = match feature_wall(feature, &features[other], other) {
+		Some(wall) => wall.reach,
+		None => return vec![ConicArc::line(from, to)],
+	};
+	let start = sweep_value(feature, from);
+	let mut end = sweep_value(feature, to);
+	match reach {
+		Reach::Polar(..) => {
+			//Angles wrap, but the sweep only ever turns forwards
+			end = start + (end - start).rem_euclid(2.0 * PI);
+			let mut arcs = Vec::new();
+			polar_arcs(feature, &reach, (start, *from), (end, *to), 0, &mut arcs);
+			arcs
+		},
+		Reach::Normal(..) => {
+			//A quadratic path, so one quadratic Bézier curve traces it
+			let middle = position(feature, (start + end) / 2.0, reach.at((start + end) / 2.0));
+			let ends = position(feature, start, reach.at(start)) + position(feature, end, reach.at(end));
+			vec![ConicArc {
+				start : *from,
+				control : middle * 2.0 - ends * 0.5,
+				end : *to,
+				weight : 1.0,
+			}]
+		},
+	}
+}
+
+//The arcs of a polar sweep from one (angle, point) to another, split until
+//none turns through more than a third of a turn, where its control point
+//could be far away
+fn polar_arcs(feature : &Feature, reach : &Reach, a : (f64, Point), b : (f64, Point), splits : u32, out : &mut Vec<ConicArc>) {
+	let (a_tangent, b_tangent) = (polar_tangent(reach, a.0), polar_tangent(reach, b.0));
+	let middle_angle = (a.0 + b.0) / 2.0;
+	let middle = position(feature, middle_angle, reach.at(middle_angle));
+	let turn = a_tangent.dot(b_tangent) / (a_tangent.dot(a_tangent) * b_tangent.dot(b_tangent)).sqrt();
+	if turn < 0.5 && splits < MAX_SPLITS {
+		polar_arcs(feature, reach, a, (middle_angle, middle), splits + 1, out);
+		polar_arcs(feature, reach, (middle_angle, middle), b, splits + 1, out);
+		return;
+	}
+	out.push(ConicArc::through(&a.1, &a_tangent, &b.1, &b_tangent, &middle).unwrap_or_else(|| ConicArc::line(&a.1, &b.1)));
+}
+
+//The direction in which a polar sweep's reach moves as the angle grows
+fn polar_tangent(reach : &Reach, angle : f64) -> Point {
+	match *reach {
+		Reach::Polar(k, b, c) => {
+			let (u, across) = (Point::new(angle.cos(), angle.sin()), Point::new(-angle.sin(), angle.cos()));
+			let denominator = b + c.dot(u);
+			let t = k / denominator;
+			let rate = -k * c.dot(across) / (denominator * denominator);
+			u * rate + across * t
+		},
+		Reach::Normal(..) => unreachable!(),
+	}
+}
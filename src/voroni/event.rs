@@ -4,38 +4,34 @@
 use std::cmp::Ordering;
 
 use geometry::point::Point;
+use voroni::status_struct::NodePtr;
 
-#[derive(Copy, Clone)]
-pub struct IsValid {
-    pub value : bool,
-}
-
-impl IsValid {
-    pub fn new() -> IsValid {
-        IsValid {
-            value : true,
-        }
-    }
-}
+handle!(
+	/// Identifies a circle event pushed onto the event queue.
+	EventId);
+handle!(
+	/// Identifies an input site by its position in the input.
+	SiteId);
 
 #[derive(Copy, Clone)]
 pub enum Event {
-    Site(Point), //A site event, containing the site it occurs
-    Circle(Point, f64, u32, u32, IsValid), // A circle event. Contains circle center, radius, leaf pointer, and event id
+    Site(Point, SiteId), //A site event, containing the site it occurs and its input id
+    Circle(Point, f64, NodePtr, EventId), // A circle event. Contains circle center, radius, leaf pointer, and event id
 }
 
 impl Event {
     pub fn get_y(&self) -> f64 {
         match *self {
-            Event::Site(point) => point.y(),
-            Event::Circle(center, radius, _, _, _) => center.y() + radius,
+            Event::Site(point, _) => point.y(),
+            //The sweep moves down, so a circle is reached at its lowest point
+            Event::Circle(center, radius, _, _) => center.y() - radius,
         }
     }
 }
 
 impl PartialEq for Event {
     fn eq(&self, other : &Event) -> bool {
-        return self.get_y().eq(&other.get_y());
+        self.get_y().eq(&other.get_y())
     }
 }
 
@@ -43,21 +39,26 @@ impl Eq for Event {}
 
 impl PartialOrd for Event {
     fn partial_cmp(&self, other: &Event) -> Option<Ordering> {
-        let lhs_y = self.get_y();
-        let rhs_y = other.get_y();
-        if lhs_y == rhs_y {
-            match self {
-                &Event::Site(_) => return Some(Ordering::Greater),
-                &Event::Circle(_,_,_,_,_) => return Some(Ordering::Less), 
-            }
-        }
-        return lhs_y.partial_cmp(&rhs_y);
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Event {
     fn cmp(&self, other: &Event) -> Ordering {
-        return self.partial_cmp(other).unwrap_or(Ordering::Greater);
+        //Coincident sites are swept in input order, so the first one listed
+        //is the one that survives
+        if let (Event::Site(lhs, lhs_id), Event::Site(rhs, rhs_id)) = (*self, *other) {
+            return lhs.cmp(&rhs).then(rhs_id.cmp(&lhs_id));
+        }
+        let lhs_y = self.get_y();
+        let rhs_y = other.get_y();
+        if lhs_y == rhs_y {
+            return match *self {
+                Event::Site(_, _) => Ordering::Greater,
+                Event::Circle(_,_,_,_) => Ordering::Less,
+            };
+        }
+        lhs_y.partial_cmp(&rhs_y).unwrap_or(Ordering::Greater)
     }
 }
 
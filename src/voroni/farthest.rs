@@ -0,0 +1,251 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use geometry::dcel::{DoublyConnectedEdgeList, EdgePtr, VertexPtr};
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::event::SiteId;
+use voroni::geometry::{get_circle_center, get_distance};
+
+/// A farthest-point Voronoi diagram, in which the cell of a site is the
+/// region whose farthest site it is.
+pub struct FarthestDiagram {
+	/// The sites on the convex hull, counter-clockwise. No other site is
+	/// farthest from anywhere, so only these have faces.
+	pub hull : Vec<SiteId>,
+	/// One unbounded face per hull site, in the order of `hull`, carrying its
+	/// `SiteId`. The edges form a tree: they never enclose a bounded face, and
+	/// the ends at infinity have no origin and are marked `is_inf`.
+	pub dcel : DoublyConnectedEdgeList<(), (), SiteId>,
+}
+
+/// Computes the farthest-point Voronoi diagram of `sites`, ignoring sites
+/// with a NaN or infinite coordinate.
+///
+/// The dual triangulation of the hull is built by repeatedly cutting off
+/// the hull corner whose circle through its neighbours is largest, since
+/// that circle holds every other site. This takes O(h^2) time for h hull
+/// sites.
+pub fn farthest_point_diagram(sites : &[Point]) -> FarthestDiagram {
+	let hull : Vec<SiteId> = polygon::convex_hull_indices(sites).into_iter().map(|i| SiteId(i as u32)).collect();
+	let points : Vec<Point> = hull.iter().map(|id| sites[id.index()]).collect();
+	let mut dcel = DoublyConnectedEdgeList::new();
+	for (&id, point) in hull.iter().zip(&points) {
+		let vertex = dcel.new_site_vertex(point);
+		dcel.new_face_with_data(Some(vertex), id);
+	}
+
+	//Each triangle, counter-clockwise, along with its circle's center
+	let mut triangles : Vec<((usize, usize, usize), VertexPtr)> = Vec::new();
+	let mut remaining : Vec<usize> = (0..points.len()).collect();
+	while remaining.len() >= 3 {
+		let n = remaining.len();
+		let triple = |k : usize| (remaining[(k + n - 1) % n], remaining[k], remaining[(k + 1) % n]);
+		let radius = |k : usize| {
+			let (a, b, c) = triple(k);
+			get_circle_center(&(points[a], points[b], points[c]))
+				.map_or(f64::INFINITY, |center| get_distance(&center, &points[b]))
+		};
+		let largest = (0..n)
+			.max_by(|&i, &j| radius(i).partial_cmp(&radius(j)).unwrap_or(Ordering::Equal))
+			.unwrap();
+		let (a, b, c) = triple(largest);
+		//Three hull corners are never collinear, but may be too close to it
+		//to find a center; then push out along the bisector of `a` and `c`,
+		//away from `b`, as far as the height of `b` above them puts it
+		let center = get_circle_center(&(points[a], points[b], points[c]))
+			.unwrap_or_else(|| far_center(&points[a], &points[b], &points[c]));
+		triangles.push(((a, b, c), dcel.new_vertex(&center)));
+		remaining.remove(largest);
+	}
+
+	//The half-edge between the cells of `a` and `b` lies in the face of
+	//`a` and starts at the center of the triangle right of `a` to `b`
+	let mut left_of : BTreeMap<(usize, usize), VertexPtr> = BTreeMap::new();
+	for &((a, b, c), center) in &triangles {
+		for &pair in &[(a, b), (b, c), (c, a)] {
+			left_of.insert(pair, center);
+		}
+	}
+	let mut pairs : Vec<(usize, usize)> = left_of.keys()
+		.map(|&(a, b)| (a.min(b), a.max(b)))
+		.collect();
+	if points.len() == 2 {
+		pairs.push((0, 1));
+	}
+	pairs.sort();
+	pairs.dedup();
+
+	let mut edges : BTreeMap<(usize, usize), EdgePtr> = BTreeMap::new();
+	for (a, b) in pairs {
+		let (origin_ab, origin_ba) = (left_of.get(&(b, a)).cloned(), left_of.get(&(a, b)).cloned());
+		let (ab, ba) = dcel.new_dual_edges(origin_ab, origin_ba);
+		for &(edge, face, origin) in &[(ab, a, origin_ab), (ba, b, origin_ba)] {
+			dcel[edge].incident_face = Some(dcel.faces[face].index());
+			if origin_ab.is_none() || origin_ba.is_none() {
+				dcel[edge].is_inf = true;
+			}
+			if let Some(origin) = origin {
+				if dcel[origin].incident_edge.is_none() {
+					dcel[origin].incident_edge = Some(edge);
+				}
+			}
+		}
+		edges.insert((a, b), ab);
+		edges.insert((b, a), ba);
+	}
+	for &((a, b, c), _) in &triangles {
+		for &(from, to, then) in &[(a, b, c), (b, c, a), (c, a, b)] {
+			dcel.link_edges(edges[&(from, to)], edges[&(from, then)]);
+		}
+	}
+
+	//Each face starts where it comes in from infinity, beside the next
+	//site round the hull
+	for index in 0..points.len() {
+		if let Some(&edge) = edges.get(&(index, (index + 1) % points.len())) {
+			let face = dcel.faces[index].index();
+			dcel[face].outer_component.push(edge);
+		}
+	}
+
+	FarthestDiagram {
+		hull,
+		dcel,
+	}
+}
+
+//The center of the circle through `a`, `b` and `c`, for `b` almost on the
+//line from `a` to `c`. It lies on the bisector of `a` and `c`, at a
+//distance t from their midpoint m along the unit normal n. It is as far
+//from `b` as from `a`, so t = (|b - m|^2 - |a - m|^2) / 2n.(b - m)
+fn far_center(a : &Point, b : &Point, c : &Point) -> Point {
+	let middle = (*a + *c) * 0.5;
+	let chord = *c - *a;
+	let normal = Point::new(-chord.y(), chord.x()).unit();
+	let (to_a, to_b) = (*a - middle, *b - middle);
+	let t = (to_b.dot(to_b) - to_a.dot(to_a)) / (2.0 * normal.dot(to_b));
+	//Only exactly collinear corners leave nowhere to go, so go very far
+	middle + normal * if t.is_finite() { t } else { chord.dot(chord).sqrt() * 1e12 }
+}
+
+/// The smallest circle holding every site, as its center and radius, or
+/// `None` if there are no finite sites. Its center is the point of the
+/// farthest-point diagram nearest to its farthest site: either a vertex of
+/// the diagram, or the midpoint of two sites on a shared edge.
+pub fn minimum_enclosing_circle(sites : &[Point]) -> Option<(Point, f64)> {
+	let diagram = farthest_point_diagram(sites);
+	let hull : Vec<Point> = diagram.hull.iter().map(|id| sites[id.index()]).collect();
+	let farthest = |center : &Point| hull.iter().map(|p| get_distance(center, p)).fold(0.0, f64::max);
+
+	let mut candidates : Vec<Point> = diagram.dcel.vertices.iter()
+		.filter(|vertex| !vertex.is_site)
+		.map(|vertex| vertex.point)
+		.collect();
+	for edge in &diagram.dcel.edges {
+		let twin = &diagram.dcel[edge.twin.unwrap()];
+		let a = sites[diagram.dcel[edge.incident_face.unwrap()].data.index()];
+		let b = sites[diagram.dcel[twin.incident_face.unwrap()].data.index()];
+		candidates.push((a + b) * 0.5);
+	}
+	if hull.len() == 1 {
+		candidates.push(hull[0]);
+	}
+
+	candidates.into_iter()
+		.map(|center| (center, farthest(&center)))
+		.min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+
+	#[test]
+	fn test_vertices_are_farthest() {
+		let mut rng = XorShiftRng::from_seed([6, 2, 8, 3]);
+		let (min, max) = (Point::new(-50.0, -50.0), Point::new(50.0, 50.0));
+		for count in 1..60 {
+			let sites = distributions::uniform_box(&mut rng, count, &min, &max);
+			let diagram = farthest_point_diagram(&sites);
+			let dcel = &diagram.dcel;
+			assert_eq!(dcel.validate(), Ok(()));
+			assert_eq!(dcel.faces.len(), diagram.hull.len());
+
+			//A tree with a leaf at infinity for each hull side
+			let h = diagram.hull.len();
+			let inner = dcel.vertices.iter().filter(|v| !v.is_site).count();
+			assert_eq!(inner, h.saturating_sub(2));
+			assert_eq!(dcel.edges.len(), if h < 2 { 0 } else { 2 * (2 * h - 3) });
+
+			//Each vertex is farthest from the sites of the faces around it
+			for vertex in dcel.vertices.iter().filter(|v| !v.is_site) {
+				let farthest = sites.iter().map(|site| get_distance(&vertex.point, site)).fold(0.0, f64::max);
+				let start = vertex.incident_edge.unwrap();
+				let mut edge = start;
+				loop {
+					let face = dcel[edge].incident_face.unwrap();
+					let site = sites[dcel[face].data.index()];
+					assert!((get_distance(&vertex.point, &site) - farthest).abs() < 1e-9 * farthest);
+					edge = dcel[dcel[edge].twin.unwrap()].next.unwrap();
+					if edge == start {
+						break;
+					}
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_minimum_enclosing_circle() {
+		let mut rng = XorShiftRng::from_seed([4, 4, 2, 1]);
+		assert_eq!(minimum_enclosing_circle(&[]), None);
+		assert_eq!(minimum_enclosing_circle(&[Point::new(1.0, 2.0)]), Some((Point::new(1.0, 2.0), 0.0)));
+		for _ in 0..100 {
+			let count = rng.gen_range(2, 20);
+			let sites = distributions::uniform_box(&mut rng, count, &Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+			let (center, radius) = minimum_enclosing_circle(&sites).unwrap();
+
+			//The smallest circle through two or three sites that holds them all
+			let holds_all = |center : &Point, radius : f64| sites.iter().all(|p| get_distance(center, p) <= radius + 1e-9);
+			let mut best = f64::INFINITY;
+			for i in 0..count {
+				for j in i + 1..count {
+					let middle = (sites[i] + sites[j]) * 0.5;
+					let r = get_distance(&middle, &sites[i]);
+					if holds_all(&middle, r) { best = best.min(r); }
+					for k in j + 1..count {
+						if let Some(c) = get_circle_center(&(sites[i], sites[j], sites[k])) {
+							let r = get_distance(&c, &sites[i]);
+							if holds_all(&c, r) { best = best.min(r); }
+						}
+					}
+				}
+			}
+			assert!(holds_all(&center, radius));
+			assert!((radius - best).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn test_far_center() {
+		//Matches the circumcenter while that can still be found
+		let (a, c) = (Point::new(0.0, 0.0), Point::new(2.0, 0.0));
+		for &b in &[Point::new(1.0, 1e-3), Point::new(0.5, -1e-4)] {
+			let expected = get_circle_center(&(a, b, c)).unwrap();
+			let center = far_center(&a, &b, &c);
+			assert!(get_distance(&center, &expected) < 1e-6 * get_distance(&expected, &a));
+		}
+	}
+
+	#[test]
+	fn test_collinear_and_repeated_sites() {
+		let sites = vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(3.0, 3.0), Point::new(0.0, 0.0)];
+		let diagram = farthest_point_diagram(&sites);
+		assert_eq!(diagram.hull, vec![SiteId(0), SiteId(2)]);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(minimum_enclosing_circle(&sites), Some((Point::new(1.5, 1.5), 4.5f64.sqrt())));
+	}
+}
@@ -2,20 +2,31 @@
 use geometry::point::Point;
 
 pub fn get_circle_center(triple : &(Point, Point, Point)) -> Option<Point> {
-	let a = triple.1.x() - triple.0.x();
-	let b = triple.1.y() - triple.0.y();
-	let c = triple.2.x() - triple.0.x();
-	let d = triple.2.y() - triple.0.y();
-	let e = (&a)*(triple.0.x() + triple.1.x()) + (&b)*(triple.0.y() + triple.1.y());
-	let f = (&c)*(triple.0.x() + triple.2.x()) + (&d)*(triple.0.y() + triple.2.y());
-	let g = 2.0*((&a)*(triple.2.y() - triple.1.y()) - (&b)*(triple.2.x() - triple.1.x()));
-	
+	//Work relative to the first point and in units of the largest offset, so
+	//the squares below neither overflow for huge coordinates nor underflow
+	//for tiny ones
+	let (b, c) = (triple.1 - triple.0, triple.2 - triple.0);
+	let scale = get_scale(&[b.x(), b.y(), c.x(), c.y()]);
+	if scale == 0.0 { return None }
+	let (b, c) = (b * (1.0 / scale), c * (1.0 / scale));
+
+	let g = 2.0 * b.cross(c);
 	if g == 0.0 { return None }
 
-	return Some(Point::new(
-		(d*e-b*f)/g,
-		(a*f-c*e)/g,
-	));
+	let (b_len, c_len) = (b.dot(b), c.dot(c));
+	let offset = Point::new(
+		(c.y()*b_len - b.y()*c_len)/g,
+		(b.x()*c_len - c.x()*b_len)/g,
+	);
+	let center = triple.0 + offset * scale;
+
+	//Nearly collinear points meet beyond the range of an f64, which is as
+	//good as never
+	if center.x().is_finite() && center.y().is_finite() {
+		Some(center)
+	} else {
+		None
+	}
 }
 
 pub fn get_parabola_intersection_x(focus1 : &Point, focus2 : &Point, line_y : f64) -> f64 {
@@ -25,10 +36,14 @@ pub fn get_parabola_intersection_x(focus1 : &Point, focus2 : &Point, line_y : f6
     let ay = focus1.y();
     let by = focus2.y();
 
-    // shift frames
-    let bx_s = bx - ax;
-    let ay_s = ay - line_y;
-    let by_s = by - line_y;
+    // shift frames, and scale so the products below stay in range
+    let scale = get_scale(&[bx - ax, ay - line_y, by - line_y]);
+    if scale == 0.0 {
+        return ax;
+    }
+    let bx_s = (bx - ax) / scale;
+    let ay_s = (ay - line_y) / scale;
+    let by_s = (by - line_y) / scale;
 
     let discrim = ay_s * by_s * ((ay_s - by_s) * (ay_s - by_s) + bx_s * bx_s);
     let numer = ay_s * bx_s - discrim.sqrt();
@@ -39,14 +54,14 @@ pub fn get_parabola_intersection_x(focus1 : &Point, focus2 : &Point, line_y : f6
     } else {
         bx_s / 2.
     };
-    x_bp += ax; // shift back to original frame
+    x_bp = x_bp * scale + ax; // shift back to original frame
 
-    return x_bp;
+    x_bp
 }
 
 pub fn get_parabola_y(focus : &Point, line_y : f64, x : f64) -> f64 {
 	let parabola = get_parabola(focus, line_y);
-	return parabola.0 * x * x + parabola.1 * x + parabola.2;
+	parabola.0 * x * x + parabola.1 * x + parabola.2
 }
 
 pub fn get_parabola(focus : &Point, line_y : f64) -> (f64, f64, f64) {
@@ -55,7 +70,7 @@ pub fn get_parabola(focus : &Point, line_y : f64) -> (f64, f64, f64) {
 	let a : f64 = 1.0/dp;
 	let b : f64 = -2.0*focus.x()/dp;
 	let c : f64 = (focus.x()*focus.x() + focus.y()*focus.y() - line_y*line_y)/dp;
-	return (a, b, c);
+	(a, b, c)
 }
 
 pub fn is_clockwise(triple : &(Point, Point, Point)) -> bool {
@@ -64,17 +79,28 @@ pub fn is_clockwise(triple : &(Point, Point, Point)) -> bool {
 	}
 	
     let (a, b, c) = *triple;
-    let ax = a.x();
-    let ay = a.y();
-    let bx = b.x();
-    let by = b.y();
-    let cx = c.x();
-    let cy = c.y();
-
-    return (ay - by) * (bx - cx) > (by - cy) * (ax - bx)
+    let (ab, bc) = (a - b, b - c);
+    let scale = get_scale(&[ab.x(), ab.y(), bc.x(), bc.y()]);
+    let (ab, bc) = (ab * (1.0 / scale), bc * (1.0 / scale));
+
+    ab.y() * bc.x() > bc.y() * ab.x()
 }
 
 pub fn get_distance(p1 : &Point, p2 : &Point) -> f64 {
 	let v = *p2 - *p1;
-    return (v.x()*v.x() + v.y()*v.y()).sqrt();
+    v.x().hypot(v.y())
+}
+
+//A power of two near the largest magnitude among some coordinates, used to
+//bring them near one before they are multiplied together. Being a power of
+//two, dividing by it loses no precision.
+fn get_scale(values : &[f64]) -> f64 {
+	let max = values.iter().fold(0.0, |max : f64, value| value.abs().max(max));
+	if max == 0.0 {
+		0.0
+	} else if max < f64::MIN_POSITIVE {
+		f64::MIN_POSITIVE
+	} else {
+		f64::from_bits(max.to_bits() & 0x7ff0_0000_0000_0000)
+	}
 }
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+use delaunay;
+use geometry::dcel::{DoublyConnectedEdgeList, FacePtr};
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::geometry::get_distance;
+use voroni::locate::PointLocator;
+
+/// Interpolates the values carried by the faces of a finished diagram, such
+/// as one swept from `VoroniProcess::with_data` with a measurement at every
+/// site. The surface is only defined inside the convex hull of the sites.
+pub struct Interpolator {
+	sites : Vec<Option<Point>>,
+	values : Vec<f64>,
+	//The faces across the edges of each face
+	neighbors : Vec<Vec<usize>>,
+	locator : PointLocator,
+	//The Delaunay triangles, counter-clockwise, as faces of the diagram, with
+	//the triangle across the side after each corner
+	triangles : Vec<[usize; 3]>,
+	adjacent : Vec<[Option<usize>; 3]>,
+	//A triangle at the site of each face
+	site_triangles : Vec<Option<usize>>,
+	hull : Vec<Point>,
+	min : Point,
+	max : Point,
+	eps : f64,
+}
+
+impl Interpolator {
+	pub fn new<V, E>(dcel : &DoublyConnectedEdgeList<V, E, f64>) -> Interpolator {
+		let sites : Vec<Option<Point>> = dcel.faces.iter().map(|face| face.site.map(|site| dcel[site].point)).collect();
+		let mut neighbors = vec![Vec::new(); dcel.faces.len()];
+		for edge in &dcel.edges {
+			let other = edge.twin.and_then(|twin| dcel[twin].incident_face);
+			if let (Some(face), Some(other)) = (edge.incident_face, other) {
+				if face != other && sites[other.index()].is_some() && !neighbors[face.index()].contains(&other.index()) {
+					neighbors[face.index()].push(other.index());
+				}
+			}
+		}
+
+		let triangles = triangulate(dcel, &sites);
+		let mut sides = HashMap::new();
+		let mut site_triangles = vec![None; dcel.faces.len()];
+		for (index, triangle) in triangles.iter().enumerate() {
+			for k in 0..3 {
+				sides.insert((triangle[k], triangle[(k + 1) % 3]), index);
+				site_triangles[triangle[k]] = Some(index);
+			}
+		}
+		let adjacent = triangles.iter()
+			.map(|triangle| {
+				let across = |k : usize| sides.get(&(triangle[(k + 1) % 3], triangle[k])).cloned();
+				[across(0), across(1), across(2)]
+			})
+			.collect();
+
+		let points : Vec<Point> = sites.iter().filter_map(|site| *site).collect();
+		let hull = polygon::convex_hull(&points);
+		let first = points.first().cloned().unwrap_or_else(|| Point::new(0.0, 0.0));
+		let (min, max) = points.iter().fold((first, first), |(min, max), p| {
+			(Point::new(min.x().min(p.x()), min.y().min(p.y())), Point::new(max.x().max(p.x()), max.y().max(p.y())))
+		});
+		Interpolator {
+			sites,
+			values : dcel.faces.iter().map(|face| face.data).collect(),
+			neighbors,
+			locator : PointLocator::new(dcel),
+			triangles,
+			adjacent,
+			site_triangles,
+			hull,
+			min,
+			max,
+			eps : (max.x() - min.x()).max(max.y() - min.y()) * 1e-9,
+		}
+	}
+
+	/// The value of the nearest site, which is constant over each cell. Gives
+	/// `None` outside the hull, like the other surfaces, though it is defined
+	/// everywhere.
+	pub fn nearest(&self, query : &Point) -> Option<f64> {
+		if self.hull_clearance(query)? < -self.eps {
+			return None;
+		}
+		self.locator.locate(query).map(|(face, _)| self.values[face.index()])
+	}
+
+	/// Linear interpolation over the Delaunay triangle holding `query`, by
+	/// its barycentric coordinates, giving a surface of flat facets (a TIN).
+	pub fn linear(&self, query : &Point) -> Option<f64> {
+		let corners = self.triangles[self.locate_triangle(query)?];
+		let [a, b, c] = [0, 1, 2].map(|k| self.sites[corners[k]].unwrap());
+		let area = (b - a).cross(c - a);
+		let weights = [(c - b).cross(*query - b) / area, (a - c).cross(*query - c) / area, (b - a).cross(*query - a) / area];
+		Some((0..3).map(|k| weights[k] * self.values[corners[k]]).sum())
+	}
+
+	/// Sibson's interpolation, weighting each natural neighbour by the area
+	/// the query would take from its cell.
+	pub fn sibson(&self, query : &Point) -> Option<f64> {
+		self.weighted(query, |_, area, _| area)
+	}
+
+	/// The Laplace, or non-Sibsonian, interpolation, weighting each natural
+	/// neighbour by the length of the side its cell would share with the
+	/// query's over its distance from the query. Like Sibson's, it
+	/// reproduces linear functions exactly.
+	pub fn laplace(&self, query : &Point) -> Option<f64> {
+		self.weighted(query, |distance, _, length| length / distance)
+	}
+
+	/// The natural neighbours of `query`: the faces whose cells would lose
+	/// area if it were added as a site, with the area each would lose and the
+	/// length of the side it would share with the cell of the query. Gives
+	/// `None` for a query on or outside the hull, where that cell would be
+	/// unbounded.
+	///
+	/// The neighbours are found by growing a set of faces from the one
+	/// holding the query, adding the neighbours of any face that loses area,
+	/// until no more do.
+	pub fn natural_neighbors(&self, query : &Point) -> Option<Vec<(FacePtr, f64, f64)>> {
+		if !self.inside_hull(query) {
+			return None;
+		}
+		let (start, _) = self.locator.locate(query)?;
+		let site = |i : usize| self.sites[i].unwrap();
+		let extent = (self.max.x() - self.min.x()).max(self.max.y() - self.min.y());
+		let mut candidates = vec![start.index()];
+		let mut margin = extent;
+
+		loop {
+			let (low, high) = (self.min - Point::new(margin, margin), self.max + Point::new(margin, margin));
+			let cell = candidates.iter()
+				.fold(polygon::rectangle(&low, &high), |cell, &i| polygon::clip_bisector(&cell, query, &site(i)));
+			let areas : Vec<f64> = candidates.iter()
+				.map(|&i| {
+					let stolen = candidates.iter()
+						.filter(|&&j| j != i)
+						.fold(cell.clone(), |piece, &j| polygon::clip_bisector(&piece, &site(i), &site(j)));
+					polygon::signed_area(&stolen)
+				})
+				.collect();
+
+			let mut added = Vec::new();
+			for (&i, &area) in candidates.iter().zip(&areas) {
+				if area > 0.0 {
+					for &j in &self.neighbors[i] {
+						if !candidates.contains(&j) && !added.contains(&j) {
+							added.push(j);
+						}
+					}
+				}
+			}
+			if !added.is_empty() {
+				candidates.extend(added);
+				continue;
+			}
+
+			//A cell still touching the frame runs past it, so widen the frame
+			let open = cell.iter().any(|p| {
+				p.x() <= low.x() + self.eps || p.x() >= high.x() - self.eps
+					|| p.y() <= low.y() + self.eps || p.y() >= high.y() - self.eps
+			});
+			if open {
+				margin *= 2.0;
+				if margin > extent * 1e6 {
+					return None;
+				}
+				continue;
+			}
+
+			//Each side lies on the bisector of the query and one neighbour
+			let mut lengths = vec![0.0; candidates.len()];
+			for i in 0..cell.len() {
+				let (a, b) = (cell[i], cell[(i + 1) % cell.len()]);
+				let middle = (a + b) * 0.5;
+				let gap = |k : usize| (get_distance(&middle, &site(candidates[k])) - get_distance(&middle, query)).abs();
+				let nearest = (0..candidates.len())
+					.fold(0, |best, k| if gap(k) < gap(best) { k } else { best });
+				lengths[nearest] += get_distance(&a, &b);
+			}
+			return Some(candidates.into_iter().zip(areas).zip(lengths)
+				.filter(|&((_, area), _)| area > 0.0)
+				.map(|((i, area), length)| (FacePtr(i as u32), area, length))
+				.collect());
+		}
+	}
+
+	//Weights the value of every natural neighbour by `weight(distance, area,
+	//length)`. A query on a site takes its value, wherever the site is.
+	fn weighted<W : Fn(f64, f64, f64) -> f64>(&self, query : &Point, weight : W) -> Option<f64> {
+		let (face, site) = self.locator.locate(query)?;
+		if get_distance(query, &site) <= self.eps {
+			return Some(self.values[face.index()]);
+		}
+		let (mut total, mut sum) = (0.0, 0.0);
+		for (face, area, length) in self.natural_neighbors(query)? {
+			let w = weight(get_distance(query, &self.sites[face.index()].unwrap()), area, length);
+			total += w;
+			sum += w * self.values[face.index()];
+		}
+		if total > 0.0 { Some(sum / total) } else { None }
+	}
+
+	//Walks from a triangle at the nearest site towards the query, crossing
+	//any side the query lies beyond, until no side is left. Leaving the
+	//triangulation means the query is off the hull.
+	fn locate_triangle(&self, query : &Point) -> Option<usize> {
+		let (face, _) = self.locator.locate(query)?;
+		let mut current = self.site_triangles[face.index()]?;
+		for _ in 0..self.triangles.len() {
+			let corners = self.triangles[current];
+			let beyond = (0..3).find(|&k| {
+				let (a, b) = (self.sites[corners[k]].unwrap(), self.sites[corners[(k + 1) % 3]].unwrap());
+				(b - a).cross(*query - a) < -self.eps * get_distance(&a, &b)
+			});
+			match beyond {
+				Some(k) => current = self.adjacent[current][k]?,
+				None => return Some(current),
+			}
+		}
+		None
+	}
+
+	//How far inside the hull the query is, negative outside
+	fn hull_clearance(&self, query : &Point) -> Option<f64> {
+		if self.hull.len() < 3 {
+			return None;
+		}
+		(0..self.hull.len())
+			.map(|i| {
+				let (a, b) = (self.hull[i], self.hull[(i + 1) % self.hull.len()]);
+				(b - a).cross(*query - a) / get_distance(&a, &b)
+			})
+			.fold(None, |least : Option<f64>, clearance| Some(least.map_or(clearance, |least| least.min(clearance))))
+	}
+
+	fn inside_hull(&self, query : &Point) -> bool {
+		self.hull_clearance(query).is_some_and(|clearance| clearance > self.eps)
+	}
+}
+
+//The Delaunay triangles of a diagram, as counter-clockwise triples of its
+//faces. A face of the triangulation with more than three corners, around a
+//vertex shared by more than three cells, is cut into a fan.
+fn triangulate<V, E, F>(dcel : &DoublyConnectedEdgeList<V, E, F>, sites : &[Option<Point>]) -> Vec<[usize; 3]> {
+	let triangulation = delaunay::execute(dcel);
+	let mut triangles = Vec::new();
+	for face in &triangulation.faces {
+		let start = match face.outer_component.first() {
+			Some(&start) => start,
+			None => continue,
+		};
+		let mut corners = Vec::new();
+		let mut edge = start;
+		loop {
+			if let Some(origin) = triangulation[edge].origin {
+				corners.push(triangulation[origin].data.index());
+			}
+			match triangulation[edge].next {
+				Some(next) if next != start && corners.len() <= triangulation.edges.len() => edge = next,
+				_ => break,
+			}
+		}
+		for k in 1..corners.len().saturating_sub(1) {
+			let mut triangle = [corners[0], corners[k], corners[k + 1]];
+			let [a, b, c] = triangle.map(|corner| sites[corner].unwrap());
+			if (b - a).cross(c - a) < 0.0 {
+				triangle.swap(1, 2);
+			}
+			if (b - a).cross(c - a) != 0.0 {
+				triangles.push(triangle);
+			}
+		}
+	}
+	triangles
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use file::raster::{format_ascii_grid, format_pgm, Raster};
+	use generate::distributions;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+	use voroni::voroni_process::VoroniProcess;
+
+	#[test]
+	fn test_natural_neighbors_reproduce_planes() {
+		let plane = |p : &Point| 2.0 * p.x() - 3.0 * p.y() + 1.0;
+		let mut rng = XorShiftRng::from_seed([9, 4, 6, 2]);
+		let sites = distributions::uniform_box(&mut rng, 100, &Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+		let mut vp = VoroniProcess::with_data(sites.iter().map(|site| (*site, plane(site))).collect(), 0.0);
+		vp.execute();
+		let interpolator = Interpolator::new(vp.get_dcel());
+
+		assert_eq!(interpolator.sibson(&sites[7]), Some(plane(&sites[7])));
+		assert_eq!(interpolator.sibson(&Point::new(-1.0, 5.0)), None);
+		for _ in 0..100 {
+			let query = Point::new(rng.gen_range(2.0, 8.0), rng.gen_range(2.0, 8.0));
+			let neighbours = interpolator.natural_neighbors(&query).unwrap();
+			assert!(neighbours.len() >= 3);
+			assert!((interpolator.sibson(&query).unwrap() - plane(&query)).abs() < 1e-9);
+			assert!((interpolator.laplace(&query).unwrap() - plane(&query)).abs() < 1e-9);
+		}
+
+		//Cells off the hull have no data
+		let raster = Raster::sample(&Point::new(-1.0, -1.0), 0.5, 24, 24, |p| interpolator.sibson(p));
+		assert_eq!(raster.get(0, 0), None);
+		let centre = raster.get(12, 12).unwrap();
+		assert!((centre - plane(&Point::new(5.25, 5.25))).abs() < 1e-9);
+		assert!(format_ascii_grid(&raster).starts_with("ncols 24\nnrows 24\nxllcorner -1\nyllcorner -1\ncellsize 0.5\nNODATA_value -9999\n"));
+	}
+
+	#[test]
+	fn test_nearest_and_linear_surfaces() {
+		let plane = |p : &Point| p.x() + 4.0 * p.y() - 2.0;
+		let mut rng = XorShiftRng::from_seed([1, 6, 1, 8]);
+		let sites = distributions::uniform_box(&mut rng, 80, &Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+		let mut vp = VoroniProcess::with_data(sites.iter().map(|site| (*site, plane(site))).collect(), 0.0);
+		vp.execute();
+		let interpolator = Interpolator::new(vp.get_dcel());
+
+		for _ in 0..200 {
+			let query = Point::new(rng.gen_range(2.0, 8.0), rng.gen_range(2.0, 8.0));
+			assert!((interpolator.linear(&query).unwrap() - plane(&query)).abs() < 1e-9);
+			let nearest = sites.iter()
+				.fold(sites[0], |best, site| if get_distance(site, &query) < get_distance(&best, &query) { *site } else { best });
+			assert_eq!(interpolator.nearest(&query), Some(plane(&nearest)));
+		}
+		assert_eq!(interpolator.linear(&Point::new(5.0, 11.0)), None);
+		assert_eq!(interpolator.nearest(&Point::new(5.0, 11.0)), None);
+
+		let raster = Raster::sample(&Point::new(-1.0, -1.0), 1.0, 12, 12, |p| interpolator.linear(p));
+		let pgm = format_pgm(&raster);
+		assert!(pgm.starts_with("P2\n12 12\n255\n0 0 0"));
+		assert_eq!(pgm.split_whitespace().count(), 4 + 12 * 12);
+	}
+}
@@ -0,0 +1,175 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use geometry::bound::Bound;
+use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::cells::{box_corners, clip_cells, share_corners};
+use voroni::event::SiteId;
+use voroni::geometry::get_distance;
+use voroni::voroni_process::VoroniProcess;
+
+/// A k-th order Voronoi diagram, clipped to a box: each cell is the region
+/// where the same k sites are nearest, in any order.
+pub struct KthOrderDiagram {
+	pub k : usize,
+	/// One face per cell, carrying its k nearest sites in increasing order.
+	/// Faces are sorted by their sites.
+	pub dcel : DoublyConnectedEdgeList<(), (), Vec<SiteId>>,
+	/// The counter-clockwise polygon of each face, indexed like `dcel.faces`.
+	pub cells : Vec<Vec<Point>>,
+}
+
+/// Computes the k-th order Voronoi diagram of `sites` inside the box the
+/// sweep would use for them: their bounds grown by a fifth of their size.
+pub fn kth_order_voronoi(sites : &[Point], k : usize) -> KthOrderDiagram {
+	let mut bound = Bound::new();
+	for site in sites.iter().filter(|site| site.x().is_finite() && site.y().is_finite()) {
+		bound.update(site);
+	}
+	let bound = match (bound.get_left(), bound.get_top(), bound.get_right(), bound.get_bottom()) {
+		(Some(left), Some(top), Some(right), Some(bottom)) =>
+			Bound::from_corners(&Point::new(left, top), &Point::new(right, bottom)),
+		_ => Bound::new(),
+	};
+	kth_order_voronoi_in(sites, k, &bound)
+}
+
+/// Computes the k-th order Voronoi diagram of `sites` inside `bound`.
+///
+/// Sites with a NaN or infinite coordinate are left out, and repeated sites
+/// count once, as the first of them. `k` must be between 1 and the number
+/// of sites left; otherwise the diagram has no faces.
+///
+/// The order-1 cells come from the sweep. Each order is then built from the
+/// one before: every cell, with its set of nearest sites, is split by the
+/// Voronoi diagram of the remaining sites, and the pieces that gain the
+/// same site set are merged. Cells of every order are convex.
+pub fn kth_order_voronoi_in(sites : &[Point], k : usize, bound : &Bound) -> KthOrderDiagram {
+	let mut unique : Vec<usize> = Vec::new();
+	for (index, site) in sites.iter().enumerate() {
+		if site.x().is_finite() && site.y().is_finite() && !unique.iter().any(|&u| sites[u] == *site) {
+			unique.push(index);
+		}
+	}
+	let (min, max, eps) = box_corners(bound);
+
+	let mut level : Vec<(Vec<usize>, Vec<Point>)> = Vec::new();
+	if k >= 1 && k <= unique.len() && min != max {
+		let mut voroni_process = VoroniProcess::with_data(unique.iter().map(|&u| (sites[u], ())).collect(), 0.0);
+		voroni_process.execute();
+		let cells = clip_cells(voroni_process.get_dcel(), bound);
+		for (position, &u) in unique.iter().enumerate() {
+			if let Some(face) = voroni_process.get_site_face(SiteId(position as u32)) {
+				if !cells[face.index()].is_empty() {
+					level.push((vec![u], cells[face.index()].clone()));
+				}
+			}
+		}
+	}
+
+	for _ in 1..k {
+		let mut merged : BTreeMap<Vec<usize>, Vec<Point>> = BTreeMap::new();
+		for (nearest, cell) in level {
+			let rest : Vec<usize> = unique.iter().cloned().filter(|u| !nearest.contains(u)).collect();
+			for &next in &rest {
+				let piece = next_nearest_region(sites, &cell, next, &rest);
+				if polygon::signed_area(&piece) > eps * eps {
+					let mut label = nearest.clone();
+					label.push(next);
+					label.sort();
+					merged.entry(label).or_default().extend(piece);
+				}
+			}
+		}
+		level = merged.into_iter()
+			.map(|(label, corners)| (label, polygon::remove_collinear(&polygon::convex_hull(&corners), eps)))
+			.collect();
+	}
+	level.sort_by(|a, b| a.0.cmp(&b.0));
+
+	let mut cells : Vec<Vec<Point>> = level.iter().map(|(_, cell)| cell.clone()).collect();
+	share_corners(cells.iter_mut().collect(), eps);
+	let dcel = DoublyConnectedEdgeList::from_polygons(
+		level.iter().zip(&cells)
+			.map(|((label, _), cell)| (cell.clone(), label.iter().map(|&u| SiteId(u as u32)).collect()))
+			.collect(),
+		eps);
+
+	KthOrderDiagram {
+		k,
+		dcel,
+		cells,
+	}
+}
+
+//The part of `cell` nearer to `site` than to any other of `rest`
+fn next_nearest_region(sites : &[Point], cell : &[Point], site : usize, rest : &[usize]) -> Vec<Point> {
+	let mut others : Vec<(f64, usize)> = rest.iter()
+		.filter(|&&other| other != site)
+		.map(|&other| (get_distance(&sites[site], &sites[other]), other))
+		.collect();
+	others.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+	let mut piece = cell.to_vec();
+	for (gap, other) in others {
+		//Past twice the piece's reach no site can take anything from it
+		let reach = piece.iter().map(|corner| get_distance(&sites[site], corner)).fold(0.0, f64::max);
+		if piece.is_empty() || gap > 2.0 * reach {
+			break;
+		}
+		piece = polygon::clip_bisector(&piece, &sites[site], &sites[other]);
+	}
+	piece
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use rand::{SeedableRng, XorShiftRng};
+
+	#[test]
+	fn test_cells_hold_their_nearest_sites() {
+		let mut rng = XorShiftRng::from_seed([8, 6, 7, 5]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(20.0, 20.0));
+		let bound = Bound::from_corners(&min, &max);
+		let sites = distributions::uniform_box(&mut rng, 25, &min, &max);
+		for k in 1..5 {
+			let diagram = kth_order_voronoi_in(&sites, k, &bound);
+			assert_eq!(diagram.dcel.validate(), Ok(()));
+
+			let total : f64 = diagram.cells.iter().map(|cell| polygon::signed_area(cell)).sum();
+			assert!((total - 400.0).abs() < 1e-6, "order {} covers {}", k, total);
+
+			//The centroid of each cell has the cell's sites as its k nearest
+			for (face, cell) in diagram.dcel.faces.iter().zip(&diagram.cells) {
+				assert_eq!(face.data.len(), k);
+				let centroid = polygon::centroid(cell).unwrap();
+				let mut order : Vec<usize> = (0..sites.len()).collect();
+				order.sort_by(|&a, &b| get_distance(&centroid, &sites[a])
+					.partial_cmp(&get_distance(&centroid, &sites[b])).unwrap());
+				let mut nearest : Vec<SiteId> = order[..k].iter().map(|&u| SiteId(u as u32)).collect();
+				nearest.sort();
+				assert_eq!(face.data, nearest);
+			}
+		}
+	}
+
+	#[test]
+	fn test_degenerate_input() {
+		let mut rng = XorShiftRng::from_seed([1, 2, 3, 5]);
+		let mut sites = distributions::jittered_grid(&mut rng, 4, 4, 0.0, &Point::new(0.0, 0.0), &Point::new(12.0, 12.0));
+		sites.push(sites[3]);
+		sites.push(Point::new(f64::NAN, 1.0));
+		let diagram = kth_order_voronoi(&sites, 2);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert!(diagram.dcel.faces.iter().all(|face| !face.data.contains(&SiteId(16)) && !face.data.contains(&SiteId(17))));
+
+		assert_eq!(kth_order_voronoi(&sites, 16).dcel.faces.len(), 1);
+		assert_eq!(kth_order_voronoi(&sites, 17).dcel.faces.len(), 0);
+		assert_eq!(kth_order_voronoi(&sites, 0).dcel.faces.len(), 0);
+		assert_eq!(kth_order_voronoi(&[], 1).dcel.faces.len(), 0);
+	}
+}
@@ -0,0 +1,117 @@
+use geometry::bound::Bound;
+use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::cells::clip_cells;
+use voroni::event::SiteId;
+use voroni::voroni_process::VoroniProcess;
+
+/// The outcome of `lloyd_relax`.
+pub struct Relaxation {
+	/// The relaxed sites, in input order.
+	pub sites : Vec<Point>,
+	/// The diagram of the relaxed sites.
+	pub dcel : DoublyConnectedEdgeList,
+	/// The cells of `dcel` clipped to the bound, indexed like its faces.
+	pub cells : Vec<Vec<Point>>,
+	/// The energy of every diagram computed: the sum over cells of the
+	/// squared distance to the site, integrated over the clipped cell. The
+	/// first entry is for the input sites and the last for `sites`.
+	pub energy : Vec<f64>,
+	/// Whether the sites stopped moving before the iterations ran out.
+	pub converged : bool,
+}
+
+/// Moves every site to the centroid of its cell clipped to `bound`, up to
+/// `iterations` times, which approaches a centroidal Voronoi tessellation.
+/// Stops early once no site moves further than `tolerance` in a step.
+///
+/// A site whose cell misses the bound stays where it is.
+pub fn lloyd_relax(sites : Vec<Point>, bound : &Bound, iterations : usize, tolerance : f64) -> Relaxation {
+	let mut sites = sites;
+	let mut energy = Vec::new();
+	let mut converged = false;
+	let mut iteration = 0;
+
+	loop {
+		let mut voroni_process = VoroniProcess::with_data(sites.iter().map(|site| (*site, ())).collect(), 0.0);
+		voroni_process.execute();
+		let cells = clip_cells(voroni_process.get_dcel(), bound);
+
+		let site_cell = |index : usize| voroni_process.get_site_face(SiteId(index as u32))
+			.map(|face| &cells[face.index()]);
+		energy.push(voroni_process.get_dcel().faces.iter()
+			.map(|face| {
+				let site = voroni_process.get_dcel()[face.site.unwrap()].point;
+				polygon::second_moment(&cells[face.index().index()], &site)
+			})
+			.sum());
+
+		if converged || iteration == iterations {
+			return Relaxation {
+				sites,
+				dcel : voroni_process.into_dcel(),
+				cells,
+				energy,
+				converged,
+			};
+		}
+
+		let mut max_move : f64 = 0.0;
+		let moved : Vec<Point> = sites.iter().enumerate()
+			.map(|(index, site)| {
+				let target = site_cell(index)
+					.and_then(|cell| polygon::centroid(cell))
+					.unwrap_or(*site);
+				max_move = max_move.max((target - *site).dot(target - *site).sqrt());
+				target
+			})
+			.collect();
+		sites = moved;
+		converged = max_move <= tolerance;
+		iteration += 1;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use rand::{SeedableRng, XorShiftRng};
+
+	#[test]
+	fn test_lloyd_lowers_energy() {
+		let mut rng = XorShiftRng::from_seed([1, 1, 2, 3]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(20.0, 10.0));
+		let sites = distributions::uniform_box(&mut rng, 40, &min, &max);
+		let relaxation = lloyd_relax(sites, &Bound::from_corners(&min, &max), 30, 0.0);
+
+		assert_eq!(relaxation.energy.len(), 31);
+		for pair in relaxation.energy.windows(2) {
+			assert!(pair[1] <= pair[0] + 1e-9);
+		}
+		assert!(relaxation.energy[30] < relaxation.energy[0]);
+		for site in &relaxation.sites {
+			assert!(site.x() >= min.x() && site.x() <= max.x() && site.y() >= min.y() && site.y() <= max.y());
+		}
+	}
+
+	#[test]
+	fn test_lloyd_converges() {
+		let mut rng = XorShiftRng::from_seed([2, 3, 5, 7]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+		let sites = distributions::jittered_grid(&mut rng, 3, 3, 0.2, &min, &max);
+		let relaxation = lloyd_relax(sites, &Bound::from_corners(&min, &max), 500, 1e-3);
+
+		assert!(relaxation.converged);
+		assert!(relaxation.energy.len() < 501);
+		//Each site ends at the centroid of its own cell
+		for (index, site) in relaxation.sites.iter().enumerate() {
+			let face = relaxation.dcel.faces.iter()
+				.find(|face| relaxation.dcel[face.site.unwrap()].point == *site)
+				.unwrap();
+			let centroid = polygon::centroid(&relaxation.cells[face.index().index()]).unwrap();
+			assert!((centroid - *site).dot(centroid - *site).sqrt() < 1e-2, "site {} is off center", index);
+		}
+	}
+}
@@ -0,0 +1,71 @@
+use geometry::dcel::{DoublyConnectedEdgeList, FacePtr};
+use geometry::kd_tree::KdTree;
+use geometry::point::Point;
+
+/////////////////////////////////////////////////////////////////
+
+/// Answers "which cell contains this point?" for a finished Voronoi diagram.
+///
+/// A point lies in the cell of the site nearest to it, so locating a point is
+/// a nearest-site query. The sites are taken from the faces of the diagram
+/// and stored in a 2-d tree, giving O(log n) queries without relying on the
+/// geometry of the (possibly unbounded) cell boundaries.
+pub struct PointLocator {
+	tree : KdTree<FacePtr>,
+}
+
+impl PointLocator {
+	pub fn new<V, E, F>(dcel : &DoublyConnectedEdgeList<V, E, F>) -> PointLocator {
+		let mut sites = Vec::new();
+		for face in &dcel.faces {
+			if let Some(site_ptr) = face.site {
+				sites.push((dcel[site_ptr].point, face.index()));
+			}
+		}
+		PointLocator {
+			tree : KdTree::new(sites),
+		}
+	}
+
+	/// Returns the face containing `query` and the site of that face, or
+	/// `None` if the diagram has no sites. Points on a cell boundary are
+	/// assigned to one of the touching cells.
+	pub fn locate(&self, query : &Point) -> Option<(FacePtr, Point)> {
+		self.tree.nearest(query).map(|(site, face_ptr)| (face_ptr, site))
+	}
+
+	/// Locates every point of `queries`, in order.
+	pub fn locate_all(&self, queries : &[Point]) -> Vec<Option<(FacePtr, Point)>> {
+		queries.iter().map(|query| self.locate(query)).collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+
+	#[test]
+	fn test_locate_matches_linear_scan() {
+		let mut rng = XorShiftRng::from_seed([7, 11, 13, 17]);
+		let mut dcel : DoublyConnectedEdgeList = DoublyConnectedEdgeList::new();
+		for _ in 0..200 {
+			let site = Point::new(rng.gen_range(-50.0, 50.0), rng.gen_range(-50.0, 50.0));
+			let vertex = dcel.new_vertex(&site);
+			dcel.new_face(Some(vertex));
+		}
+		let locator = PointLocator::new(&dcel);
+
+		let queries : Vec<Point> = (0..500)
+			.map(|_| Point::new(rng.gen_range(-60.0, 60.0), rng.gen_range(-60.0, 60.0)))
+			.collect();
+
+		for (query, located) in queries.iter().zip(locator.locate_all(&queries)) {
+			let (_, site) = located.unwrap();
+			let best = dcel.vertices.iter()
+				.map(|v| (v.point - *query).dot(v.point - *query))
+				.fold(f64::INFINITY, f64::min);
+			assert_eq!((site - *query).dot(site - *query), best);
+		}
+	}
+}
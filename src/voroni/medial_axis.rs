@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use geometry::dcel::VertexPtr;
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::event::SiteId;
+use voroni::geometry::get_distance;
+use voroni::voroni_process::VoroniProcess;
+
+/// An approximate medial axis: the centerline graph of a polygon.
+pub struct MedialAxis {
+	pub points : Vec<Point>,
+	/// The distance from each point to the polygon's boundary: the radius
+	/// of the largest disk inside the polygon centered there.
+	pub clearance : Vec<f64>,
+	/// The segments of the axis, as indices into `points`.
+	pub edges : Vec<(usize, usize)>,
+}
+
+/// Approximates the medial axis of a simple polygon, given by its ring of
+/// corners in either order.
+///
+/// The boundary is sampled at most `spacing` apart, and the Voronoi edges
+/// of the samples that lie inside the polygon are kept, except those
+/// between samples next to each other on the boundary, which only cross
+/// from the boundary to the axis. Every branch from a leaf to the first
+/// fork that is shorter than `prune` is then removed, which drops the
+/// spurs that small wiggles in the boundary leave, along with the short
+/// branches into convex corners. This repeats until no branch is short
+/// enough, as removing a spur can leave a short branch behind.
+///
+/// A `spacing` that is not positive and finite gives an empty axis.
+pub fn medial_axis(ring : &[Point], spacing : f64, prune : f64) -> MedialAxis {
+	let mut axis = MedialAxis {
+		points : Vec::new(),
+		clearance : Vec::new(),
+		edges : Vec::new(),
+	};
+	if !(spacing > 0.0 && spacing.is_finite()) {
+		return axis;
+	}
+
+	//Samples round the ring, with how far round each lies
+	let mut samples : Vec<Point> = Vec::new();
+	let mut positions : Vec<f64> = Vec::new();
+	let mut step : f64 = 0.0;
+	let mut perimeter = 0.0;
+	for i in 0..ring.len() {
+		let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+		let length = get_distance(&a, &b);
+		if length == 0.0 || !length.is_finite() {
+			continue;
+		}
+		let steps = (length / spacing).ceil().clamp(1.0, 1e6) as usize;
+		step = step.max(length / steps as f64);
+		for k in 0..steps {
+			let t = k as f64 / steps as f64;
+			samples.push(a + (b - a) * t);
+			positions.push(perimeter + length * t);
+		}
+		perimeter += length;
+	}
+
+	if samples.len() < 3 {
+		return axis;
+	}
+
+	let mut voroni_process = VoroniProcess::with_data(samples.iter().map(|&sample| (sample, ())).collect(), 0.0);
+	voroni_process.execute();
+	let mut face_sample = BTreeMap::new();
+	for index in 0..samples.len() {
+		if let Some(face) = voroni_process.get_site_face(SiteId(index as u32)) {
+			face_sample.insert(face, index);
+		}
+	}
+
+	let dcel = voroni_process.get_dcel();
+	let mut indices : BTreeMap<VertexPtr, usize> = BTreeMap::new();
+	let mut edges : Vec<(usize, usize)> = Vec::new();
+	for edge in &dcel.edges {
+		let twin = &dcel[edge.twin.unwrap()];
+		let (from, to) = match (edge.origin, twin.origin) {
+			(Some(from), Some(to)) if from < to => (from, to),
+			_ => continue,
+		};
+		let (a, b) = match (edge.incident_face.and_then(|f| face_sample.get(&f)),
+				twin.incident_face.and_then(|f| face_sample.get(&f))) {
+			(Some(&a), Some(&b)) => (a, b),
+			_ => continue,
+		};
+		let apart = (positions[a] - positions[b]).abs();
+		if apart.min(perimeter - apart) <= 1.5 * step {
+			continue;
+		}
+		if !polygon::contains(ring, &dcel[from].point) || !polygon::contains(ring, &dcel[to].point) {
+			continue;
+		}
+		let mut index_of = |vertex : VertexPtr| {
+			let next = indices.len();
+			*indices.entry(vertex).or_insert(next)
+		};
+		edges.push((index_of(from), index_of(to)));
+	}
+	let mut points = vec![Point::new(0.0, 0.0); indices.len()];
+	for (&vertex, &index) in &indices {
+		points[index] = dcel[vertex].point;
+	}
+
+	let keep = prune_branches(&points, &edges, prune);
+	let mut renumber = vec![None; points.len()];
+	for (index, point) in points.iter().enumerate() {
+		if keep[index] {
+			renumber[index] = Some(axis.points.len());
+			axis.points.push(*point);
+			axis.clearance.push((0..ring.len())
+				.map(|i| polygon::distance_to_segment(point, &ring[i], &ring[(i + 1) % ring.len()]))
+				.fold(f64::INFINITY, f64::min));
+		}
+	}
+	for &(a, b) in &edges {
+		if let (Some(a), Some(b)) = (renumber[a], renumber[b]) {
+			axis.edges.push((a, b));
+		}
+	}
+	axis
+}
+
+//Which points survive removing every leaf branch shorter than `prune`,
+//pass after pass until none is left
+fn prune_branches(points : &[Point], edges : &[(usize, usize)], prune : f64) -> Vec<bool> {
+	let mut keep = vec![true; points.len()];
+	loop {
+		let mut neighbours : Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+		for &(a, b) in edges {
+			if keep[a] && keep[b] {
+				neighbours[a].push(b);
+				neighbours[b].push(a);
+			}
+		}
+		let mut changed = false;
+		for leaf in 0..points.len() {
+			if neighbours[leaf].len() != 1 {
+				continue;
+			}
+			//Walk in from the leaf to the first fork
+			let mut branch = vec![leaf];
+			let mut length = 0.0;
+			let (mut previous, mut current) = (leaf, neighbours[leaf][0]);
+			loop {
+				length += get_distance(&points[previous], &points[current]);
+				if neighbours[current].len() != 2 || length >= prune {
+					break;
+				}
+				branch.push(current);
+				let next = if neighbours[current][0] == previous { neighbours[current][1] } else { neighbours[current][0] };
+				previous = current;
+				current = next;
+			}
+			//A lone path with no fork is the whole axis, so keep it
+			if length < prune && neighbours[current].len() > 2 {
+				for point in branch {
+					keep[point] = false;
+				}
+				changed = true;
+			}
+		}
+		if !changed {
+			return keep;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn connected(axis : &MedialAxis) -> bool {
+		let mut seen = vec![false; axis.points.len()];
+		let mut stack = vec![0];
+		while let Some(point) = stack.pop() {
+			if !seen[point] {
+				seen[point] = true;
+				for &(a, b) in &axis.edges {
+					if a == point { stack.push(b); }
+					if b == point { stack.push(a); }
+				}
+			}
+		}
+		seen.iter().all(|&s| s)
+	}
+
+	#[test]
+	fn test_rectangle_centerline() {
+		let ring = polygon::rectangle(&Point::new(0.0, 0.0), &Point::new(10.0, 2.0));
+		let axis = medial_axis(&ring, 0.05, 0.0);
+		assert!(connected(&axis));
+		for (point, clearance) in axis.points.iter().zip(&axis.clearance) {
+			assert!(polygon::contains(&ring, point));
+			assert!(*clearance <= 1.0 + 1e-9);
+		}
+
+		//Pruning leaves only the centerline, at a clearance of one
+		let axis = medial_axis(&ring, 0.05, 2.0);
+		assert!(!axis.edges.is_empty() && connected(&axis));
+		for (point, clearance) in axis.points.iter().zip(&axis.clearance) {
+			assert!((point.y() - 1.0).abs() < 0.01, "{:?} is off the centerline", point);
+			assert!(point.x() > 0.9 && point.x() < 9.1);
+			assert!((clearance - 1.0).abs() < 0.01);
+		}
+	}
+
+	#[test]
+	fn test_winding_river() {
+		//A bend between two offset arcs, with square ends whose corner branches
+		//are about 2.1 long
+		let mut ring = Vec::new();
+		for k in 0..=40 {
+			let angle = ::std::f64::consts::PI * k as f64 / 40.0;
+			ring.push(Point::new(10.0 - 8.0 * angle.cos(), 8.0 * angle.sin()));
+		}
+		for k in (0..=40).rev() {
+			let angle = ::std::f64::consts::PI * k as f64 / 40.0;
+			ring.push(Point::new(10.0 - 5.0 * angle.cos(), 5.0 * angle.sin()));
+		}
+		let axis = medial_axis(&ring, 0.1, 2.5);
+		assert!(connected(&axis));
+		for (point, clearance) in axis.points.iter().zip(&axis.clearance) {
+			let radius = get_distance(point, &Point::new(10.0, 0.0));
+			assert!((radius - 6.5).abs() < 0.05, "{:?} is off the centerline", point);
+			assert!((clearance - 1.5).abs() < 0.05);
+		}
+	}
+
+	#[test]
+	fn test_pruning_repeats() {
+		//Two short spurs off a short branch off a long path
+		let points = vec![Point::new(-10.0, 0.0), Point::new(0.0, 0.0), Point::new(0.0, 1.0),
+			Point::new(-0.5, 1.0), Point::new(0.5, 1.0), Point::new(10.0, 0.0)];
+		let edges = vec![(0, 1), (1, 2), (2, 3), (2, 4), (1, 5)];
+		assert_eq!(prune_branches(&points, &edges, 2.0), vec![true, true, false, false, false, true]);
+
+		let ring = polygon::rectangle(&Point::new(0.0, 0.0), &Point::new(10.0, 2.0));
+		for &spacing in &[0.0, -1.0, f64::NAN] {
+			assert!(medial_axis(&ring, spacing, 0.0).points.is_empty());
+		}
+	}
+}
@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use geometry::bound::Bound;
+use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::cells::{box_corners, clip_cells, share_corners};
+use voroni::event::SiteId;
+use voroni::voroni_process::VoroniProcess;
+
+/// The distance a Voronoi diagram is built for.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Metric {
+	/// Straight-line distance.
+	Euclidean,
+	/// Manhattan distance, `|dx| + |dy|`, for moves along the grid axes.
+	Manhattan,
+	/// Chebyshev distance, `max(|dx|, |dy|)`, for moves that include diagonals.
+	Chebyshev,
+}
+
+impl Metric {
+	pub fn distance(&self, a : &Point, b : &Point) -> f64 {
+		let (dx, dy) = ((a.x() - b.x()).abs(), (a.y() - b.y()).abs());
+		match *self {
+			Metric::Euclidean => dx.hypot(dy),
+			Metric::Manhattan => dx + dy,
+			Metric::Chebyshev => dx.max(dy),
+		}
+	}
+
+	//The lines, as (normal, offset), across which the distance to `site`
+	//changes formula
+	fn zone_lines(&self, site : &Point) -> Vec<(Point, f64)> {
+		match *self {
+			Metric::Euclidean => Vec::new(),
+			Metric::Manhattan => vec![(Point::new(1.0, 0.0), site.x()), (Point::new(0.0, 1.0), site.y())],
+			Metric::Chebyshev => vec![
+				(Point::new(1.0, -1.0), site.x() - site.y()),
+				(Point::new(1.0, 1.0), site.x() + site.y()),
+			],
+		}
+	}
+
+	//The distance to `site` as `gradient . x + offset`, valid throughout the
+	//zone holding `inside`. Euclidean distance is squared and left without
+	//its `x . x`, which is the same for every site.
+	fn linear_form(&self, site : &Point, inside : &Point) -> (Point, f64) {
+		let (dx, dy) = (inside.x() - site.x(), inside.y() - site.y());
+		let (sx, sy) = (if dx < 0.0 { -1.0 } else { 1.0 }, if dy < 0.0 { -1.0 } else { 1.0 });
+		match *self {
+			Metric::Euclidean => (*site * -2.0, site.dot(*site)),
+			Metric::Manhattan => (Point::new(sx, sy), -sx * site.x() - sy * site.y()),
+			Metric::Chebyshev => if dx.abs() >= dy.abs() {
+				(Point::new(sx, 0.0), -sx * site.x())
+			} else {
+				(Point::new(0.0, sy), -sy * site.y())
+			},
+		}
+	}
+}
+
+/// A Voronoi diagram under some `Metric`, clipped to a box.
+pub struct MetricDiagram {
+	pub metric : Metric,
+	/// One face per input site, in input order, carrying its `SiteId`. The
+	/// site of each face is set to a vertex at the site's point, if it is
+	/// finite.
+	pub dcel : DoublyConnectedEdgeList<(), (), SiteId>,
+	/// The rings bounding the cell of each site, in input order, as given by
+	/// `polygon::union`. Under the Manhattan and Chebyshev metrics cells need
+	/// not be convex, and a tie region can leave one in several pieces.
+	pub cells : Vec<Vec<Vec<Point>>>,
+	/// The sites with no cell inside the box, in input order.
+	pub empty_cells : Vec<SiteId>,
+}
+
+/// Computes the Voronoi diagram of `sites` inside `bound` under `metric`.
+///
+/// Under the Manhattan and Chebyshev metrics the bisector of two sites is a
+/// polyline of axis-aligned and 45° segments, and for some pairs of sites
+/// whole regions are equally far from both: under Manhattan when the sites
+/// lie on a common diagonal, and under Chebyshev when they share an x or y
+/// coordinate. Every tie, whether a region, a segment or a point, goes to
+/// the site with the lower `SiteId`. Sites at the same point are a tie
+/// everywhere, so only the first of them gets a cell. Sites with a NaN or
+/// infinite coordinate get no cell, and are listed in `empty_cells` too.
+///
+/// Euclidean cells come from the sweep. Under the other metrics each cell is
+/// cut from the box by its nearest sites, splitting it into pieces on which
+/// the distance to every site is linear. Far sites are skipped once they
+/// cannot reach the cell, so this takes about O(n) time per site for evenly
+/// spread sites, and O(n^2) at worst.
+pub fn metric_diagram(sites : &[Point], metric : Metric, bound : &Bound) -> MetricDiagram {
+	let (min, max, eps) = box_corners(bound);
+	let rectangle = polygon::rectangle(&min, &max);
+
+	let finite : Vec<usize> = (0..sites.len())
+		.filter(|&index| sites[index].x().is_finite() && sites[index].y().is_finite())
+		.collect();
+	let mut cells : Vec<Vec<Vec<Point>>> = if metric == Metric::Euclidean {
+		euclidean_cells(sites, &finite, bound)
+	} else {
+		let mut cells = vec![Vec::new(); sites.len()];
+		for &index in &finite {
+			cells[index] = cut_cell(sites, &finite, index, metric, &rectangle, eps);
+		}
+		cells
+	};
+
+	share_corners(cells.iter_mut().flatten().collect(), eps);
+
+	let mut dcel = DoublyConnectedEdgeList::from_rings(
+		cells.iter().enumerate()
+			.map(|(index, cell)| (cell.clone(), SiteId(index as u32)))
+			.collect(),
+		eps);
+
+	let mut empty_cells = Vec::new();
+	for (index, site) in sites.iter().enumerate() {
+		let face = dcel.faces[index].index();
+		if dcel[face].outer_component.is_empty() {
+			empty_cells.push(SiteId(index as u32));
+		}
+		if site.x().is_finite() && site.y().is_finite() {
+			let vertex = dcel.new_site_vertex(site);
+			dcel[face].site = Some(vertex);
+		}
+	}
+
+	MetricDiagram {
+		metric,
+		dcel,
+		cells,
+		empty_cells,
+	}
+}
+
+//The cells of the sweep over the finite sites, clipped to the box. A site
+//that repeats an earlier one gets no cell.
+fn euclidean_cells(sites : &[Point], finite : &[usize], bound : &Bound) -> Vec<Vec<Vec<Point>>> {
+	let mut cells = vec![Vec::new(); sites.len()];
+	if finite.is_empty() {
+		return cells;
+	}
+	let mut voroni_process = VoroniProcess::with_data(finite.iter().map(|&index| (sites[index], ())).collect(), 0.0);
+	voroni_process.execute();
+	let duplicates : HashSet<SiteId> = voroni_process.get_duplicates().iter().map(|&(duplicate, _)| duplicate).collect();
+	let clipped = clip_cells(voroni_process.get_dcel(), bound);
+	for (position, &index) in finite.iter().enumerate() {
+		let id = SiteId(position as u32);
+		if duplicates.contains(&id) {
+			continue;
+		}
+		if let Some(face) = voroni_process.get_site_face(id) {
+			if !clipped[face.index()].is_empty() {
+				cells[index] = vec![clipped[face.index()].clone()];
+			}
+		}
+	}
+	cells
+}
+
+fn cut_cell(sites : &[Point], finite : &[usize], index : usize, metric : Metric, rectangle : &[Point], eps : f64) -> Vec<Vec<Point>> {
+	let site = sites[index];
+	let mut pieces = vec![rectangle.to_vec()];
+	for line in metric.zone_lines(&site) {
+		pieces = split(pieces, line, eps);
+	}
+
+	let mut others : Vec<(f64, usize)> = finite.iter()
+		.filter(|&&other_index| other_index != index)
+		.map(|&other_index| (metric.distance(&site, &sites[other_index]), other_index))
+		.collect();
+	others.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+	for (gap, other_index) in others {
+		//Past twice the cell's reach no site can take anything from it
+		let reach = pieces.iter()
+			.flat_map(|piece| piece.iter())
+			.map(|corner| metric.distance(&site, corner))
+			.fold(0.0, f64::max);
+		if pieces.is_empty() || gap > 2.0 * reach {
+			break;
+		}
+
+		let other = sites[other_index];
+		for line in metric.zone_lines(&other) {
+			pieces = split(pieces, line, eps);
+		}
+		pieces = pieces.into_iter()
+			.filter_map(|piece| {
+				let inside = piece.iter().fold(Point::new(0.0, 0.0), |sum, p| sum + *p) * (1.0 / piece.len() as f64);
+				let (own_gradient, own_offset) = metric.linear_form(&site, &inside);
+				let (other_gradient, other_offset) = metric.linear_form(&other, &inside);
+				let normal = own_gradient - other_gradient;
+				let offset = other_offset - own_offset;
+				if normal.x() == 0.0 && normal.y() == 0.0 {
+					//Equally far across the whole piece
+					return if offset > 0.0 || (offset == 0.0 && index < other_index) { Some(piece) } else { None };
+				}
+				let piece = polygon::clip_half_plane(&piece, normal, offset);
+				if is_solid(&piece, eps) { Some(piece) } else { None }
+			})
+			.collect();
+	}
+	polygon::union(&pieces, eps)
+}
+
+//Cuts every piece in two along the line `normal . p = offset`
+fn split(pieces : Vec<Vec<Point>>, (normal, offset) : (Point, f64), eps : f64) -> Vec<Vec<Point>> {
+	let mut out = Vec::with_capacity(pieces.len());
+	for piece in pieces {
+		let below = polygon::clip_half_plane(&piece, normal, offset);
+		let above = polygon::clip_half_plane(&piece, normal * -1.0, -offset);
+		if is_solid(&below, eps) {
+			out.push(below);
+		}
+		if is_solid(&above, eps) {
+			out.push(above);
+		}
+	}
+	out
+}
+
+//Whether a convex piece is more than a rounding sliver
+fn is_solid(piece : &[Point], eps : f64) -> bool {
+	let perimeter : f64 = (0..piece.len())
+		.map(|i| {
+			let side = piece[(i + 1) % piece.len()] - piece[i];
+			side.dot(side).sqrt()
+		})
+		.sum();
+	polygon::signed_area(piece) > eps * perimeter
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+	use voroni::power::power_diagram;
+
+	fn area(rings : &[Vec<Point>]) -> f64 {
+		rings.iter().map(|ring| polygon::signed_area(ring)).sum()
+	}
+
+	fn cell_contains(rings : &[Vec<Point>], p : &Point) -> bool {
+		rings.iter().filter(|ring| polygon::contains(ring, p)).count() % 2 == 1
+	}
+
+	fn check(sites : &[Point], metric : Metric, rng : &mut XorShiftRng) {
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(20.0, 20.0));
+		let diagram = metric_diagram(sites, metric, &Bound::from_corners(&min, &max));
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+
+		let total : f64 = diagram.cells.iter().map(|cell| area(cell)).sum();
+		assert!((total - 400.0).abs() < 1e-6, "{:?} cells cover {}", metric, total);
+
+		//Random points lie in the cell of their nearest site, ties aside
+		for _ in 0..500 {
+			let p = Point::new(rng.gen_range(0.0, 20.0), rng.gen_range(0.0, 20.0));
+			let distances : Vec<f64> = sites.iter().map(|site| metric.distance(&p, site)).collect();
+			let nearest = distances.iter().cloned().fold(f64::INFINITY, f64::min);
+			let owner = distances.iter().position(|&d| d == nearest).unwrap();
+			let second = distances.iter().enumerate()
+				.filter(|&(i, _)| i != owner)
+				.map(|(_, &d)| d)
+				.fold(f64::INFINITY, f64::min);
+			if second - nearest > 1e-6 {
+				assert!(cell_contains(&diagram.cells[owner], &p), "{:?} misplaces {:?}", metric, p);
+			}
+		}
+	}
+
+	#[test]
+	fn test_metrics_tile_the_box() {
+		let mut rng = XorShiftRng::from_seed([5, 8, 13, 21]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(20.0, 20.0));
+		for &metric in &[Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+			let sites = distributions::uniform_box(&mut rng, 40, &min, &max);
+			check(&sites, metric, &mut rng);
+			//A grid is full of ties under both grid metrics
+			let sites = distributions::jittered_grid(&mut rng, 6, 6, 0.0, &min, &max);
+			check(&sites, metric, &mut rng);
+		}
+	}
+
+	#[test]
+	fn test_euclidean_matches_power() {
+		let mut rng = XorShiftRng::from_seed([1, 1, 2, 3]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+		let bound = Bound::from_corners(&min, &max);
+		let sites = distributions::uniform_box(&mut rng, 30, &min, &max);
+		let diagram = metric_diagram(&sites, Metric::Euclidean, &bound);
+		let power = power_diagram(&sites.iter().map(|&site| (site, 0.0)).collect::<Vec<_>>(), &bound);
+		for (cell, power_cell) in diagram.cells.iter().zip(&power.cells) {
+			assert!((area(cell) - polygon::signed_area(power_cell)).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn test_ties_go_to_the_lower_site() {
+		//On a common diagonal, the corners beyond both sites are ties
+		let sites = vec![Point::new(12.0, 12.0), Point::new(8.0, 8.0), Point::new(12.0, 12.0)];
+		let bound = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(20.0, 20.0));
+		let diagram = metric_diagram(&sites, Metric::Manhattan, &bound);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(diagram.empty_cells, vec![SiteId(2)]);
+		//Site 0 also takes both tied squares beside the diagonal
+		assert!((area(&diagram.cells[0]) - 264.0).abs() < 1e-9);
+		assert!((area(&diagram.cells[1]) - 136.0).abs() < 1e-9);
+
+		//The sweep keeps the first of two sites at one point too, and skips
+		//a site that is not finite
+		let sites = vec![Point::new(12.0, 12.0), Point::new(8.0, 8.0), Point::new(12.0, 12.0), Point::new(f64::NAN, 1.0)];
+		let diagram = metric_diagram(&sites, Metric::Euclidean, &bound);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(diagram.empty_cells, vec![SiteId(2), SiteId(3)]);
+		assert!((area(&diagram.cells[0]) - 200.0).abs() < 1e-9);
+	}
+}
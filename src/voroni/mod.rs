@@ -1,5 +1,21 @@
 
+pub mod apollonius;
+pub mod cells;
+pub mod empty_circle;
+pub mod envelope;
 pub mod event;
+pub mod farthest;
 pub mod geometry;
+pub mod interpolate;
+pub mod kth_order;
+pub mod lloyd;
+pub mod locate;
+pub mod medial_axis;
+pub mod metric;
+pub mod periodic;
+pub mod power;
+pub mod reference;
+pub mod shapes;
+pub mod spherical;
 pub mod status_struct;
 pub mod voroni_process;
\ No newline at end of file
@@ -0,0 +1,218 @@
+use std::collections::{BTreeMap, HashMap};
+
+use geometry::bound::Bound;
+use geometry::dcel::{DoublyConnectedEdgeList, EdgePtr, VertexPtr};
+use geometry::point::Point;
+use voroni::cells::clip_cells;
+use voroni::event::SiteId;
+use voroni::voroni_process::VoroniProcess;
+
+/// A Voronoi tessellation of a rectangular torus: the box of a `Bound` with
+/// its opposite sides joined.
+pub struct PeriodicDiagram {
+	/// One face per input site, in input order, carrying its `SiteId`. Every
+	/// edge is finite and every face is closed.
+	///
+	/// Vertices lie in the box, and a cell that crosses a side of the box
+	/// reaches some of its corners through the side. The data of each
+	/// half-edge is the number of box widths and heights to move its origin
+	/// by to get where the edge starts in the cell of its face, as listed in
+	/// `cells`.
+	pub dcel : DoublyConnectedEdgeList<(), (i32, i32), SiteId>,
+	/// The counter-clockwise cell of each site, around the site's position
+	/// wrapped into the box. Cells can stick out of the box.
+	pub cells : Vec<Vec<Point>>,
+	/// Each site wrapped into the box.
+	pub sites : Vec<Point>,
+	/// The sites with no cell: repeats of an earlier site on the torus, and
+	/// sites with a NaN or infinite coordinate. Their faces have no edges.
+	pub empty_cells : Vec<SiteId>,
+}
+
+/// Computes the Voronoi tessellation of `sites` on the torus made from the
+/// box of `bound`. Sites outside the box are wrapped into it.
+///
+/// The sites are copied into the eight boxes around the box, the copies
+/// are swept together, and the cells of the middle copies are stitched
+/// across the sides of the box. Every cell lies within half a box of its
+/// site, so the copies around the box are all that can reach it.
+pub fn periodic_voronoi(sites : &[Point], bound : &Bound) -> PeriodicDiagram {
+	let (min, max) = match (bound.get_min(), bound.get_max()) {
+		(Some(min), Some(max)) => (min, max),
+		_ => (Point::new(0.0, 0.0), Point::new(0.0, 0.0)),
+	};
+	let size = max - min;
+	let eps = size.x().max(size.y()) * 1e-9;
+	let wrap = |p : &Point| -> (Point, (i32, i32)) {
+		//Points a rounding error short of the far sides wrap to the near
+		//ones, so that both copies of a corner on a side agree
+		let k = ((p.x() - min.x() + eps) / size.x()).floor();
+		let l = ((p.y() - min.y() + eps) / size.y()).floor();
+		(Point::new(p.x() - k * size.x(), p.y() - l * size.y()), (k as i32, l as i32))
+	};
+
+	let wrapped : Vec<Point> = sites.iter().map(|site| wrap(site).0).collect();
+	let mut unique : Vec<usize> = Vec::new();
+	let mut empty = vec![false; sites.len()];
+	//Unique sites by their cell in a grid as wide as `eps`
+	let mut grid : HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+	let cell_of = |p : &Point| (((p.x() - min.x()) / eps).floor() as i64, ((p.y() - min.y()) / eps).floor() as i64);
+	for (index, site) in wrapped.iter().enumerate() {
+		let finite = site.x().is_finite() && site.y().is_finite();
+		if !finite || size.x() <= 0.0 || size.y() <= 0.0 {
+			empty[index] = true;
+			continue;
+		}
+		//Wrapping can round, so repeats only need to be close, and may lie
+		//across a side of the box
+		let mut repeat = false;
+		for &(k, l) in &[(0.0, 0.0), (-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0),
+				(1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)] {
+			let image = *site + Point::new(k * size.x(), l * size.y());
+			let (cx, cy) = cell_of(&image);
+			for (dx, dy) in (-1..2).flat_map(|dx| (-1..2).map(move |dy| (dx, dy))) {
+				repeat |= grid.get(&(cx + dx, cy + dy)).is_some_and(|bucket| bucket.iter().any(|&u| {
+					(wrapped[u].x() - image.x()).abs() <= eps && (wrapped[u].y() - image.y()).abs() <= eps
+				}));
+			}
+		}
+		if repeat {
+			empty[index] = true;
+		} else {
+			unique.push(index);
+			grid.entry(cell_of(site)).or_default().push(index);
+		}
+	}
+	let empty_cells : Vec<SiteId> = (0..sites.len()).filter(|&index| empty[index]).map(|index| SiteId(index as u32)).collect();
+
+	let mut cells = vec![Vec::new(); sites.len()];
+	if !unique.is_empty() {
+		//The middle copies come first, so their ids match `unique`
+		let mut images = Vec::with_capacity(9 * unique.len());
+		for &(k, l) in &[(0.0, 0.0), (-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0),
+				(1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)] {
+			let shift = Point::new(k * size.x(), l * size.y());
+			images.extend(unique.iter().map(|&u| (wrapped[u] + shift, ())));
+		}
+		let mut voroni_process = VoroniProcess::with_data(images, 0.0);
+		voroni_process.execute();
+		let outer = Bound::from_corners(&(min - size), &(max + size));
+		let image_cells = clip_cells(voroni_process.get_dcel(), &outer);
+		for (position, &u) in unique.iter().enumerate() {
+			if let Some(face) = voroni_process.get_site_face(SiteId(position as u32)) {
+				cells[u] = image_cells[face.index()].clone();
+			}
+		}
+	}
+
+	let mut dcel = DoublyConnectedEdgeList::with_tolerance(eps);
+	for index in 0..sites.len() {
+		dcel.new_face_with_data(None, SiteId(index as u32));
+	}
+	//Half-edges by their ends, and how many boxes the end is from the start
+	let mut sides : BTreeMap<(VertexPtr, VertexPtr, (i32, i32)), EdgePtr> = BTreeMap::new();
+	for (index, cell) in cells.iter().enumerate() {
+		let mut corners : Vec<(VertexPtr, (i32, i32))> = cell.iter()
+			.map(|corner| {
+				let (point, offset) = wrap(corner);
+				(dcel.new_vertex(&point), offset)
+			})
+			.collect();
+		corners.dedup();
+		while corners.len() > 1 && corners.first() == corners.last() {
+			corners.pop();
+		}
+		let face = dcel.faces[index].index();
+		let mut edges = Vec::with_capacity(corners.len());
+		for (i, &(vertex, offset)) in corners.iter().enumerate() {
+			let (end, end_offset) = corners[(i + 1) % corners.len()];
+			let edge = dcel.new_edge_with_data(Some(vertex), offset);
+			dcel[edge].incident_face = Some(face);
+			if dcel[vertex].incident_edge.is_none() {
+				dcel[vertex].incident_edge = Some(edge);
+			}
+			let jump = (end_offset.0 - offset.0, end_offset.1 - offset.1);
+			if let Some(twin) = sides.remove(&(end, vertex, (-jump.0, -jump.1))) {
+				dcel[edge].twin = Some(twin);
+				dcel[twin].twin = Some(edge);
+			} else {
+				sides.insert((vertex, end, jump), edge);
+			}
+			edges.push(edge);
+		}
+		for i in 0..edges.len() {
+			dcel.link_edges(edges[i], edges[(i + 1) % edges.len()]);
+		}
+		if let Some(&first) = edges.first() {
+			dcel[face].outer_component.push(first);
+		}
+	}
+	for (index, site) in wrapped.iter().enumerate() {
+		if !empty[index] {
+			let face = dcel.faces[index].index();
+			let vertex = dcel.new_site_vertex(site);
+			dcel[face].site = Some(vertex);
+		}
+	}
+
+	PeriodicDiagram {
+		dcel,
+		cells,
+		sites : wrapped,
+		empty_cells,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use geometry::polygon;
+	use rand::{SeedableRng, XorShiftRng};
+
+	fn torus_distance(a : &Point, b : &Point, size : &Point) -> f64 {
+		let dx = (a.x() - b.x()).abs() % size.x();
+		let dy = (a.y() - b.y()).abs() % size.y();
+		dx.min(size.x() - dx).hypot(dy.min(size.y() - dy))
+	}
+
+	#[test]
+	fn test_torus_is_closed() {
+		let mut rng = XorShiftRng::from_seed([9, 9, 8, 1]);
+		let (min, max) = (Point::new(-2.0, 1.0), Point::new(8.0, 5.0));
+		let size = max - min;
+		let bound = Bound::from_corners(&min, &max);
+		for count in 1..40 {
+			let mut sites = distributions::uniform_box(&mut rng, count, &min, &max);
+			//A copy of the first site one box over is the same site
+			sites.push(sites[0] + Point::new(size.x(), -size.y()));
+			let diagram = periodic_voronoi(&sites, &bound);
+			let dcel = &diagram.dcel;
+			assert_eq!(dcel.validate(), Ok(()));
+			assert_eq!(diagram.empty_cells, vec![SiteId(count as u32)]);
+
+			assert!(dcel.edges.iter().all(|edge| edge.twin.is_some() && edge.next.is_some() && !edge.is_inf));
+			let total : f64 = diagram.cells.iter().map(|cell| polygon::signed_area(cell)).sum();
+			assert!((total - size.x() * size.y()).abs() < 1e-9);
+
+			//A torus has Euler characteristic zero
+			let vertices = dcel.vertices.iter().filter(|v| !v.is_site).count() as i64;
+			assert_eq!(vertices - dcel.edges.len() as i64 / 2 + count as i64, 0);
+
+			//Corners are as far from the sites on both sides of their edges
+			for edge in &dcel.edges {
+				let corner = dcel[edge.origin.unwrap()].point;
+				let own = diagram.sites[dcel[edge.incident_face.unwrap()].data.index()];
+				let other = diagram.sites[dcel[dcel[edge.twin.unwrap()].incident_face.unwrap()].data.index()];
+				let gap = torus_distance(&corner, &own, &size) - torus_distance(&corner, &other, &size);
+				assert!(gap.abs() < 1e-9);
+			}
+		}
+
+		//Sites a rounding error apart across a side of the box are repeats
+		let sites = [Point::new(8.0 - 1.2e-8, 3.0), Point::new(3.0, 2.0), Point::new(-2.0 - 0.5e-8, 3.0)];
+		let diagram = periodic_voronoi(&sites, &bound);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(diagram.empty_cells, vec![SiteId(2)]);
+	}
+}
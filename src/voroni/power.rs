@@ -0,0 +1,171 @@
+use delaunay::regular::RegularTriangulation;
+use geometry::bound::Bound;
+use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::cells::{box_corners, cut_convex};
+use voroni::event::SiteId;
+
+/// A power diagram (weighted Voronoi diagram), clipped to a box.
+pub struct PowerDiagram {
+	/// One face per input site, in input order, carrying its `SiteId`. The
+	/// site of each face is set to a vertex at the site's point.
+	pub dcel : DoublyConnectedEdgeList<(), (), SiteId>,
+	/// The counter-clockwise cell of each site, in input order.
+	pub cells : Vec<Vec<Point>>,
+	/// The sites that the others outweigh everywhere, so that they have no
+	/// cell at all, in input order. Their faces have no edges.
+	pub dominated : Vec<SiteId>,
+	/// The sites that have a cell, but not inside the box, in input order.
+	/// Their faces have no edges either.
+	pub outside_box : Vec<SiteId>,
+}
+
+/// Computes the power diagram of weighted sites inside `bound`. A point
+/// belongs to the site with the least power distance `|x - p|^2 - w`, so a
+/// site's weight is the square of its radius when modelling disks.
+///
+/// Sites at the same point are settled by weight alone: the heavier one
+/// takes the whole cell, and between equal weights the lower `SiteId` does.
+/// A site whose disk lies inside the union of the others can end up with
+/// no cell at all, and is listed in `dominated`. Sites with a NaN or
+/// infinite coordinate or weight get an empty cell, and are in neither list.
+///
+/// The cells are dual to the regular triangulation of the sites, so each one
+/// is cut from the box by the power bisectors with its neighbours there
+/// alone, which takes O(n log n) time for evenly spread sites.
+pub fn power_diagram(sites : &[(Point, f64)], bound : &Bound) -> PowerDiagram {
+	let (min, max, eps) = box_corners(bound);
+	let rectangle = polygon::rectangle(&min, &max);
+	let triangulation = RegularTriangulation::new(sites);
+	let finite = |&(site, weight) : &(Point, f64)| site.x().is_finite() && site.y().is_finite() && weight.is_finite();
+
+	let cells : Vec<Vec<Point>> = sites.iter().enumerate()
+		.map(|(i, &(site, weight))| {
+			if !finite(&sites[i]) || triangulation.is_redundant(i) {
+				return Vec::new();
+			}
+			let bisectors = triangulation.neighbours(i).iter()
+				.map(|&j| power_bisector(&site, weight, &sites[j].0, sites[j].1));
+			cut_convex(rectangle.clone(), bisectors)
+		})
+		.collect();
+
+	//Merge corners that differ only by rounding, relative to the box
+	let mut dcel = DoublyConnectedEdgeList::from_polygons(
+		cells.iter().enumerate()
+			.map(|(index, cell)| (cell.clone(), SiteId(index as u32)))
+			.collect(),
+		eps);
+
+	let mut dominated = Vec::new();
+	let mut outside_box = Vec::new();
+	for (index, &(site, _)) in sites.iter().enumerate() {
+		let face = dcel.faces[index].index();
+		if !finite(&sites[index]) {
+			continue;
+		}
+		if triangulation.is_redundant(index) {
+			dominated.push(SiteId(index as u32));
+		} else if dcel[face].outer_component.is_empty() {
+			outside_box.push(SiteId(index as u32));
+		}
+		let vertex = dcel.new_site_vertex(&site);
+		dcel[face].site = Some(vertex);
+	}
+
+	PowerDiagram {
+		dcel,
+		cells,
+		dominated,
+		outside_box,
+	}
+}
+
+//The half-plane, as (normal, offset), with less power distance to `site`
+//than to `other`
+fn power_bisector(site : &Point, weight : f64, other : &Point, other_weight : f64) -> (Point, f64) {
+	let normal = *other - *site;
+	(normal, (other.dot(*other) - site.dot(*site) + weight - other_weight) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+	use voroni::cells::clip_cells;
+	use voroni::voroni_process::VoroniProcess;
+
+	fn power(p : &Point, site : &(Point, f64)) -> f64 {
+		(*p - site.0).dot(*p - site.0) - site.1
+	}
+
+	#[test]
+	fn test_equal_weights_match_voroni() {
+		let mut rng = XorShiftRng::from_seed([3, 3, 3, 3]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(10.0, 10.0));
+		let bound = Bound::from_corners(&min, &max);
+		let sites = distributions::uniform_box(&mut rng, 30, &min, &max);
+
+		let diagram = power_diagram(&sites.iter().map(|&site| (site, 2.0)).collect::<Vec<_>>(), &bound);
+		let mut vp = VoroniProcess::with_data(sites.iter().map(|&site| (site, ())).collect(), 0.0);
+		vp.execute();
+		let voroni_cells = clip_cells(vp.get_dcel(), &bound);
+
+		assert!(diagram.dominated.is_empty() && diagram.outside_box.is_empty());
+		for (index, cell) in diagram.cells.iter().enumerate() {
+			let face = vp.get_site_face(SiteId(index as u32)).unwrap();
+			let area = polygon::signed_area(&voroni_cells[face.index()]);
+			assert!((polygon::signed_area(cell) - area).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn test_weighted_cells() {
+		let mut rng = XorShiftRng::from_seed([9, 2, 6, 5]);
+		let (min, max) = (Point::new(0.0, 0.0), Point::new(20.0, 20.0));
+		let bound = Bound::from_corners(&min, &max);
+		let mut sites : Vec<(Point, f64)> = distributions::uniform_box(&mut rng, 40, &min, &max).into_iter()
+			.map(|site| (site, rng.gen_range(0.0, 9.0)))
+			.collect();
+		//A small disk inside a big one, a site under a heavier twin, and a
+		//site whose cell stays clear of the box
+		sites.push((Point::new(10.0, 10.0), 400.0));
+		sites.push((Point::new(10.5, 10.0), 0.1));
+		sites.push((sites[0].0, sites[0].1 - 1.0));
+		sites.push((Point::new(-40.0, 10.0), 0.0));
+
+		let diagram = power_diagram(&sites, &bound);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert_eq!(diagram.dcel.faces.len(), sites.len());
+
+		let total : f64 = diagram.cells.iter().map(|cell| polygon::signed_area(cell)).sum();
+		assert!((total - 400.0).abs() < 1e-6);
+		assert!(diagram.dominated.contains(&SiteId(41)));
+		assert!(diagram.dominated.contains(&SiteId(42)));
+		assert!(diagram.outside_box.contains(&SiteId(43)));
+		assert!(diagram.outside_box.iter().all(|id| !diagram.dominated.contains(id)));
+
+		//Every cell's centroid is closest, by power, to its own site
+		for (index, cell) in diagram.cells.iter().enumerate() {
+			if let Some(centroid) = polygon::centroid(cell) {
+				let own = power(&centroid, &sites[index]);
+				for site in &sites {
+					assert!(own <= power(&centroid, site) + 1e-9);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_site_on_corner() {
+		//The site vertex stays apart from the corner of the box it sits on
+		let bound = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+		let diagram = power_diagram(&[(Point::new(0.0, 0.0), 1.0), (Point::new(6.0, 6.0), 1.0)], &bound);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		let site = diagram.dcel.faces[0].site.unwrap();
+		assert!(diagram.dcel[site].is_site);
+		assert!(diagram.dcel[site].incident_edge.is_none());
+	}
+}
@@ -0,0 +1,183 @@
+use geometry::polygon;
+use geometry::point::Point;
+
+/////////////////////////////////////////////////////////////////
+
+/// A slow but simple Voronoi construction, used to check `VoroniProcess`.
+///
+/// The cell of every site is found on its own by clipping a large box with
+/// the bisector of the site and each other site, which takes O(n^2) time.
+pub struct ReferenceDiagram {
+	pub sites : Vec<Point>,
+	/// The counter-clockwise cell of each site, clipped to the box.
+	pub cells : Vec<Vec<Point>>,
+	min : Point,
+	max : Point,
+}
+
+impl ReferenceDiagram {
+	/// Builds the diagram inside a box `scale` times wider than the sites.
+	/// The box has to be large enough to hold every Voronoi vertex, or the
+	/// vertices outside it are lost.
+	pub fn new(sites : &[Point], scale : f64) -> ReferenceDiagram {
+		let (mut min, mut max) = (sites[0], sites[0]);
+		for site in sites {
+			min = Point::new(min.x().min(site.x()), min.y().min(site.y()));
+			max = Point::new(max.x().max(site.x()), max.y().max(site.y()));
+		}
+		let diff = max - min;
+		let margin = (diff.x().max(diff.y()) + 1.0) * scale;
+		let min = min - Point::new(margin, margin);
+		let max = max + Point::new(margin, margin);
+
+		let bound = polygon::rectangle(&min, &max);
+		let cells = sites.iter().enumerate()
+			.map(|(i, site)| {
+				let mut cell = bound.clone();
+				for (j, other) in sites.iter().enumerate() {
+					if i != j && other != site {
+						cell = polygon::clip_bisector(&cell, site, other);
+					}
+				}
+				cell
+			})
+			.collect();
+
+		ReferenceDiagram {
+			sites : sites.to_vec(),
+			cells,
+			min,
+			max,
+		}
+	}
+
+	/// The Voronoi vertices: cell corners that are not on the box, with
+	/// corners closer than `tolerance` (relative to their magnitude) merged.
+	pub fn vertices(&self, tolerance : f64) -> Vec<Point> {
+		let on_box = |p : &Point| {
+			let eps = tolerance * (1.0 + p.x().abs().max(p.y().abs()));
+			(p.x() - self.min.x()).abs() < eps || (p.x() - self.max.x()).abs() < eps
+				|| (p.y() - self.min.y()).abs() < eps || (p.y() - self.max.y()).abs() < eps
+		};
+
+		let mut vertices : Vec<Point> = Vec::new();
+		for corner in self.cells.iter().flat_map(|cell| cell.iter()) {
+			if on_box(corner) {
+				continue;
+			}
+			let eps = tolerance * (1.0 + corner.x().abs().max(corner.y().abs()));
+			if !vertices.iter().any(|v| (*v - *corner).dot(*v - *corner) < eps * eps) {
+				vertices.push(*corner);
+			}
+		}
+		vertices
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use generate::distributions;
+	use geometry::dcel::DoublyConnectedEdgeList;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+	use voroni::geometry::get_distance;
+	use voroni::voroni_process::VoroniProcess;
+
+	const TOLERANCE : f64 = 1e-6;
+
+	fn sweep(sites : &[Point]) -> DoublyConnectedEdgeList {
+		let mut vp = VoroniProcess::with_data(sites.iter().map(|site| (*site, ())).collect(), 0.0);
+		vp.execute();
+		vp.into_dcel()
+	}
+
+	fn scaled_eps(p : &Point) -> f64 {
+		TOLERANCE * (1.0 + p.x().abs().max(p.y().abs()))
+	}
+
+	fn check_against_reference(sites : &[Point]) {
+		let dcel = sweep(sites);
+		let reference = ReferenceDiagram::new(sites, 1e6);
+
+		//Same number of Voronoi vertices
+		let voroni_vertices : Vec<Point> = dcel.vertices.iter()
+			.filter(|v| !v.is_site)
+			.map(|v| v.point)
+			.collect();
+		assert_eq!(voroni_vertices.len(), reference.vertices(TOLERANCE).len(),
+			"vertex count differs for {:?}", sites);
+
+		//Every vertex is equidistant to its three nearest sites
+		for vertex in &voroni_vertices {
+			let mut distances : Vec<f64> = sites.iter().map(|site| get_distance(vertex, site)).collect();
+			distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			assert!(distances[2] - distances[0] < scaled_eps(vertex),
+				"vertex {:?} is not equidistant to three sites of {:?}", vertex, sites);
+		}
+
+		//Every finite edge lies on the bisector of its two sites, with the
+		//site of its face on its left, so each cell contains its site
+		for edge in &dcel.edges {
+			let twin = &dcel[edge.twin.unwrap()];
+			let site = dcel[dcel[edge.incident_face.unwrap()].site.unwrap()].point;
+			let other = dcel[dcel[twin.incident_face.unwrap()].site.unwrap()].point;
+			if let (Some(a), Some(b)) = (edge.origin, twin.origin) {
+				let (a, b) = (dcel[a].point, dcel[b].point);
+				for end in &[a, b] {
+					let gap = (get_distance(end, &site) - get_distance(end, &other)).abs();
+					assert!(gap < scaled_eps(end), "edge {:?} is off its bisector in {:?}", edge, sites);
+				}
+				assert!((b - a).cross(site - a) >= 0.0, "site is right of edge {:?} in {:?}", edge, sites);
+			} else {
+				assert!(edge.is_inf && twin.is_inf);
+			}
+			if let Some(next) = edge.next {
+				assert_eq!(dcel[next].prev, Some(edge.index()));
+				assert_eq!(dcel[next].origin, twin.origin);
+				assert_eq!(dcel[next].incident_face, edge.incident_face);
+			}
+		}
+	}
+
+	#[test]
+	fn test_reference_square() {
+		let sites = vec![
+			Point::new(0.0, 1.0), Point::new(2.0, 1.0),
+			Point::new(1.0, 0.0), Point::new(1.0, 2.0),
+		];
+		let reference = ReferenceDiagram::new(&sites, 10.0);
+		assert_eq!(reference.vertices(TOLERANCE), vec![Point::new(1.0, 1.0)]);
+		for cell in &reference.cells {
+			assert!(polygon::signed_area(cell) > 0.0);
+		}
+	}
+
+	#[test]
+	fn test_sweep_matches_reference() {
+		let mut rng = XorShiftRng::from_seed([3, 1, 4, 1]);
+		for count in 1..40 {
+			for _ in 0..5 {
+				let sites = distributions::uniform_box(&mut rng, count, &Point::new(-100.0, -100.0), &Point::new(100.0, 100.0));
+				check_against_reference(&sites);
+			}
+		}
+	}
+
+	#[test]
+	fn test_sweep_matches_reference_on_integer_grid() {
+		//Small integer coordinates give plenty of shared x and y values
+		let mut rng = XorShiftRng::from_seed([2, 7, 1, 8]);
+		for count in 2..25 {
+			for _ in 0..5 {
+				let mut sites : Vec<Point> = Vec::new();
+				while sites.len() < count {
+					let site = Point::new(rng.gen_range(0, 40) as f64, rng.gen_range(0, 40) as f64 * 0.5 + 0.25);
+					if !sites.contains(&site) {
+						sites.push(site);
+					}
+				}
+				check_against_reference(&sites);
+			}
+		}
+	}
+}
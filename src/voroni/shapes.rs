@@ -0,0 +1,278 @@
+use std::f64::consts::PI;
+
+use geometry::bound::Bound;
+use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::point::Point;
+use geometry::polygon;
+use voroni::cells::{box_corners, share_corners};
+use voroni::envelope::{nearest_regions, CurvedEdge, Feature};
+use voroni::event::SiteId;
+use voroni::geometry::get_distance;
+
+/// A site that need not be a point.
+#[derive(Clone, PartialEq, Debug)]
+pub enum SiteShape {
+	Point(Point),
+	/// A line segment between two points.
+	Segment(Point, Point),
+	/// A simple polygon, given by its ring of corners in either order, with
+	/// the last corner joined back to the first. The region it encloses
+	/// belongs to its cell.
+	Polygon(Vec<Point>),
+}
+
+impl SiteShape {
+	/// The distance from `p` to the nearest point of the shape. Points
+	/// inside a polygon are at distance zero.
+	pub fn distance(&self, p : &Point) -> f64 {
+		match *self {
+			SiteShape::Point(site) => get_distance(p, &site),
+			SiteShape::Segment(a, b) => polygon::distance_to_segment(p, &a, &b),
+			SiteShape::Polygon(ref ring) => {
+				if polygon::contains(ring, p) {
+					return 0.0;
+				}
+				(0..ring.len())
+					.map(|i| polygon::distance_to_segment(p, &ring[i], &ring[(i + 1) % ring.len()]))
+					.fold(f64::INFINITY, f64::min)
+			},
+		}
+	}
+
+	//The parts of the shape that can be nearest to a point outside it, and
+	//what it covers itself, or `None` if it is not finite
+	fn features(&self) -> Option<(Vec<Feature>, Option<Vec<Point>>)> {
+		let finite = |p : &Point| p.x().is_finite() && p.y().is_finite();
+		match *self {
+			SiteShape::Point(site) if finite(&site) => Some((vec![Feature::Point(site, 0.0, 0.0, 2.0 * PI)], None)),
+			SiteShape::Segment(a, b) if finite(&a) && finite(&b) => {
+				if a == b {
+					return SiteShape::Point(a).features();
+				}
+				//Each side, and the half-plane beyond each end
+				let angle = |p : Point| p.y().atan2(p.x());
+				Some((vec![
+					Feature::Side(a, b),
+					Feature::Side(b, a),
+					Feature::Point(a, 0.0, angle(a - b) - PI / 2.0, PI),
+					Feature::Point(b, 0.0, angle(b - a) - PI / 2.0, PI),
+				], None))
+			},
+			SiteShape::Polygon(ref ring) if ring.iter().all(finite) => {
+				let mut ring = ring.clone();
+				ring.dedup();
+				while ring.len() > 1 && ring[0] == ring[ring.len() - 1] {
+					ring.pop();
+				}
+				let area = polygon::signed_area(&ring);
+				if ring.len() < 3 || area == 0.0 {
+					return None;
+				}
+				if area < 0.0 {
+					ring.reverse();
+				}
+				//The outside lies to the right of every side, and each convex
+				//corner is nearest in the wedge between its sides' normals
+				let n = ring.len();
+				let mut features : Vec<Feature> = (0..n).map(|i| Feature::Side(ring[i], ring[(i + 1) % n])).collect();
+				for i in 0..n {
+					let (before, corner, after) = (ring[(i + n - 1) % n], ring[i], ring[(i + 1) % n]);
+					let (incoming, outgoing) = (corner - before, after - corner);
+					let turn = incoming.cross(outgoing).atan2(incoming.dot(outgoing));
+					if turn > 0.0 {
+						features.push(Feature::Point(corner, 0.0, (-incoming.x()).atan2(incoming.y()), turn));
+					}
+				}
+				Some((features, Some(ring)))
+			},
+			_ => None,
+		}
+	}
+}
+
+/// A Voronoi diagram of points, segments and polygons, clipped to a box.
+pub struct ShapeDiagram {
+	/// One face per input shape, in input order, carrying its `SiteId`. The
+	/// faces of point shapes have their site set. Curved edges appear as
+	/// chains of straight half-edges.
+	pub dcel : DoublyConnectedEdgeList<(), (), SiteId>,
+	/// The rings bounding the cell of each shape, in input order, as given
+	/// by `polygon::union`. A cell wraps around its shape, so it can have
+	/// holes where other shapes sit inside its reach.
+	pub cells : Vec<Vec<Vec<Point>>>,
+	/// Every edge between two cells, once, as arcs of parabolas between a
+	/// point and a side, and straight segments elsewhere.
+	pub edges : Vec<CurvedEdge>,
+}
+
+/// Computes the Voronoi diagram of `shapes` inside `bound`.
+///
+/// Each shape is split into the parts that can be nearest to a point: the
+/// shape itself for a point, each side and the half-plane beyond each end
+/// for a segment, and each side and convex corner for a polygon, which also
+/// keeps what it encloses. The region nearest to each part is found exactly
+/// by `envelope::nearest_regions`, and the regions of each shape's parts
+/// are joined into its cell. The bisector of a point and a side is a
+/// parabola, and of two points or two sides a straight line, so edges come
+/// out as exact arcs, and cells follow them as polylines that stray from
+/// them by at most `tolerance`.
+///
+/// Shapes must lie inside the box, apart from one another, with no shape
+/// inside a polygon, and polygons must be simple. A point given twice goes
+/// to the first of its shapes, and a shape with a NaN or infinite
+/// coordinate gets an empty cell.
+pub fn shape_voronoi(shapes : &[SiteShape], bound : &Bound, tolerance : f64) -> ShapeDiagram {
+	let (min, max, eps) = box_corners(bound);
+	let tolerance = if tolerance > 0.0 { tolerance.max(eps * 1e-3) } else { (max - min).x().max((max - min).y()) / 1e4 };
+
+	let mut features = Vec::new();
+	let mut owners = Vec::new();
+	let mut pieces : Vec<Vec<Vec<Point>>> = vec![Vec::new(); shapes.len()];
+	for (index, shape) in shapes.iter().enumerate() {
+		let repeated = match *shape {
+			SiteShape::Point(site) => shapes[..index].contains(&SiteShape::Point(site)),
+			_ => false,
+		};
+		if let (Some((parts, inside)), false) = (shape.features(), repeated) {
+			owners.extend(parts.iter().map(|_| index));
+			features.extend(parts);
+			pieces[index].extend(inside);
+		}
+	}
+
+	let regions = nearest_regions(&features, &min, &max, eps, tolerance);
+	for (polygon, &owner) in regions.polygons.into_iter().zip(&owners) {
+		if !polygon.is_empty() {
+			pieces[owner].push(polygon);
+		}
+	}
+	let edges = regions.edges.into_iter()
+		.filter(|edge| owners[edge.features.0] != owners[edge.features.1])
+		.map(|edge| CurvedEdge {
+			sites : (SiteId(owners[edge.features.0] as u32), SiteId(owners[edge.features.1] as u32)),
+			arcs : edge.arcs,
+			points : edge.points,
+		})
+		.collect();
+
+	let mut cells : Vec<Vec<Vec<Point>>> = pieces.iter().map(|pieces| polygon::union(pieces, eps)).collect();
+	share_corners(cells.iter_mut().flatten().collect(), eps);
+	let mut dcel = DoublyConnectedEdgeList::from_rings(
+		cells.iter().enumerate()
+			.map(|(index, cell)| (cell.clone(), SiteId(index as u32)))
+			.collect(),
+		eps);
+	for (index, shape) in shapes.iter().enumerate() {
+		if let SiteShape::Point(site) = *shape {
+			if site.x().is_finite() && site.y().is_finite() {
+				let face = dcel.faces[index].index();
+				let vertex = dcel.new_site_vertex(&site);
+				dcel[face].site = Some(vertex);
+			}
+		}
+	}
+
+	ShapeDiagram {
+		dcel,
+		cells,
+		edges,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+
+	#[test]
+	fn test_cells_hold_their_nearest_shape() {
+		let shapes = vec![
+			SiteShape::Point(Point::new(3.0, 15.0)),
+			SiteShape::Segment(Point::new(2.0, 4.0), Point::new(9.0, 8.0)),
+			SiteShape::Polygon(vec![Point::new(13.0, 12.0), Point::new(17.0, 12.0), Point::new(15.0, 17.0)]),
+			SiteShape::Segment(Point::new(12.0, 2.0), Point::new(18.0, 6.0)),
+			SiteShape::Point(Point::new(9.0, 12.0)),
+		];
+		let bound = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(20.0, 20.0));
+		let diagram = shape_voronoi(&shapes, &bound, 1e-4);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+
+		let total : f64 = diagram.cells.iter().flatten().map(|ring| polygon::signed_area(ring)).sum();
+		assert!((total - 400.0).abs() < 1e-6);
+
+		let mut rng = XorShiftRng::from_seed([2, 4, 6, 8]);
+		for _ in 0..1000 {
+			let p = Point::new(rng.gen_range(0.0, 20.0), rng.gen_range(0.0, 20.0));
+			let mut distances : Vec<(f64, usize)> = shapes.iter().enumerate()
+				.map(|(index, shape)| (shape.distance(&p), index))
+				.collect();
+			distances.sort_by(|a, b| a.partial_cmp(b).unwrap());
+			if distances[1].0 - distances[0].0 > 1e-3 {
+				assert!(cell_contains(&diagram.cells[distances[0].1], &p), "{:?} is not in the cell of shape {}", p, distances[0].1);
+			}
+		}
+		//The inside of the polygon is part of its cell
+		assert!(cell_contains(&diagram.cells[2], &Point::new(15.0, 14.0)));
+	}
+
+	#[test]
+	fn test_edges_are_exact() {
+		let shapes = vec![
+			SiteShape::Segment(Point::new(2.0, 2.0), Point::new(18.0, 4.0)),
+			SiteShape::Point(Point::new(8.0, 12.0)),
+			SiteShape::Polygon(vec![Point::new(13.0, 14.0), Point::new(17.0, 14.0), Point::new(17.0, 18.0), Point::new(13.0, 18.0)]),
+		];
+		let bound = Bound::from_corners(&Point::new(0.0, 0.0), &Point::new(20.0, 20.0));
+		let diagram = shape_voronoi(&shapes, &bound, 1e-3);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		let total : f64 = diagram.cells.iter().flatten().map(|ring| polygon::signed_area(ring)).sum();
+		assert!((total - 400.0).abs() < 1e-6);
+
+		//Every arc lies on the bisector of its two shapes, all the way along,
+		//and a point and a side are split by a parabola
+		let mut parabolas = 0;
+		for edge in &diagram.edges {
+			let (a, b) = (&shapes[edge.sites.0.index()], &shapes[edge.sites.1.index()]);
+			for arc in &edge.arcs {
+				for k in 0..11 {
+					let p = arc.point_at(k as f64 / 10.0);
+					assert!((a.distance(&p) - b.distance(&p)).abs() < 1e-9, "{:?} is off the edge between {:?} and {:?}", p, a, b);
+				}
+				let chord = arc.end - arc.start;
+				if chord.cross(arc.control - arc.start).abs() > 1e-9 * chord.dot(chord) {
+					assert!((arc.weight - 1.0).abs() < 1e-12);
+					parabolas += 1;
+				}
+			}
+			assert_eq!(edge.points.first(), Some(&edge.arcs[0].start));
+			assert_eq!(edge.points.last(), Some(&edge.arcs[edge.arcs.len() - 1].end));
+		}
+		assert!(parabolas >= 2);
+
+		//Repeated points and shapes that are not finite get no cell
+		let shapes = vec![
+			SiteShape::Point(Point::new(5.0, 5.0)),
+			SiteShape::Point(Point::new(5.0, 5.0)),
+			SiteShape::Segment(Point::new(1.0, 1.0), Point::new(f64::NAN, 3.0)),
+			SiteShape::Segment(Point::new(12.0, 12.0), Point::new(16.0, 12.0)),
+		];
+		let diagram = shape_voronoi(&shapes, &bound, 1e-3);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		assert!(diagram.cells[1].is_empty() && diagram.cells[2].is_empty());
+		let total : f64 = diagram.cells.iter().flatten().map(|ring| polygon::signed_area(ring)).sum();
+		assert!((total - 400.0).abs() < 1e-6);
+
+		//A slanted segment alone, whose two faces and ends meet only along it,
+		//which rounding puts a hair either side
+		let shapes = vec![SiteShape::Segment(Point::new(12.007071457388736, 7.54766639734878), Point::new(14.683848942059837, 9.876905569998566))];
+		let diagram = shape_voronoi(&shapes, &bound, 1e-3);
+		assert_eq!(diagram.dcel.validate(), Ok(()));
+		let total : f64 = diagram.cells[0].iter().map(|ring| polygon::signed_area(ring)).sum();
+		assert!((total - 400.0).abs() < 1e-6);
+	}
+
+	//Inside an odd number of rings, counting holes
+	fn cell_contains(rings : &[Vec<Point>], p : &Point) -> bool {
+		rings.iter().filter(|ring| polygon::contains(ring, p)).count() % 2 == 1
+	}
+}
@@ -0,0 +1,237 @@
+use std::collections::{HashMap, HashSet};
+
+use geometry::geo::{GeoPoint, Vector3, EARTH_RADIUS_KM};
+use voroni::event::SiteId;
+
+/// A Voronoi diagram on the sphere, with distance measured along great
+/// circles.
+pub struct SphericalDiagram {
+	pub sites : Vec<GeoPoint>,
+	/// The corners of each site's cell, in input order, counter-clockwise
+	/// as seen from outside the sphere. Neighbouring corners are joined by
+	/// the shorter great-circle arc.
+	pub cells : Vec<Vec<GeoPoint>>,
+	/// The sites across each side of each cell: the side from corner `i` to
+	/// corner `i + 1` borders `neighbours[site][i + 1]`'s cell.
+	pub neighbours : Vec<Vec<SiteId>>,
+	/// The sites with no cell: repeats of an earlier site, sites with a NaN
+	/// or infinite coordinate, and every site when there are fewer than
+	/// four or they all lie on one circle of the sphere.
+	pub empty_cells : Vec<SiteId>,
+}
+
+impl SphericalDiagram {
+	/// The area of a site's cell in steradians. The cells cover `4π`.
+	pub fn area(&self, site : SiteId) -> f64 {
+		let cell = &self.cells[site.index()];
+		let center = self.sites[site.index()].to_vector();
+		(0..cell.len())
+			.map(|i| triangle_area(&center, &cell[i].to_vector(), &cell[(i + 1) % cell.len()].to_vector()))
+			.sum()
+	}
+
+	/// The area of a site's cell on the Earth, in square kilometres.
+	pub fn area_km2(&self, site : SiteId) -> f64 {
+		self.area(site) * EARTH_RADIUS_KM * EARTH_RADIUS_KM
+	}
+}
+
+/// Computes the Voronoi diagram of sites on the sphere.
+///
+/// The sites are turned into unit vectors and their 3D convex hull is
+/// built one site at a time, in O(n^2) time. Each hull triangle is a
+/// Delaunay triangle on the sphere, and its outward normal is the Voronoi
+/// vertex equally far from its three sites, so the cell of a site is the
+/// ring of normals of the triangles around it.
+pub fn spherical_voronoi(sites : &[GeoPoint]) -> SphericalDiagram {
+	let vectors : Vec<Vector3> = sites.iter().map(|site| site.to_vector()).collect();
+	let mut candidates : Vec<usize> = Vec::new();
+	for (index, v) in vectors.iter().enumerate() {
+		let finite = v.x.is_finite() && v.y.is_finite() && v.z.is_finite();
+		if finite && !candidates.iter().any(|&c| vectors[c].angle_to(v) < 1e-12) {
+			candidates.push(index);
+		}
+	}
+
+	let mut diagram = SphericalDiagram {
+		sites : sites.to_vec(),
+		cells : vec![Vec::new(); sites.len()],
+		neighbours : vec![Vec::new(); sites.len()],
+		empty_cells : Vec::new(),
+	};
+	let triangles = convex_hull(&vectors, &candidates).unwrap_or_default();
+
+	//Each triangle by its counter-clockwise sides
+	let mut side_triangle : HashMap<(usize, usize), usize> = HashMap::new();
+	let mut first_triangle : HashMap<usize, usize> = HashMap::new();
+	for (t, triangle) in triangles.iter().enumerate() {
+		for k in 0..3 {
+			side_triangle.insert((triangle[k], triangle[(k + 1) % 3]), t);
+			first_triangle.entry(triangle[k]).or_insert(t);
+		}
+	}
+	let centers : Vec<Vector3> = triangles.iter()
+		.map(|t| (vectors[t[1]] - vectors[t[0]]).cross(&(vectors[t[2]] - vectors[t[0]])).unit())
+		.collect();
+
+	for index in 0..sites.len() {
+		let start = match first_triangle.get(&index) {
+			Some(&t) => t,
+			None => {
+				diagram.empty_cells.push(SiteId(index as u32));
+				continue;
+			},
+		};
+		//Turn counter-clockwise round the site from triangle to triangle
+		let mut triangle = start;
+		loop {
+			let t = triangles[triangle];
+			let k = (0..3).find(|&k| t[k] == index).unwrap();
+			let (after, last) = (t[(k + 1) % 3], t[(k + 2) % 3]);
+			let corner = GeoPoint::from_vector(&centers[triangle]);
+			//Cocircular sites give several triangles with the same center
+			let repeat = diagram.cells[index].last().is_some_and(|c| c.angle_to(&corner) < 1e-12);
+			if !repeat {
+				diagram.cells[index].push(corner);
+				diagram.neighbours[index].push(SiteId(after as u32));
+			}
+			triangle = side_triangle[&(index, last)];
+			if triangle == start {
+				break;
+			}
+		}
+		let cell = &mut diagram.cells[index];
+		if cell.len() > 1 && cell[0].angle_to(cell.last().unwrap()) < 1e-12 {
+			cell.pop();
+			let neighbour = diagram.neighbours[index].pop().unwrap();
+			diagram.neighbours[index][0] = neighbour;
+		}
+	}
+	diagram
+}
+
+//The counter-clockwise triangles of the convex hull of the points at
+//`indices`, or `None` if they are all on one plane. Points that end up
+//inside, or too close to the hull to tell, are left out.
+fn convex_hull(points : &[Vector3], indices : &[usize]) -> Option<Vec<[usize; 3]>> {
+	if indices.len() < 4 {
+		return None;
+	}
+	let p = |i : usize| points[i];
+	let first = indices[0];
+	let second = *indices.iter().max_by(|&&a, &&b| {
+		(p(a) - p(first)).length().partial_cmp(&(p(b) - p(first)).length()).unwrap()
+	})?;
+	let off_line = |i : usize| (p(second) - p(first)).cross(&(p(i) - p(first))).length();
+	let third = *indices.iter().max_by(|&&a, &&b| off_line(a).partial_cmp(&off_line(b)).unwrap())?;
+	let normal = (p(second) - p(first)).cross(&(p(third) - p(first)));
+	let volume = |i : usize| normal.dot(&(p(i) - p(first)));
+	let fourth = *indices.iter().max_by(|&&a, &&b| volume(a).abs().partial_cmp(&volume(b).abs()).unwrap())?;
+	if volume(fourth).abs() < 1e-12 {
+		return None;
+	}
+
+	let inside = (p(first) + p(second) + p(third) + p(fourth)) * 0.25;
+	let outward = |t : [usize; 3]| {
+		let normal = (p(t[1]) - p(t[0])).cross(&(p(t[2]) - p(t[0])));
+		if normal.dot(&(inside - p(t[0]))) > 0.0 { [t[0], t[2], t[1]] } else { t }
+	};
+	let mut triangles : Vec<[usize; 3]> = vec![
+		outward([first, second, third]),
+		outward([first, second, fourth]),
+		outward([first, third, fourth]),
+		outward([second, third, fourth]),
+	];
+
+	for &i in indices {
+		if i == first || i == second || i == third || i == fourth {
+			continue;
+		}
+		let sees = |t : &[usize; 3]| {
+			let normal = (p(t[1]) - p(t[0])).cross(&(p(t[2]) - p(t[0])));
+			normal.dot(&(p(i) - p(t[0]))) > 1e-15 * normal.length()
+		};
+		let (visible, hidden) : (Vec<[usize; 3]>, Vec<[usize; 3]>) = triangles.into_iter().partition(|t| sees(t));
+		triangles = hidden;
+		if visible.is_empty() {
+			continue;
+		}
+		//The sides of the visible triangles that no other visible one shares
+		//ring the hole, which the new point closes
+		let sides : HashSet<(usize, usize)> = visible.iter()
+			.flat_map(|t| (0..3).map(move |k| (t[k], t[(k + 1) % 3])))
+			.collect();
+		for &(a, b) in &sides {
+			if !sides.contains(&(b, a)) {
+				triangles.push([a, b, i]);
+			}
+		}
+	}
+	Some(triangles)
+}
+
+//The signed area of the spherical triangle with corners at unit vectors
+fn triangle_area(a : &Vector3, b : &Vector3, c : &Vector3) -> f64 {
+	2.0 * a.dot(&b.cross(c)).atan2(1.0 + a.dot(b) + b.dot(c) + c.dot(a))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rand::{Rng, SeedableRng, XorShiftRng};
+	use std::f64::consts::PI;
+
+	fn random_sites(rng : &mut XorShiftRng, count : usize) -> Vec<GeoPoint> {
+		(0..count)
+			.map(|_| GeoPoint::new(rng.gen_range(-1.0f64, 1.0).asin().to_degrees(), rng.gen_range(-180.0, 180.0)))
+			.collect()
+	}
+
+	#[test]
+	fn test_cells_cover_the_sphere() {
+		let mut rng = XorShiftRng::from_seed([3, 6, 9, 12]);
+		for &count in &[4, 5, 10, 50, 300] {
+			let sites = random_sites(&mut rng, count);
+			let diagram = spherical_voronoi(&sites);
+			assert!(diagram.empty_cells.is_empty());
+
+			let total : f64 = (0..count).map(|i| diagram.area(SiteId(i as u32))).sum();
+			assert!((total - 4.0 * PI).abs() < 1e-9, "cells cover {}", total);
+
+			//Each corner is nearest to the site and the two across its sides
+			for (index, cell) in diagram.cells.iter().enumerate() {
+				for (i, corner) in cell.iter().enumerate() {
+					let nearest = sites.iter().map(|site| site.angle_to(corner)).fold(f64::INFINITY, f64::min);
+					let before = diagram.neighbours[index][i];
+					assert!((sites[index].angle_to(corner) - nearest).abs() < 1e-9);
+					assert!((sites[before.index()].angle_to(corner) - nearest).abs() < 1e-9);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_octahedron() {
+		//Six sites at the poles and on the equator have equal cells, with
+		//corners where the cube through them has its corners
+		let sites = vec![
+			GeoPoint::new(90.0, 0.0), GeoPoint::new(-90.0, 0.0),
+			GeoPoint::new(0.0, 0.0), GeoPoint::new(0.0, 90.0), GeoPoint::new(0.0, 180.0), GeoPoint::new(0.0, -90.0),
+			GeoPoint::new(0.0, 360.0),
+		];
+		let diagram = spherical_voronoi(&sites);
+		assert_eq!(diagram.empty_cells, vec![SiteId(6)]);
+		for index in 0..6 {
+			assert_eq!(diagram.cells[index].len(), 4);
+			assert!((diagram.area(SiteId(index as u32)) - 4.0 * PI / 6.0).abs() < 1e-12);
+		}
+		let mut around_pole = diagram.neighbours[0].clone();
+		around_pole.sort();
+		assert_eq!(around_pole, vec![SiteId(2), SiteId(3), SiteId(4), SiteId(5)]);
+		let earth = 4.0 * PI * EARTH_RADIUS_KM * EARTH_RADIUS_KM;
+		assert!((diagram.area_km2(SiteId(0)) - earth / 6.0).abs() < 1e-3);
+
+		//All on the equator is one circle, which has no hull
+		assert_eq!(spherical_voronoi(&sites[2..6]).empty_cells.len(), 4);
+	}
+}
@@ -4,24 +4,30 @@
 use std::collections::HashMap;
 use std::fmt;
 
+use geometry::handle::{EdgePtr, FacePtr};
 use geometry::point::Point;
+use voroni::event::EventId;
+
+handle!(
+	/// Points at a `StatusNode` of a `StatusStruct`.
+	NodePtr);
 
 /////////////////////////////////////////////////////////////////
 
 #[derive(Default)]
 pub struct StatusStruct {
     node_index : u32,
-    pub map : HashMap<u32, StatusNode>,
-    pub head : Option<u32>,
+    pub map : HashMap<NodePtr, StatusNode>,
+    pub head : Option<NodePtr>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct StatusNode {
-    index : u32,
+    index : NodePtr,
     pub node_type : NodeType,
-	pub left : Option<u32>,
-	pub right : Option<u32>,
-    pub parent : Option<u32>,
+	pub left : Option<NodePtr>,
+	pub right : Option<NodePtr>,
+    pub parent : Option<NodePtr>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -33,15 +39,15 @@ pub enum NodeType {
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Arc {
 	pub site : Point,
-	pub face_ptr : u32,
-	pub event_ptr : Option<u32>,
+	pub face_ptr : FacePtr,
+	pub event_ptr : Option<EventId>,
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct BreakPoint {
 	pub left_site : Point,
 	pub right_site : Point,
-	pub half_edge : u32,
+	pub half_edge : EdgePtr,
 }
 
 /////////////////////////////////////////////////////////////////
@@ -59,39 +65,39 @@ impl StatusStruct {
 		self.head.is_some()
 	}
 	
-	pub fn new_leaf(&mut self, site : &Point, face_ptr : u32) -> u32 {
-		let new_index = self.node_index;
+	pub fn new_leaf(&mut self, site : &Point, face_ptr : FacePtr) -> NodePtr {
+		let new_index = NodePtr(self.node_index);
 		let leaf = StatusNode::new_leaf(new_index, site, face_ptr);
 		self.map.insert(new_index, leaf);
 		self.node_index += 1;
-		return new_index;
+		new_index
 	}
 	
-	pub fn new_internal(&mut self, break_point : BreakPoint, left : u32, right : u32) -> u32 {
-		let new_index = self.node_index;
+	pub fn new_internal(&mut self, break_point : BreakPoint, left : NodePtr, right : NodePtr) -> NodePtr {
+		let new_index = NodePtr(self.node_index);
 		let internal = StatusNode::new_internal(new_index, break_point, left, right);
 		self.map.insert(new_index, internal);
 		self.node_index += 1;
-		return new_index;
+		new_index
 	}
 	
-	pub fn tree_max(&self, root_ptr : u32) -> u32 {
+	pub fn tree_max(&self, root_ptr : NodePtr) -> NodePtr {
 		let mut iter_node = root_ptr;
 		while let Some(right) = self.get(iter_node).right {
 			iter_node = right;
 		}
-		return iter_node;
+		iter_node
 	}
 	
-	pub fn tree_min(&self, root_ptr : u32) -> u32 {
+	pub fn tree_min(&self, root_ptr : NodePtr) -> NodePtr {
 		let mut iter_node = root_ptr;
 		while let Some(left) = self.get(iter_node).left {
 			iter_node = left;
 		}
-		return iter_node;
+		iter_node
 	}
 	
-	pub fn successor(&self, node_ptr : u32) -> Option<u32> {
+	pub fn successor(&self, node_ptr : NodePtr) -> Option<NodePtr> {
 		//If this node has a right node, then the min of that subtree is successor
 		if let Some(right) = self.get(node_ptr).right {
 			return Some(self.tree_min(right));
@@ -100,41 +106,47 @@ impl StatusStruct {
 		let mut iter_node = Some(node_ptr);
 		let mut iter_parent = self.get(node_ptr).parent;
 		//While the iter has a parent, and that parent's right node is iter
-		while iter_parent.is_some() && iter_node == self.get(iter_parent.unwrap()).right {
+		while let Some(parent) = iter_parent {
+			if iter_node != self.get(parent).right {
+				break;
+			}
 			//Go up the tree
 			iter_node = iter_parent;
-			iter_parent = self.get(iter_parent.unwrap()).parent;
+			iter_parent = self.get(parent).parent;
 		}
-		return iter_parent;
+		iter_parent
 	}
 	
-	pub fn predecessor(&self, node_ptr : u32) -> Option<u32> {
+	pub fn predecessor(&self, node_ptr : NodePtr) -> Option<NodePtr> {
 		if let Some(left) = self.get(node_ptr).left {
 			return Some(self.tree_max(left));
 		}
 		
 		let mut iter_node = Some(node_ptr);
 		let mut iter_parent = self.get(node_ptr).parent;
-		while iter_parent.is_some() && iter_node == self.get(iter_parent.unwrap()).left {
+		while let Some(parent) = iter_parent {
+			if iter_node != self.get(parent).left {
+				break;
+			}
 			iter_node = iter_parent;
-			iter_parent = self.get(iter_parent.unwrap()).parent;
+			iter_parent = self.get(parent).parent;
 		}
-		return iter_parent;
+		iter_parent
 	}
 	
-	pub fn get_left_arc(&self, node_ptr : Option<u32>) -> Option<u32> {
-		return node_ptr
+	pub fn get_left_arc(&self, node_ptr : Option<NodePtr>) -> Option<NodePtr> {
+		node_ptr
 			.and_then(|node| self.predecessor(node))
-			.and_then(|left| self.predecessor(left));
+			.and_then(|left| self.predecessor(left))
 	}
 	
-	pub fn get_right_arc(&self, node_ptr : Option<u32>) -> Option<u32> {
-		return node_ptr
+	pub fn get_right_arc(&self, node_ptr : Option<NodePtr>) -> Option<NodePtr> {
+		node_ptr
 			.and_then(|node| self.successor(node))
-			.and_then(|right| self.successor(right));
+			.and_then(|right| self.successor(right))
 	}
 	
-	pub fn get_left_triple(&self, node_ptr : u32) -> Option<(Point, Point, Point)> {
+	pub fn get_left_triple(&self, node_ptr : NodePtr) -> Option<(Point, Point, Point)> {
         let left_arc = self.get_left_arc(Some(node_ptr));
         let left_left_arc = self.get_left_arc(left_arc);
 
@@ -142,14 +154,10 @@ impl StatusStruct {
         let left_site = self.get_site(left_arc);
         let left_left_site = self.get_site(left_left_arc);
 
-        if this_site.is_some() && left_site.is_some() && left_left_site.is_some() {
-            return Some((left_left_site.unwrap(), left_site.unwrap(), this_site.unwrap()));
-        } else { 
-			return None;
-		}
+        Some((left_left_site?, left_site?, this_site?))
 	}
 	
-	pub fn get_middle_triple(&self, node_ptr : u32) -> Option<(Point, Point, Point)> {
+	pub fn get_middle_triple(&self, node_ptr : NodePtr) -> Option<(Point, Point, Point)> {
         let right_arc = self.get_right_arc(Some(node_ptr));
         let left_arc = self.get_left_arc(Some(node_ptr));
 
@@ -157,14 +165,10 @@ impl StatusStruct {
         let right_site = self.get_site(right_arc);
         let left_site = self.get_site(left_arc);
 
-        if this_site.is_some() && right_site.is_some() && left_site.is_some() {
-            return Some((left_site.unwrap(), this_site.unwrap(), right_site.unwrap()));
-        } else { 
-			return None;
-		}
+        Some((left_site?, this_site?, right_site?))
 	}
 	
-	pub fn get_right_triple(&self, node_ptr : u32) -> Option<(Point, Point, Point)> {
+	pub fn get_right_triple(&self, node_ptr : NodePtr) -> Option<(Point, Point, Point)> {
         let right_arc = self.get_right_arc(Some(node_ptr));
         let right_right_arc = self.get_right_arc(right_arc);
 
@@ -172,14 +176,10 @@ impl StatusStruct {
         let right_site = self.get_site(right_arc);
         let right_right_site = self.get_site(right_right_arc);
 
-        if this_site.is_some() && right_site.is_some() && right_right_site.is_some() {
-            return Some((this_site.unwrap(), right_site.unwrap(), right_right_site.unwrap()));
-        } else { 
-			return None;
-		}
+        Some((this_site?, right_site?, right_right_site?))
 	}
 	
-	pub fn set_right_site(&mut self, node_ptr : u32, site : Point) {
+	pub fn set_right_site(&mut self, node_ptr : NodePtr, site : Point) {
         if let NodeType::Internal(ref mut bp) = self.get_mut(node_ptr).node_type {
             bp.right_site = site;
         } else {
@@ -187,7 +187,7 @@ impl StatusStruct {
         }
     }
 
-    pub fn set_left_site(&mut self, node_ptr : u32, site : Point) {
+    pub fn set_left_site(&mut self, node_ptr : NodePtr, site : Point) {
         if let NodeType::Internal(ref mut bp) = self.get_mut(node_ptr).node_type {
             bp.left_site = site;
         } else {
@@ -195,7 +195,7 @@ impl StatusStruct {
         }
     }
 
-	pub fn get_site(&self, node_ptr_option : Option<u32>) -> Option<Point> {
+	pub fn get_site(&self, node_ptr_option : Option<NodePtr>) -> Option<Point> {
 		match node_ptr_option {
 			Some(node_ptr) => match self.get(node_ptr).node_type {
 				NodeType::Leaf(arc) => Some(arc.site),
@@ -205,15 +205,15 @@ impl StatusStruct {
 		}
 	}
 	
-	pub fn get_mut(&mut self, index : u32) -> &mut StatusNode {
-		return self.map.get_mut(&index).unwrap();
+	pub fn get_mut(&mut self, index : NodePtr) -> &mut StatusNode {
+		self.map.get_mut(&index).unwrap()
 	}
 	
-	pub fn get(&self, index : u32) -> &StatusNode {
-		return self.map.get(&index).unwrap();
+	pub fn get(&self, index : NodePtr) -> &StatusNode {
+		&self.map[&index]
 	}
 
-	fn iter_fmt(&self, string : &mut String, root_ptr : u32) {
+	fn iter_fmt(&self, string : &mut String, root_ptr : NodePtr) {
 		let root_option = self.map.get(&root_ptr);
 		match root_option {
 			Some(root) => {
@@ -233,7 +233,7 @@ impl StatusStruct {
 		}
 	}
 
-	pub fn get_edge(&self, index : u32) -> u32 {
+	pub fn get_edge(&self, index : NodePtr) -> EdgePtr {
 		match self.get(index).node_type {
 			NodeType::Internal(break_point) => break_point.half_edge,
 			_ => panic!("Can't get a half edge from a leaf node!"),
@@ -244,9 +244,8 @@ impl StatusStruct {
 impl fmt::Debug for StatusStruct {
 	fn fmt(&self, f : &mut fmt::Formatter) -> fmt::Result {
 		let mut msg = String::from("\t\tStatus Structure:\n");
-		match self.head {
-			Some(head_ptr) => self.iter_fmt(&mut msg, head_ptr),
-			_ => (),
+		if let Some(head_ptr) = self.head {
+			self.iter_fmt(&mut msg, head_ptr);
 		}
 		write!(f, "{}", msg.as_str())
 	}
@@ -255,9 +254,9 @@ impl fmt::Debug for StatusStruct {
 /////////////////////////////////////////////////////////////////
 
 impl StatusNode {
-	pub fn new_leaf(index : u32, site : &Point, face_ptr : u32) -> StatusNode {
+	pub fn new_leaf(index : NodePtr, site : &Point, face_ptr : FacePtr) -> StatusNode {
 		StatusNode {
-			index : index,
+			index,
 			node_type : NodeType::Leaf(Arc::new(site, face_ptr)),
 			left : None,
 			right : None,
@@ -265,9 +264,9 @@ impl StatusNode {
 		}
 	}
 	
-	pub fn new_internal(index : u32, break_point : BreakPoint, left : u32, right : u32) -> StatusNode {
+	pub fn new_internal(index : NodePtr, break_point : BreakPoint, left : NodePtr, right : NodePtr) -> StatusNode {
 		StatusNode {
-			index : index,
+			index,
 			node_type : NodeType::Internal(break_point),
 			left : Some(left),
 			right : Some(right),
@@ -275,7 +274,7 @@ impl StatusNode {
 		}
 	}
 	
-    pub fn index(&self) -> u32 {
+    pub fn index(&self) -> NodePtr {
         self.index
     }
 }
@@ -293,10 +292,10 @@ impl fmt::Debug for StatusNode {
 /////////////////////////////////////////////////////////////////
 
 impl Arc {
-	pub fn new(site : &Point, face_ptr : u32) -> Arc {
+	pub fn new(site : &Point, face_ptr : FacePtr) -> Arc {
 		Arc {
 			site : *site,
-			face_ptr : face_ptr,
+			face_ptr,
 			event_ptr : None,
 		}
 	}
@@ -1,91 +1,268 @@
 
 #![allow(dead_code)]
 
-use std::collections::BinaryHeap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use geometry::dcel::DoublyConnectedEdgeList;
+use geometry::dcel::{DoublyConnectedEdgeList, FacePtr, VertexPtr};
 use geometry::point::Point;
 use voroni::event::*;
 use voroni::geometry;
 use voroni::status_struct::*;
 
+//Prints a line of sweep progress when the process is verbose
+macro_rules! trace {
+	($process:expr, $($arg:tt)*) => {
+		if $process.verbose {
+			println!($($arg)*);
+		}
+	};
+}
+
 /////////////////////////////////////////////////////////////////
 
 pub enum ParabolaResult {
 	None, //There are no parabolas in the diagram
-	ToLeftOf(u32), //Site is not below an arc, but to the left of one (colinear)
-	ToRightOf(u32), //Site is not below an arc, but to the right of one (colinear)
-	Intersecting(u32), //Site intersects a single arc
+	ToLeftOf(NodePtr), //Site is not below an arc, but to the left of one (colinear)
+	ToRightOf(NodePtr), //Site is not below an arc, but to the right of one (colinear)
+	Intersecting(NodePtr), //Site intersects a single arc
 }
 
 /////////////////////////////////////////////////////////////////
 
-pub struct VoroniProcess {
+/// Computes a Voronoi diagram with Fortune's sweep. Every site carries a
+/// payload of type `F`, which ends up as the data of the site's face.
+///
+/// Sites are identified by their position in the input, as a `SiteId`. The
+/// face of every site can be looked up by id once the sweep has run, and a
+/// site that coincides with an earlier one is recorded as its duplicate
+/// rather than given a face of its own.
+pub struct VoroniProcess<F = ()> {
 	event_queue_index : u32,
-	dcel : DoublyConnectedEdgeList,
+	cancelled_events : HashSet<EventId>,
+	sites : Vec<Point>,
+	site_data : Vec<Option<F>>,
+	site_faces : Vec<Option<FacePtr>>,
+	site_vertices : HashMap<VertexPtr, SiteId>,
+	duplicates : Vec<(SiteId, SiteId)>,
+	dcel : DoublyConnectedEdgeList<(), (), F>,
 	event_queue : BinaryHeap<Event>,
 	status_struct : StatusStruct,
 	iteration : u32,
+	verbose : bool,
 }
 
 impl VoroniProcess {
 	pub fn new(sites : Vec<(i32, i32)>) -> VoroniProcess {
+		VoroniProcess::with_tolerance(sites, 0.0)
+	}
+
+	/// Like `new`, but Voronoi vertices (and sites) closer together than
+	/// `tolerance` are merged into a single DCEL vertex.
+	pub fn with_tolerance(sites : Vec<(i32, i32)>, tolerance : f64) -> VoroniProcess {
+		let sites = sites.into_iter()
+			.map(|site| (Point::new(site.0 as f64, site.1 as f64), ()))
+			.collect();
+		VoroniProcess::with_data(sites, tolerance)
+	}
+}
+
+impl<F> VoroniProcess<F> {
+	/// Creates a process over sites that each carry a payload. The payload of
+	/// a site is moved into the data of its face when the site is swept.
+	pub fn with_data(sites : Vec<(Point, F)>, tolerance : f64) -> VoroniProcess<F> {
 		let mut vp = VoroniProcess {
 			event_queue_index : 0,
-			dcel : DoublyConnectedEdgeList::new(),
+			cancelled_events : HashSet::new(),
+			sites : Vec::with_capacity(sites.len()),
+			site_data : Vec::with_capacity(sites.len()),
+			site_faces : Vec::with_capacity(sites.len()),
+			site_vertices : HashMap::new(),
+			duplicates : Vec::new(),
+			dcel : DoublyConnectedEdgeList::with_tolerance(tolerance),
 			event_queue : BinaryHeap::new(),
 			status_struct : StatusStruct::new(),
 			iteration : 0,
+			verbose : false,
 		};
-		for site in sites {
-			vp.push_site_event(&Point::new(site.0 as f64, site.1 as f64));
+		for (site, data) in sites {
+			vp.push_site_event(&site, data);
 		}
-		return vp;
+		vp
+	}
+
+	/// Prints every event and the beach line after it as the sweep runs.
+	pub fn set_verbose(&mut self, verbose : bool) {
+		self.verbose = verbose;
 	}
 
-	pub fn get_dcel(&self) -> &DoublyConnectedEdgeList {
+	pub fn get_dcel(&self) -> &DoublyConnectedEdgeList<(), (), F> {
 		&self.dcel
 	}
 
+	pub fn into_dcel(self) -> DoublyConnectedEdgeList<(), (), F> {
+		self.dcel
+	}
+
+	/// The input sites, in input order.
+	pub fn sites(&self) -> &[Point] {
+		&self.sites
+	}
+
+	/// The face of the given input site, once the site has been swept. A
+	/// duplicate site maps to the face of the site it duplicates.
+	pub fn get_site_face(&self, site : SiteId) -> Option<FacePtr> {
+		self.site_faces.get(site.index()).and_then(|face| *face)
+	}
+
+	/// Every input site in input order, with its face if it has been swept.
+	pub fn get_cells(&self) -> Vec<(SiteId, Point, Option<FacePtr>)> {
+		self.sites.iter().enumerate()
+			.map(|(index, site)| (SiteId(index as u32), *site, self.site_faces[index]))
+			.collect()
+	}
+
+	/// Pairs of `(duplicate, original)` for every site that coincided with an
+	/// earlier input site and was merged into its face, in input order. The
+	/// payload of a duplicate is dropped.
+	pub fn get_duplicates(&self) -> &[(SiteId, SiteId)] {
+		&self.duplicates
+	}
+
+	/// The sites of the unbounded cells, which are the sites on the convex
+	/// hull, counter-clockwise from the leftmost, lowest one. Unlike
+	/// `polygon::convex_hull`, this keeps sites along the sides of the hull,
+	/// as their cells are unbounded too. If all sites lie on a line, they
+	/// are listed in order along it. Call this once the sweep has run.
+	///
+	/// Gives `None` if the unbounded edges do not join up into one loop
+	/// around the sites, which means the diagram is broken.
+	pub fn convex_hull(&self) -> Option<Vec<SiteId>> {
+		let site_of = |face : Option<FacePtr>| face
+			.and_then(|face| self.dcel[face].site)
+			.and_then(|vertex| self.site_vertices.get(&vertex).cloned());
+		let by_position = |a : &SiteId, b : &SiteId| {
+			let (a, b) = (self.sites[a.index()], self.sites[b.index()]);
+			(a.x(), a.y()).partial_cmp(&(b.x(), b.y())).unwrap_or(Ordering::Equal)
+		};
+		let mut unique : Vec<SiteId> = self.dcel.faces.iter().filter_map(|face| site_of(Some(face.index()))).collect();
+		let first = match unique.first() {
+			Some(site) => self.sites[site.index()],
+			None => return Some(unique),
+		};
+		let collinear = match unique.iter().map(|site| self.sites[site.index()]).find(|&site| site != first) {
+			Some(second) => unique.iter().all(|site| (second - first).cross(self.sites[site.index()] - first) == 0.0),
+			None => true,
+		};
+		if collinear {
+			//Every cell is unbounded, and there is no loop to follow
+			unique.sort_by(&by_position);
+			return Some(unique);
+		}
+		let inside = unique.iter().fold(Point::new(0.0, 0.0), |sum, site| sum + self.sites[site.index()]) * (1.0 / unique.len() as f64);
+
+		//Every unbounded edge runs between two sites next to each other on the
+		//hull, and the one with the rest of the sites to its left comes first
+		let mut next : HashMap<SiteId, SiteId> = HashMap::new();
+		let mut hull_sites : Vec<SiteId> = Vec::new();
+		for edge in self.dcel.edges.iter().filter(|edge| edge.is_inf) {
+			let twin_face = edge.twin.and_then(|twin| self.dcel[twin].incident_face);
+			if let (Some(a), Some(b)) = (site_of(edge.incident_face), site_of(twin_face)) {
+				let (pa, pb) = (self.sites[a.index()], self.sites[b.index()]);
+				if (pb - pa).cross(inside - pa) > 0.0 {
+					next.insert(a, b);
+				}
+				hull_sites.push(a);
+			}
+		}
+		hull_sites.sort_by(&by_position);
+		hull_sites.dedup();
+
+		let mut hull = Vec::with_capacity(hull_sites.len());
+		if let Some(&start) = hull_sites.first() {
+			let mut current = start;
+			while hull.len() < hull_sites.len() {
+				hull.push(current);
+				match next.get(&current) {
+					Some(&site) if site != start => current = site,
+					_ => break,
+				}
+			}
+		}
+		if hull.len() == hull_sites.len() && hull.len() >= 3 {
+			Some(hull)
+		} else {
+			None
+		}
+	}
+
 	pub fn step(&mut self) -> bool {
 		let event_option = self.event_queue.pop();
 
 		match event_option {
 			Some(event) => match event {
-				Event::Site(site) => {
-					println!("Iteration {}: Site event at {:?}", self.iteration, site);
-					self.process_site(site);
+				Event::Site(site, site_id) => {
+					trace!(self, "Iteration {}: Site event at {:?}", self.iteration, site);
+					self.process_site(site, site_id);
 				},
-				Event::Circle(center, radius, status_pointer, event_pointer, is_valid) => {
-					if is_valid.value {
-						println!("Iteration {}: Circle event at {:?}, radius of {}", self.iteration, center, radius);
+				Event::Circle(center, radius, status_pointer, event_pointer) => {
+					if !self.cancelled_events.remove(&event_pointer) {
+						trace!(self, "Iteration {}: Circle event at {:?}, radius of {}", self.iteration, center, radius);
 						self.process_circle(center, status_pointer, event_pointer);
 					}
 				},
 			},
-			None => return false,
+			None => {
+				self.mark_infinite_edges();
+				return false;
+			},
 		}
-		println!("\n{:?}",self.status_struct);
+		trace!(self, "\n{:?}", self.status_struct);
 		self.iteration += 1;
 
-		return true;
+		true
 	}
 
 	pub fn execute(&mut self) {
 		while self.step() {}
 	}
 
-	pub fn push_circle_event(&mut self, center : &Point, radius : f64, status_pointer : u32) {
-		let new_event = Event::Circle(*center, radius, status_pointer, self.event_queue_index, IsValid::new());
+	//Edges still traced by a breakpoint when the sweep ends never get their
+	//far endpoint, and are unbounded
+	fn mark_infinite_edges(&mut self) {
+		for index in 0..self.dcel.edges.len() {
+			let edge = self.dcel.edges[index];
+			let twin_origin = edge.twin.and_then(|twin| self.dcel[twin].origin);
+			if edge.origin.is_none() || twin_origin.is_none() {
+				self.dcel.edges[index].is_inf = true;
+			}
+		}
+	}
+
+	pub fn push_circle_event(&mut self, center : &Point, radius : f64, status_pointer : NodePtr) -> EventId {
+		let event_id = EventId(self.event_queue_index);
 		self.event_queue_index += 1;
-		self.event_queue.push(new_event);
+		self.event_queue.push(Event::Circle(*center, radius, status_pointer, event_id));
+		event_id
 	}
 
-	pub fn push_site_event(&mut self, site : &Point) {
-		self.event_queue.push(Event::Site(*site));
+	/// Adds a site to the sweep and returns its id, the number of sites
+	/// pushed before it. A site with a NaN or infinite coordinate still gets
+	/// an id, but is never swept and so never gets a face.
+	pub fn push_site_event(&mut self, site : &Point, data : F) -> SiteId {
+		let site_id = SiteId(self.sites.len() as u32);
+		self.sites.push(*site);
+		self.site_faces.push(None);
+		if site.x().is_finite() && site.y().is_finite() {
+			self.event_queue.push(Event::Site(*site, site_id));
+			self.site_data.push(Some(data));
+		} else {
+			self.site_data.push(None);
+		}
+		site_id
 	}
 
-	fn process_site(&mut self, new_site : Point) {
+	fn process_site(&mut self, new_site : Point, site_id : SiteId) {
 		/*
 		3. Replace the leaf of 'T' that represents 'a' with a subtree having 3
 		leaves. The middle leaf stores the new site p_i and the other two leaves
@@ -100,28 +277,42 @@ impl VoroniProcess {
 		new breakpoints.
 		*/
 
-		let new_vertex = self.dcel.new_vertex(&new_site);
-		self.dcel.get_vertex(new_vertex).is_site = true;
-		let new_face = self.dcel.new_face(Some(new_vertex));
+		if let Some(original) = self.dcel.find_site(&new_site)
+				.and_then(|vertex| self.site_vertices.get(&vertex).cloned()) {
+			trace!(self, "\tThe site duplicates site {}", original);
+			self.site_faces[site_id.index()] = self.site_faces[original.index()];
+			self.site_data[site_id.index()] = None;
+			//Keep the duplicates listed in input order rather than sweep order
+			let position = self.duplicates.binary_search(&(site_id, original)).unwrap_or_else(|p| p);
+			self.duplicates.insert(position, (site_id, original));
+			return;
+		}
+
+		let data = self.site_data[site_id.index()].take()
+			.expect("Site payloads are only taken once");
+		let new_vertex = self.dcel.new_site_vertex(&new_site);
+		let new_face = self.dcel.new_face_with_data(Some(new_vertex), data);
+		self.site_vertices.insert(new_vertex, site_id);
+		self.site_faces[site_id.index()] = Some(new_face);
 		let parabola_result = self.get_parabola_by_x(&new_site, new_site.y());
 
 		let (new_root_ptr, new_site_ptr, replace_ptr_option) = match parabola_result {
 			ParabolaResult::ToLeftOf(leaf_ptr) => {
-				println!("\tThe site event intersected parabola {} once on the left", leaf_ptr);
+				trace!(self, "\tThe site event intersected parabola {} once on the left", leaf_ptr);
 				/*        lr
 						/    \
 					   l	  r		
 				*/
 
 				let (old_site, old_face) = match self.status_struct.get(leaf_ptr).node_type {
-					NodeType::Internal(break_point) => panic!("We should not be intersecting an internal node!"),
+					NodeType::Internal(_) => panic!("We should not be intersecting an internal node!"),
 					NodeType::Leaf(arc) => (arc.site, arc.face_ptr),};
 
 				let (new_edge_o, new_edge_n) = self.dcel.new_dual_edges(None, None);
-				self.dcel.get_face(new_face).outer_component.push(new_edge_n);
-				self.dcel.get_face(old_face).outer_component.push(new_edge_o);
-				self.dcel.get_edge(new_edge_n).incident_face = Some(new_face);
-				self.dcel.get_edge(new_edge_o).incident_face = Some(old_face);
+				self.dcel[new_face].outer_component.push(new_edge_n);
+				self.dcel[old_face].outer_component.push(new_edge_o);
+				self.dcel[new_edge_n].incident_face = Some(new_face);
+				self.dcel[new_edge_o].incident_face = Some(old_face);
 
 				let left_ptr = self.status_struct.new_leaf(&new_site, new_face);
 				let right_ptr = self.status_struct.new_leaf(&old_site, old_face);
@@ -139,21 +330,21 @@ impl VoroniProcess {
 				(lr_ptr, left_ptr, Some(leaf_ptr))
 			},
 			ParabolaResult::ToRightOf(leaf_ptr) => {
-				println!("\tThe site event intersected parabola {} once on the right", leaf_ptr);
+				trace!(self, "\tThe site event intersected parabola {} once on the right", leaf_ptr);
 				/*        lr
 						/    \
 					   l	  r		
 				*/
 
 				let (old_site, old_face) = match self.status_struct.get(leaf_ptr).node_type {
-					NodeType::Internal(break_point) => panic!("We should not be intersecting an internal node!"),
+					NodeType::Internal(_) => panic!("We should not be intersecting an internal node!"),
 					NodeType::Leaf(arc) => (arc.site, arc.face_ptr),};
 
 				let (new_edge_o, new_edge_n) = self.dcel.new_dual_edges(None, None);
-				self.dcel.get_face(new_face).outer_component.push(new_edge_n);
-				self.dcel.get_face(old_face).outer_component.push(new_edge_o);
-				self.dcel.get_edge(new_edge_n).incident_face = Some(new_face);
-				self.dcel.get_edge(new_edge_o).incident_face = Some(old_face);
+				self.dcel[new_face].outer_component.push(new_edge_n);
+				self.dcel[old_face].outer_component.push(new_edge_o);
+				self.dcel[new_edge_n].incident_face = Some(new_face);
+				self.dcel[new_edge_o].incident_face = Some(old_face);
 
 				let left_ptr = self.status_struct.new_leaf(&old_site, old_face);
 				let right_ptr = self.status_struct.new_leaf(&new_site, new_face);
@@ -171,7 +362,7 @@ impl VoroniProcess {
 				(lr_ptr, right_ptr, Some(leaf_ptr))
 			},
 			ParabolaResult::Intersecting(leaf_ptr) => {
-				println!("\tThe site event intersected parabola {} in the middle", leaf_ptr);
+				trace!(self, "\tThe site event intersected parabola {} in the middle", leaf_ptr);
 				/*
 				          ir
 						/    \
@@ -180,13 +371,13 @@ impl VoroniProcess {
 				     l    m 		
 				*/
 				let (old_site, old_face) = match self.status_struct.get(leaf_ptr).node_type {
-					NodeType::Internal(break_point) => panic!("We should not be intersecting an internal node!"),
+					NodeType::Internal(_) => panic!("We should not be intersecting an internal node!"),
 					NodeType::Leaf(arc) => (arc.site, arc.face_ptr),};
 				let (new_edge_n, new_edge_o) = self.dcel.new_dual_edges(None, None);
-				self.dcel.get_face(new_face).outer_component.push(new_edge_n);
-				self.dcel.get_face(old_face).outer_component.push(new_edge_o);
-				self.dcel.get_edge(new_edge_n).incident_face = Some(new_face);
-				self.dcel.get_edge(new_edge_o).incident_face = Some(old_face);
+				self.dcel[new_face].outer_component.push(new_edge_n);
+				self.dcel[old_face].outer_component.push(new_edge_o);
+				self.dcel[new_edge_n].incident_face = Some(new_face);
+				self.dcel[new_edge_o].incident_face = Some(old_face);
 				let left_ptr = self.status_struct.new_leaf(&old_site, old_face);
 				let middle_ptr = self.status_struct.new_leaf(&new_site, new_face);
 				let right_ptr = self.status_struct.new_leaf(&old_site, old_face);
@@ -212,13 +403,18 @@ impl VoroniProcess {
 				(ir_ptr, middle_ptr, Some(leaf_ptr))
 			},
 			ParabolaResult::None => {
-				println!("\tThe site event didn't intersect anything");
+				trace!(self, "\tThe site event didn't intersect anything");
 				let new_leaf_ptr = self.status_struct.new_leaf(&new_site, new_face);
 				
 				(new_leaf_ptr, new_leaf_ptr, None)
 			},
 		};
 
+		//The arc being split can no longer disappear at its old circle event
+		if let Some(replace_ptr) = replace_ptr_option {
+			self.remove_circle_event(replace_ptr);
+		}
+
 		//Time to replace the old arc with our new structure
 		match replace_ptr_option {
 			Some(replace_ptr) => match self.status_struct.get(replace_ptr).parent {
@@ -243,43 +439,35 @@ impl VoroniProcess {
 		Do the same for the triple where the new arc is the right arc.
 		*/
 		if let Some(left_triple) = self.status_struct.get_left_triple(new_site_ptr) {
-			println!("\tChecking leftward triple {:?} {:?} {:?}", left_triple.0, left_triple.1, left_triple.2);
+			trace!(self, "\tChecking leftward triple {:?} {:?} {:?}", left_triple.0, left_triple.1, left_triple.2);
 			if geometry::is_clockwise(&left_triple)  {
-				println!("\tThe leftward triple converges!");
+				trace!(self, "\tThe leftward triple converges!");
 				let left_arc = self.status_struct.get_left_arc(Some(new_site_ptr)).unwrap();
 				self.make_circle_event(left_arc, &left_triple);
 			}
 		}
 		if let Some(right_triple) = self.status_struct.get_right_triple(new_site_ptr) {
-			println!("\tChecking rightward triple {:?} {:?} {:?}", right_triple.0, right_triple.1, right_triple.2);
+			trace!(self, "\tChecking rightward triple {:?} {:?} {:?}", right_triple.0, right_triple.1, right_triple.2);
 			if geometry::is_clockwise(&right_triple)  {
-				println!("\tThe rightward triple converges!");
+				trace!(self, "\tThe rightward triple converges!");
 				let right_arc = self.status_struct.get_right_arc(Some(new_site_ptr)).unwrap();
 				self.make_circle_event(right_arc, &right_triple);
 			}
 		}
 	}
 
-	fn remove_circle_event(&mut self, leaf_ptr : u32) {
+	fn remove_circle_event(&mut self, leaf_ptr : NodePtr) {
 		let mut circle_event = None;
 		if let NodeType::Leaf(ref mut arc) = self.status_struct.get_mut(leaf_ptr).node_type {
 			circle_event = arc.event_ptr;
 			arc.event_ptr = None;
 		}
 		if let Some(circle_event_ptr) = circle_event {
-			for event in &self.event_queue {
-				match event {
-					&Event::Circle(_,_,_, event_id, mut is_valid) => if event_id == circle_event_ptr {
-						is_valid.value = false;
-						break;
-					}
-					_ => (),
-				}
-			}
+			self.cancelled_events.insert(circle_event_ptr);
 		}
 	}
 
-	fn process_circle(&mut self, center : Point, leaf_middle_ptr : u32, event_pointer : u32) {
+	fn process_circle(&mut self, center : Point, leaf_middle_ptr : NodePtr, _event_pointer : EventId) {
 		
 		/*
 		1. Delete the leaf γ that represents the disappearing arc α from T. 
@@ -294,8 +482,8 @@ impl VoroniProcess {
 		let leaf_left_ptr = self.status_struct.get_left_arc(Some(leaf_middle_ptr)).unwrap();
 		let leaf_right_ptr = self.status_struct.get_right_arc(Some(leaf_middle_ptr)).unwrap();
 
-		let (pred_ptr, succ_ptr, parent_ptr, other_ptr) = {
-			let ref mut ss = &mut self.status_struct;
+		let (pred_ptr, succ_ptr, other_ptr) = {
+			let ss = &mut self.status_struct;
 
 			let pred_ptr = ss.predecessor(leaf_middle_ptr).unwrap();
 			let succ_ptr = ss.successor(leaf_middle_ptr).unwrap();
@@ -343,7 +531,7 @@ impl VoroniProcess {
 				ss.set_left_site(other_ptr, new_site);
 			}
 
-			(pred_ptr, succ_ptr, parent_ptr, other_ptr)
+			(pred_ptr, succ_ptr, other_ptr)
 		};
 
 		self.remove_circle_event(leaf_left_ptr);
@@ -358,34 +546,33 @@ impl VoroniProcess {
 		end at the vertex.
 		*/
 
-		let (twin1, twin2) = self.dcel.new_dual_edges(None, None);
+		let center_vertex_ptr = self.dcel.new_vertex(&center);
+
+		//The new breakpoint traces an edge between the left and right arcs. As
+		//with every breakpoint, its half-edge lies in the face of the left arc
+		//and has its origin where the breakpoint will stop; the twin starts here.
+		let (left_edge, right_edge) = self.dcel.new_dual_edges(None, Some(center_vertex_ptr));
 		if let NodeType::Leaf(arc) = self.status_struct.get(leaf_left_ptr).node_type {
-			self.dcel.get_edge(twin1).incident_face = Some(arc.face_ptr);
+			self.dcel[left_edge].incident_face = Some(arc.face_ptr);
 		}
 		if let NodeType::Leaf(arc) = self.status_struct.get(leaf_right_ptr).node_type {
-			self.dcel.get_edge(twin2).incident_face = Some(arc.face_ptr);
+			self.dcel[right_edge].incident_face = Some(arc.face_ptr);
 		}
-
-		let center_vertex_ptr = self.dcel.new_vertex(&center);
-		self.dcel.get_vertex(center_vertex_ptr).incident_edge = Some(twin1);
-
+		self.dcel[center_vertex_ptr].incident_edge = Some(right_edge);
 
 		// hook up next pointers on halfedges
 		let pred_edge = self.status_struct.get_edge(pred_ptr);
 		let succ_edge = self.status_struct.get_edge(succ_ptr);
-		let parent_edge = self.status_struct.get_edge(parent_ptr);
-		let other_edge = self.status_struct.get_edge(other_ptr);
 
-		let pred_edge_twin = self.dcel.get_edge(pred_edge).twin.unwrap();
-		let succ_edge_twin = self.dcel.get_edge(succ_edge).twin.unwrap();
+		let pred_edge_twin = self.dcel[pred_edge].twin.unwrap();
+		let succ_edge_twin = self.dcel[succ_edge].twin.unwrap();
 
-		self.dcel.get_edge(parent_edge).origin = Some(center_vertex_ptr);
-		self.dcel.get_edge(other_edge).origin = Some(center_vertex_ptr);
-		self.dcel.get_edge(twin1).origin = Some(center_vertex_ptr);
+		self.dcel[pred_edge].origin = Some(center_vertex_ptr);
+		self.dcel[succ_edge].origin = Some(center_vertex_ptr);
 
-		self.dcel.get_edge(pred_edge_twin).next = Some(succ_edge);
-		self.dcel.get_edge(succ_edge_twin).next = Some(twin1);
-		self.dcel.get_edge(twin2).next = Some(pred_edge);
+		self.dcel.link_edges(left_edge, pred_edge);
+		self.dcel.link_edges(pred_edge_twin, succ_edge);
+		self.dcel.link_edges(succ_edge_twin, right_edge);
 
 		/*
 		3. Check the new triple of consecutive arcs that has the former left 
@@ -397,20 +584,20 @@ impl VoroniProcess {
 		*/
 
 		if let NodeType::Internal(ref mut break_point) = self.status_struct.get_mut(other_ptr).node_type {
-			break_point.half_edge = twin2;
+			break_point.half_edge = left_edge;
 		}
 
 		if let Some(left_triple) = self.status_struct.get_middle_triple(leaf_left_ptr) {
-			println!("\tChecking leftward triple {:?}, {:?}, {:?}", left_triple.0, left_triple.1, left_triple.2);
+			trace!(self, "\tChecking leftward triple {:?}, {:?}, {:?}", left_triple.0, left_triple.1, left_triple.2);
 			if geometry::is_clockwise(&left_triple) {
-				println!("\tFound converging triple");
+				trace!(self, "\tFound converging triple");
 				self.make_circle_event(leaf_left_ptr, &left_triple);
 			}
 		}
 		if let Some(right_triple) = self.status_struct.get_middle_triple(leaf_right_ptr) {
-			println!("\tChecking rightward triple {:?}, {:?}, {:?}", right_triple.0, right_triple.1, right_triple.2);
+			trace!(self, "\tChecking rightward triple {:?}, {:?}, {:?}", right_triple.0, right_triple.1, right_triple.2);
 			if geometry::is_clockwise(&right_triple) {
-				println!("\tFound converging triple");
+				trace!(self, "\tFound converging triple");
 				self.make_circle_event(leaf_right_ptr, &right_triple);
 			}
 		}
@@ -418,7 +605,7 @@ impl VoroniProcess {
 
 	fn get_parabola_by_x(&mut self, site : &Point, line_y : f64) -> ParabolaResult {
 
-		let mut ss = &mut self.status_struct;
+		let ss = &mut self.status_struct;
 	
 		//If there is no head node, there is nothing we can point to
 		let mut iter_ptr = match ss.head {
@@ -472,11 +659,133 @@ impl VoroniProcess {
 		}
 	}
 
-	fn make_circle_event(&mut self, leaf_ptr : u32, triple : &(Point, Point, Point)) {
+	fn make_circle_event(&mut self, leaf_ptr : NodePtr, triple : &(Point, Point, Point)) {
 		if let Some(center) = geometry::get_circle_center(triple) {
-			println!("\tMaking a new circle event at {:?}", center);
+			trace!(self, "\tMaking a new circle event at {:?}", center);
 			let radius = geometry::get_distance(&center, &triple.0);
-			self.push_circle_event(&center, radius, leaf_ptr);
+			self.remove_circle_event(leaf_ptr);
+			let event_id = self.push_circle_event(&center, radius, leaf_ptr);
+			if let NodeType::Leaf(ref mut arc) = self.status_struct.get_mut(leaf_ptr).node_type {
+				arc.event_ptr = Some(event_id);
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::fs;
+	use std::path::Path;
+	use file::io::{parse_points, read_site_file};
+	use generate::distributions;
+	use geometry::polygon;
+	use rand::{SeedableRng, XorShiftRng};
+
+	#[test]
+	fn test_site_data_reaches_faces() {
+		let sites = vec![
+			(Point::new(4.0, 4.0), "a"),
+			(Point::new(8.0, 4.0), "b"),
+			(Point::new(6.0, 0.0), "c"),
+		];
+		let mut vp = VoroniProcess::with_data(sites.clone(), 0.0);
+		vp.execute();
+		let dcel = vp.into_dcel();
+
+		assert_eq!(dcel.faces.len(), 3);
+		for (site, data) in sites {
+			let face = dcel.faces.iter()
+				.find(|face| face.site.map(|ptr| dcel[ptr].point) == Some(site))
+				.unwrap();
+			assert_eq!(face.data, data);
+		}
+	}
+
+	#[test]
+	fn test_duplicate_sites_share_faces() {
+		let mut vp = VoroniProcess::new(vec![(8, 4), (4, 4), (8, 4), (6, 0), (4, 4)]);
+		vp.execute();
+
+		assert_eq!(vp.get_dcel().faces.len(), 3);
+		assert_eq!(vp.get_duplicates(), &[(SiteId(2), SiteId(0)), (SiteId(4), SiteId(1))][..]);
+		assert_eq!(vp.get_site_face(SiteId(2)), vp.get_site_face(SiteId(0)));
+		assert_eq!(vp.get_site_face(SiteId(4)), vp.get_site_face(SiteId(1)));
+
+		let cells = vp.get_cells();
+		assert_eq!(cells.len(), 5);
+		for (index, &(site_id, point, face)) in cells.iter().enumerate() {
+			assert_eq!(site_id, SiteId(index as u32));
+			let face = face.unwrap();
+			assert_eq!(vp.get_dcel()[vp.get_dcel()[face].site.unwrap()].point, point);
+		}
+	}
+
+	#[test]
+	fn test_fuzz_regressions() {
+		//Inputs that once broke the sweep, found by the fuzz targets
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions/sweep");
+		for entry in fs::read_dir(dir).unwrap() {
+			let path = entry.unwrap().path();
+			let sites : Vec<_> = parse_points(&fs::read_to_string(&path).unwrap())
+				.into_iter()
+				.map(|site| (site, ()))
+				.collect();
+			for &tolerance in &[0.0, 1e-9] {
+				let mut vp = VoroniProcess::with_data(sites.clone(), tolerance);
+				vp.execute();
+				if let Err(message) = vp.get_dcel().validate() {
+					panic!("{} with tolerance {}: {}", path.display(), tolerance, message);
+				}
+			}
 		}
 	}
+
+	#[test]
+	fn test_vertices_do_not_snap_to_sites() {
+		//The circumcentre lies within the tolerance of every site
+		let sites = vec![(Point::new(0.0, 0.0), ()), (Point::new(1.5, 0.0), ()), (Point::new(0.75, 1.3), ())];
+		let mut vp = VoroniProcess::with_data(sites, 1.0);
+		vp.execute();
+		let dcel = vp.get_dcel();
+		assert_eq!(dcel.validate(), Ok(()));
+		assert_eq!(dcel.vertices.iter().filter(|v| v.is_site).count(), 3);
+		assert_eq!(dcel.vertices.iter().filter(|v| !v.is_site).count(), 1);
+		assert!(vp.get_duplicates().is_empty());
+	}
+
+	#[test]
+	fn test_site_files_validate() {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+		for entry in fs::read_dir(dir).unwrap() {
+			let path = entry.unwrap().path();
+			if path.extension().is_some_and(|ext| ext == "txt") {
+				let mut vp = VoroniProcess::new(read_site_file(&path).unwrap());
+				vp.execute();
+				if let Err(message) = vp.get_dcel().validate() {
+					panic!("{}: {}", path.display(), message);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn test_convex_hull_from_unbounded_cells() {
+		//A grid keeps the sites along the sides of its hull
+		let mut vp = VoroniProcess::new(vec![(1, 1), (0, 0), (2, 2), (1, 0), (0, 2), (2, 0), (0, 1), (2, 1), (1, 2)]);
+		vp.execute();
+		let hull : Vec<u32> = vp.convex_hull().unwrap().into_iter().map(|site| site.0).collect();
+		assert_eq!(hull, vec![1, 3, 5, 7, 2, 8, 4, 6]);
+
+		let mut vp = VoroniProcess::new(vec![(3, 3), (1, 1), (2, 2)]);
+		vp.execute();
+		assert_eq!(vp.convex_hull(), Some(vec![SiteId(1), SiteId(2), SiteId(0)]));
+
+		let mut rng = XorShiftRng::from_seed([3, 1, 4, 1]);
+		let sites = distributions::uniform_box(&mut rng, 200, &Point::new(0.0, 0.0), &Point::new(10.0, 10.0));
+		let expected : Vec<SiteId> = polygon::convex_hull_indices(&sites).into_iter().map(|i| SiteId(i as u32)).collect();
+		let mut vp = VoroniProcess::with_data(sites.into_iter().map(|site| (site, ())).collect(), 0.0);
+		vp.execute();
+		assert_eq!(vp.convex_hull(), Some(expected));
+	}
 }